@@ -7,6 +7,8 @@ pub struct AuthBadgeData {}
 pub struct PriceInfo {
     pub timestamp: i64,
     pub price: Decimal,
+    /// Price uncertainty in both directions, when provided by the price feed
+    pub confidence: Option<Decimal>,
 }
 
 #[blueprint]
@@ -4,6 +4,8 @@ use scrypto::prelude::*;
 pub struct PriceInfo {
     pub timestamp: i64,
     pub price: Decimal,
+    /// Price uncertainty in both directions, when provided by the price feed
+    pub confidence: Option<Decimal>,
 }
 
 #[derive(ScryptoSbor, NonFungibleData, Clone)]
@@ -38,6 +40,19 @@ pub enum DepositType {
     LiquidityAddition,
 }
 
+#[derive(ScryptoSbor, Clone, Copy, PartialEq, Debug)]
+pub enum PoolFeeDestination {
+    Pool,
+    FeeVault,
+}
+
+#[derive(ScryptoSbor, Clone, Copy, PartialEq, Debug)]
+pub struct PoolFeeConfig {
+    pub contribution_fee_rate: Decimal,
+    pub redemption_fee_rate: Decimal,
+    pub fee_destination: PoolFeeDestination,
+}
+
 #[blueprint]
 pub mod staking_pool {
 
@@ -53,6 +68,8 @@ pub mod staking_pool {
                 admin_rule: AccessRule,
                 contribute_rule: AccessRule,
                 redeem_rule: AccessRule,
+                fee_config: PoolFeeConfig,
+                dapp_definition: Option<ComponentAddress>,
             ) -> (Global<SingleResourcePool>, ResourceAddress);
 
             fn contribute(&self, assets: Bucket) -> Bucket;
@@ -72,7 +89,9 @@ pub mod staking_pool {
 
             fn get_pool_unit_ratio(&self) -> PreciseDecimal;
 
-            fn get_pooled_amount(&self) -> (Decimal,Decimal);
+            fn get_pooled_amount(&self) -> (Decimal, Decimal, Decimal);
+
+            fn update_fee_config(&mut self, fee_config: PoolFeeConfig);
 
         }
     );
@@ -83,6 +102,7 @@ pub mod staking_pool {
         },
         methods {
             register_market_component => restrict_to: [admin];
+            update_unstake_fee_rate => restrict_to: [admin];
 
             withdraw_collected_fees => PUBLIC;
             deposit_converted_fees => PUBLIC;
@@ -141,6 +161,7 @@ pub mod staking_pool {
             owner_rule: AccessRule,
             pool_res_address: ResourceAddress,
             price_feed_comp_address: ComponentAddress,
+            unstake_fee_rate: Decimal,
         ) -> (Global<StakingPool>, Bucket) {
             //
             let (address_reservation, own_component_address) =
@@ -159,6 +180,13 @@ pub mod staking_pool {
                 component_rule.clone(),
                 component_rule.clone(),
                 component_rule,
+                // The unstake fee stays in the pool and accrues to the remaining stakers
+                PoolFeeConfig {
+                    contribution_fee_rate: dec!(0),
+                    redemption_fee_rate: unstake_fee_rate,
+                    fee_destination: PoolFeeDestination::Pool,
+                },
+                None,
             );
 
             let (treasury, treasury_owner_badge) = Blueprint::<Account>::create();
@@ -216,6 +244,15 @@ pub mod staking_pool {
                 .insert(len, (market_component_address.into(), fee_collerctor_badge));
         }
 
+        /// Update the fee taken from the unstaked assets, left in the pool for the remaining stakers
+        pub fn update_unstake_fee_rate(&mut self, unstake_fee_rate: Decimal) {
+            self.asset_pool.update_fee_config(PoolFeeConfig {
+                contribution_fee_rate: dec!(0),
+                redemption_fee_rate: unstake_fee_rate,
+                fee_destination: PoolFeeDestination::Pool,
+            });
+        }
+
         // * User methods * //
 
         /// Collect fees from a registered lending markets
@@ -0,0 +1 @@
+pub mod unstake_fee;
@@ -0,0 +1,46 @@
+use crate::helpers::init::*;
+use radix_engine_interface::prelude::*;
+
+#[test]
+fn test_unstake_fee() {
+    let mut helper = StakingTestHelper::new(dec!("0.1"));
+
+    let balance = helper.get_balance(XRD);
+
+    helper.stake(dec!(200)).expect_commit_success();
+
+    assert_eq!(
+        helper.get_balance(helper.pool_unit_resource_address),
+        dec!(200)
+    );
+
+    // 10% of the unstaked assets are kept by the pool
+    helper.unstake(dec!(100)).expect_commit_success();
+
+    // The fee is charged when the assets are claimed and accrues to the remaining stakers
+    helper.claim().expect_commit_success();
+
+    assert_eq!(helper.get_balance(XRD), balance - dec!(200) + dec!(90));
+
+    // The 100 remaining units are backed by 110 XRD, staking 11 XRD mints about 10 units
+    let units = helper.get_balance(helper.pool_unit_resource_address);
+
+    helper.stake(dec!(11)).expect_commit_success();
+
+    let minted_units = helper.get_balance(helper.pool_unit_resource_address) - units;
+    assert!(minted_units > dec!("9.99") && minted_units <= dec!(10));
+
+    // Without the fee the remaining units are redeemed at their increased value
+    helper
+        .update_unstake_fee_rate(dec!(1))
+        .expect_commit_failure();
+    helper
+        .update_unstake_fee_rate(dec!(0))
+        .expect_commit_success();
+
+    helper.unstake(dec!(100)).expect_commit_success();
+    helper.claim().expect_commit_success();
+
+    let claimed_amount = helper.get_balance(XRD) - (balance - dec!(121));
+    assert!(claimed_amount > dec!("109.99") && claimed_amount <= dec!(110));
+}
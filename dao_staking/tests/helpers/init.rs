@@ -0,0 +1,142 @@
+use radix_engine::vm::NoExtension;
+use radix_engine_interface::prelude::*;
+use scrypto_test::prelude::*;
+use scrypto_unit::*;
+
+// SingleResourcePool package address used by the staking pool extern blueprint
+const POOL_PACKAGE_ADDRESS: &str =
+    "package_tdx_2_1p4l8s3qymr20yr7hchwex582z3lmm37p8g56qzvtqerm3av8rtn0ue";
+
+pub struct StakingTestHelper {
+    pub test_runner: TestRunner<NoExtension, InMemorySubstateDatabase>,
+    pub public_key: Secp256k1PublicKey,
+    pub account_address: ComponentAddress,
+    pub staking_component_address: ComponentAddress,
+    pub pool_unit_resource_address: ResourceAddress,
+    pub unstaking_receipt_resource_address: ResourceAddress,
+}
+
+impl StakingTestHelper {
+    // XRD staking pool owned by the helper account
+    pub fn new(unstake_fee_rate: Decimal) -> StakingTestHelper {
+        let mut test_runner = TestRunnerBuilder::new().build();
+
+        let (public_key, _, account_address) = test_runner.new_allocated_account();
+
+        test_runner.load_account_from_faucet(account_address);
+
+        let pool_package_address = PackageAddress::try_from_bech32(
+            &AddressBech32Decoder::new(&NetworkDefinition::stokenet()),
+            POOL_PACKAGE_ADDRESS,
+        )
+        .unwrap();
+
+        test_runner.compile_and_publish_at_address("../single_resource_pool", pool_package_address);
+
+        let package_address = test_runner.compile_and_publish(this_package!());
+
+        let owner_rule = rule!(require(NonFungibleGlobalId::from_public_key(&public_key)));
+
+        let manifest = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_function(
+                package_address,
+                "StakingPool",
+                "instantiate",
+                // Any component stands for the price feed, prices are only used for fee conversion
+                manifest_args!(owner_rule, XRD, account_address, unstake_fee_rate),
+            )
+            .deposit_batch(account_address)
+            .build();
+
+        let receipt = test_runner.execute_manifest(
+            manifest,
+            vec![NonFungibleGlobalId::from_public_key(&public_key)],
+        );
+        println!("{:?}\n", receipt);
+        let result = receipt.expect_commit_success();
+
+        let (staking_component_address, _) = result.output::<(ComponentAddress, Own)>(1);
+
+        // Unstaking receipt, fees withdrawal term then pool unit resources
+        let resource_addresses_created = result.new_resource_addresses();
+
+        StakingTestHelper {
+            test_runner,
+            public_key,
+            account_address,
+            staking_component_address,
+            pool_unit_resource_address: resource_addresses_created[2],
+            unstaking_receipt_resource_address: resource_addresses_created[0],
+        }
+    }
+
+    pub fn execute(&mut self, manifest_builder: ManifestBuilder) -> TransactionReceipt {
+        let receipt = self.test_runner.execute_manifest(
+            manifest_builder.build(),
+            vec![NonFungibleGlobalId::from_public_key(&self.public_key)],
+        );
+
+        println!("{:?}\n", receipt);
+
+        receipt
+    }
+
+    // Call a staking pool method with a bucket of the given resource and deposit the returned resources
+    pub fn call_with_bucket(
+        &mut self,
+        method_name: &str,
+        resource_address: ResourceAddress,
+        amount: Decimal,
+    ) -> TransactionReceipt {
+        let staking_component_address = self.staking_component_address;
+
+        let manifest_builder = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .withdraw_from_account(self.account_address, resource_address, amount)
+            .take_all_from_worktop(resource_address, "bucket")
+            .with_name_lookup(|builder, lookup| {
+                let bucket = lookup.bucket("bucket");
+
+                builder.call_method(
+                    staking_component_address,
+                    method_name,
+                    manifest_args!(bucket),
+                )
+            })
+            .deposit_batch(self.account_address);
+
+        self.execute(manifest_builder)
+    }
+
+    pub fn stake(&mut self, amount: Decimal) -> TransactionReceipt {
+        self.call_with_bucket("stake", XRD, amount)
+    }
+
+    pub fn unstake(&mut self, units: Decimal) -> TransactionReceipt {
+        let pool_unit_resource_address = self.pool_unit_resource_address;
+
+        self.call_with_bucket("unstake", pool_unit_resource_address, units)
+    }
+
+    pub fn claim(&mut self) -> TransactionReceipt {
+        let unstaking_receipt_resource_address = self.unstaking_receipt_resource_address;
+
+        self.call_with_bucket("claim", unstaking_receipt_resource_address, dec!(1))
+    }
+
+    pub fn update_unstake_fee_rate(&mut self, unstake_fee_rate: Decimal) -> TransactionReceipt {
+        let manifest_builder = ManifestBuilder::new().lock_fee_from_faucet().call_method(
+            self.staking_component_address,
+            "update_unstake_fee_rate",
+            manifest_args!(unstake_fee_rate),
+        );
+
+        self.execute(manifest_builder)
+    }
+
+    pub fn get_balance(&mut self, resource_address: ResourceAddress) -> Decimal {
+        self.test_runner
+            .get_component_balance(self.account_address, resource_address)
+    }
+}
@@ -0,0 +1,2 @@
+pub mod blueprints;
+pub mod helpers;
@@ -1,20 +1,366 @@
+use ed25519_dalek::Verifier;
+use k256::ecdsa::signature::hazmat::PrehashVerifier;
 use scrypto::prelude::*;
 
 #[derive(ScryptoSbor, Clone)]
 pub struct PriceInfo {
     pub timestamp: i64,
     pub price: Decimal,
+    /// Price uncertainty in both directions, when provided by the price feed
+    pub confidence: Option<Decimal>,
 }
 
 #[derive(ScryptoSbor, NonFungibleData)]
 pub struct AuthBadgeData {}
 
+/// Source id of the prices submitted by the admin, updater badge ids start at 1
+pub const ADMIN_SOURCE_ID: u64 = 0;
+
+/// Default maximum age in seconds of a submission to be considered fresh
+pub const DEFAULT_MAX_AGE: i64 = 60 * 60;
+
+#[derive(ScryptoSbor, ManifestSbor, Clone, Debug, PartialEq)]
+pub struct AggregationConfig {
+    /// Minimum number of fresh submissions needed to return a price
+    pub quorum: u8,
+
+    /// Maximum relative deviation of a fresh submission from the median
+    pub max_deviation: Option<Decimal>,
+
+    /// Maximum age in seconds of a submission to be considered fresh
+    pub max_age: Option<i64>,
+}
+impl Default for AggregationConfig {
+    fn default() -> Self {
+        Self {
+            quorum: 1,
+            max_deviation: None,
+            max_age: Some(DEFAULT_MAX_AGE),
+        }
+    }
+}
+impl AggregationConfig {
+    pub fn check(&self) {
+        assert!(self.quorum >= 1, "Quorum must be at least 1");
+
+        if let Some(max_deviation) = self.max_deviation {
+            assert!(
+                max_deviation >= dec!(0),
+                "Max deviation must not be negative"
+            );
+        }
+
+        if let Some(max_age) = self.max_age {
+            assert!(max_age > 0, "Max age must be positive");
+        }
+    }
+}
+
+/// Number of price observations kept per resource for the TWAP
+pub const MAX_OBSERVATIONS: usize = 120;
+
+/// Ring buffer of the aggregated price observations, at most one per minute
+#[derive(ScryptoSbor, Clone, Default)]
+pub struct PriceObservations {
+    pub observations: Vec<PriceInfo>,
+    /// Index of the oldest observation once the buffer is full
+    pub next_index: usize,
+}
+impl PriceObservations {
+    pub fn record(&mut self, price_info: PriceInfo) {
+        if let Some(last) = self.get_chronological().last() {
+            if last.timestamp == price_info.timestamp {
+                let last_index =
+                    (self.next_index + self.observations.len() - 1) % self.observations.len();
+                self.observations[last_index] = price_info;
+                return;
+            }
+        }
+
+        if self.observations.len() < MAX_OBSERVATIONS {
+            self.observations.push(price_info);
+        } else {
+            self.observations[self.next_index] = price_info;
+            self.next_index = (self.next_index + 1) % MAX_OBSERVATIONS;
+        }
+    }
+
+    pub fn get_latest(&self) -> Option<PriceInfo> {
+        self.get_chronological().last().cloned()
+    }
+
+    // Latest observation made at or before `timestamp`, or the oldest one if none
+    pub fn get_reference(&self, timestamp: i64) -> Option<PriceInfo> {
+        let observations = self.get_chronological();
+
+        observations
+            .iter()
+            .rev()
+            .find(|observation| observation.timestamp <= timestamp)
+            .or(observations.first())
+            .cloned()
+    }
+
+    pub fn get_chronological(&self) -> Vec<PriceInfo> {
+        let mut observations = self.observations.clone();
+        observations.rotate_left(self.next_index);
+        observations
+    }
+
+    // Each observed price is weighted by the time until the next observation.
+    // The average covers less than the window when older observations are not available
+    pub fn get_twap(&self, window_minutes: i64, now: i64) -> Option<PriceInfo> {
+        let observations = self.get_chronological();
+
+        let latest = observations.last()?.clone();
+
+        let window_start = now - window_minutes * 60;
+
+        let mut weighted_price_sum = dec!(0);
+        let mut total_time = 0i64;
+
+        for (index, observation) in observations.iter().enumerate() {
+            let end = observations
+                .get(index + 1)
+                .map(|next| next.timestamp)
+                .unwrap_or(now);
+
+            let start = observation.timestamp.max(window_start);
+
+            if end > start {
+                weighted_price_sum += observation.price * (end - start);
+                total_time += end - start;
+            }
+        }
+
+        if total_time == 0 {
+            return Some(latest);
+        }
+
+        Some(PriceInfo {
+            timestamp: latest.timestamp,
+            price: weighted_price_sum / total_time,
+            confidence: None,
+        })
+    }
+}
+
+#[derive(ScryptoSbor, ManifestSbor, Clone, Debug, PartialEq)]
+pub struct PriceLimits {
+    /// Maximum relative change from the last aggregated price
+    pub max_change_per_update: Option<Decimal>,
+
+    /// Maximum relative change from the aggregated price one hour ago
+    pub max_change_per_hour: Option<Decimal>,
+
+    pub min_price: Option<Decimal>,
+    pub max_price: Option<Decimal>,
+
+    /// Suspend the resource price instead of rejecting the update when a limit is broken
+    pub suspend_on_breach: bool,
+}
+impl PriceLimits {
+    pub fn check(&self) {
+        for max_change in [self.max_change_per_update, self.max_change_per_hour]
+            .into_iter()
+            .flatten()
+        {
+            assert!(max_change > dec!(0), "Max price change must be positive");
+        }
+
+        if let (Some(min_price), Some(max_price)) = (self.min_price, self.max_price) {
+            assert!(
+                min_price <= max_price,
+                "Min price must not be greater than max price"
+            );
+        }
+    }
+}
+
+fn relative_change(reference_price: Decimal, price: Decimal) -> Decimal {
+    if reference_price == dec!(0) {
+        return Decimal::MAX;
+    }
+
+    ((price - reference_price) / reference_price)
+        .checked_abs()
+        .unwrap()
+}
+
+/// Price authored off-ledger by an authorized signer
+#[derive(ScryptoSbor, ManifestSbor, Clone, Debug)]
+pub struct PriceReport {
+    pub resource: ResourceAddress,
+    pub price: Decimal,
+    /// Time at which the report was signed, in seconds
+    pub timestamp: i64,
+    /// Must be greater than the last nonce accepted from the signer
+    pub nonce: u64,
+}
+
+#[derive(ScryptoSbor, ManifestSbor, Clone, Debug)]
+pub struct SignedPriceReport {
+    pub report: PriceReport,
+    pub signer: PublicKey,
+    /// 64 bytes Ed25519 signature or 64 bytes (r, s) Secp256k1 signature of the report hash
+    pub signature: Vec<u8>,
+}
+
+#[derive(ScryptoSbor, Clone, Debug)]
+pub struct SignerState {
+    /// Source id of the prices submitted by the signer
+    pub source_id: u64,
+    pub last_nonce: u64,
+}
+
+// Reports are signed for a specific price feed component to prevent cross-feed replays
+pub fn get_report_hash(price_feed: ComponentAddress, report: &PriceReport) -> Hash {
+    hash(scrypto_encode(&(price_feed, report.clone())).unwrap())
+}
+
+pub fn verify_report_signature(report_hash: &Hash, signer: &PublicKey, signature: &[u8]) -> bool {
+    match signer {
+        PublicKey::Ed25519(public_key) => {
+            let verifying_key = match ed25519_dalek::VerifyingKey::from_bytes(&public_key.0) {
+                Ok(verifying_key) => verifying_key,
+                Err(_) => return false,
+            };
+
+            match ed25519_dalek::Signature::from_slice(signature) {
+                Ok(signature) => verifying_key
+                    .verify(report_hash.as_slice(), &signature)
+                    .is_ok(),
+                Err(_) => false,
+            }
+        }
+        PublicKey::Secp256k1(public_key) => {
+            let verifying_key = match k256::ecdsa::VerifyingKey::from_sec1_bytes(&public_key.0) {
+                Ok(verifying_key) => verifying_key,
+                Err(_) => return false,
+            };
+
+            match k256::ecdsa::Signature::from_slice(signature) {
+                Ok(signature) => verifying_key
+                    .verify_prehash(report_hash.as_slice(), &signature)
+                    .is_ok(),
+                Err(_) => false,
+            }
+        }
+    }
+}
+
+fn median(mut prices: Vec<Decimal>) -> Decimal {
+    prices.sort();
+
+    let middle = prices.len() / 2;
+
+    if prices.len() % 2 == 0 {
+        (prices[middle - 1] + prices[middle]) / 2
+    } else {
+        prices[middle]
+    }
+}
+
+/// Maximum number of history entries kept per resource
+pub const MAX_HISTORY_CAPACITY: u32 = 1000;
+
+/// Change of the price of a resource reported by events and recorded in the history
+#[derive(ScryptoSbor, Clone, Copy, Debug, PartialEq)]
+pub enum PriceUpdateType {
+    /// Price submitted by an updater or a signer
+    Submission,
+    /// Admin price set as override
+    AdminOverride,
+    /// Admin price override removed
+    AdminOverrideRemoved,
+    /// Resource suspended by a price breaking its limits, the breaking price is not submitted
+    Suspension,
+    /// Suspended resource resumed by the admin
+    Resumption,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct PriceUpdatedEvent {
+    pub resource: ResourceAddress,
+    pub update_type: PriceUpdateType,
+    /// Previous price of the same source
+    pub old_price: Option<Decimal>,
+    /// Price given by the source, none when the admin override is removed or the resource is resumed
+    pub new_price: Option<Decimal>,
+    /// Price returned by `get_price` before the update, none if no price was available
+    pub old_aggregated_price: Option<Decimal>,
+    /// Price returned by `get_price` after the update, none if no price is available
+    pub new_aggregated_price: Option<Decimal>,
+    /// Updater badge id or signer source id, `ADMIN_SOURCE_ID` for the admin
+    pub source_id: u64,
+    pub timestamp: i64,
+}
+
+#[derive(ScryptoSbor, Clone, Debug)]
+pub struct PriceHistoryEntry {
+    pub timestamp: i64,
+    pub update_type: PriceUpdateType,
+    /// Price given by the source, as `new_price` in `PriceUpdatedEvent`
+    pub price: Option<Decimal>,
+    /// Price returned by `get_price` after the update
+    pub aggregated_price: Option<Decimal>,
+    pub source_id: u64,
+}
+
+/// Latest price changes of a resource, the oldest entries are dropped beyond the capacity
+#[derive(ScryptoSbor, Clone, Debug)]
+pub struct PriceHistory {
+    pub capacity: u32,
+    pub entries: Vec<PriceHistoryEntry>,
+}
+impl PriceHistory {
+    pub fn new(capacity: u32) -> Self {
+        Self {
+            capacity,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, entry: PriceHistoryEntry) {
+        self.entries.push(entry);
+        self.truncate();
+    }
+
+    pub fn set_capacity(&mut self, capacity: u32) {
+        self.capacity = capacity;
+        self.truncate();
+    }
+
+    // Entries with a timestamp in the inclusive range [from, to]
+    pub fn get_range(&self, from: i64, to: i64) -> Vec<PriceHistoryEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.timestamp >= from && entry.timestamp <= to)
+            .cloned()
+            .collect()
+    }
+
+    fn truncate(&mut self) {
+        let excess = self.entries.len().saturating_sub(self.capacity as usize);
+
+        if excess > 0 {
+            self.entries.drain(..excess);
+        }
+    }
+}
+
 #[derive(ScryptoSbor, NonFungibleData)]
 pub struct UpdaterBadgeData {
+    #[mutable]
     pub active: bool,
+
+    /// Resources the updater can write, all resources when not set
+    #[mutable]
+    pub allowed_resources: Option<IndexSet<ResourceAddress>>,
 }
 
 #[blueprint]
+#[events(PriceUpdatedEvent)]
 mod price_feed {
 
     enable_method_auth! {
@@ -25,16 +371,50 @@ mod price_feed {
         methods {
             mint_updater_badge => restrict_to: [admin];
             update_updater_badge => restrict_to: [admin];
+            update_updater_badge_resources => restrict_to: [admin];
             admin_update_price => restrict_to: [admin];
+            remove_admin_price => restrict_to: [admin];
+            update_aggregation_config => restrict_to: [admin];
+            update_price_limits => restrict_to: [admin];
+            add_signer => restrict_to: [admin];
+            remove_signer => restrict_to: [admin];
+            update_max_report_age => restrict_to: [admin];
+            resume_price => restrict_to: [admin];
+            update_history_capacity => restrict_to: [admin];
 
             update_price => restrict_to: [updater];
+            update_prices => restrict_to: [updater];
+
+            submit_signed_prices => PUBLIC;
 
             get_price => PUBLIC;
+            get_admin_price => PUBLIC;
+            get_submissions => PUBLIC;
+            get_aggregation_config => PUBLIC;
+            get_twap => PUBLIC;
+            get_price_limits => PUBLIC;
+            is_suspended => PUBLIC;
+            get_signers => PUBLIC;
+            get_price_history => PUBLIC;
         }
     }
 
     pub struct PriceFeed {
-        prices: IndexMap<ResourceAddress, PriceInfo>,
+        /// Last price submitted by each source, per resource
+        submissions: IndexMap<ResourceAddress, IndexMap<u64, PriceInfo>>,
+        /// Prices set by the admin, returned instead of the aggregated price until removed
+        admin_prices: IndexMap<ResourceAddress, PriceInfo>,
+        aggregation_configs: IndexMap<ResourceAddress, AggregationConfig>,
+        observations: IndexMap<ResourceAddress, PriceObservations>,
+        price_limits: IndexMap<ResourceAddress, PriceLimits>,
+        /// Resources with no price returned until resumed by the admin
+        suspended: IndexSet<ResourceAddress>,
+        /// Submission history of the resources for which it is enabled, kept out of the component state
+        histories: KeyValueStore<ResourceAddress, PriceHistory>,
+        /// Public keys allowed to author signed price reports
+        signers: IndexMap<PublicKey, SignerState>,
+        /// Maximum age in seconds of an accepted signed report
+        max_report_age: i64,
         updater_badge_manager: ResourceManager,
         updater_counter: u64,
     }
@@ -74,7 +454,15 @@ mod price_feed {
                 .create_with_no_initial_supply();
 
             Self {
-                prices: IndexMap::new(),
+                submissions: IndexMap::new(),
+                admin_prices: IndexMap::new(),
+                aggregation_configs: IndexMap::new(),
+                observations: IndexMap::new(),
+                price_limits: IndexMap::new(),
+                suspended: IndexSet::new(),
+                histories: KeyValueStore::new(),
+                signers: IndexMap::new(),
+                max_report_age: 5 * 60,
                 updater_badge_manager,
                 updater_counter: 0,
             }
@@ -92,27 +480,202 @@ mod price_feed {
 
         // * Admin Methods * //
 
-        pub fn mint_updater_badge(&mut self, active: bool) -> Bucket {
+        pub fn mint_updater_badge(
+            &mut self,
+            active: bool,
+            allowed_resources: Option<IndexSet<ResourceAddress>>,
+        ) -> Bucket {
             let badge_id = NonFungibleLocalId::Integer(self._get_new_id().into());
 
-            self.updater_badge_manager
-                .mint_non_fungible(&badge_id, UpdaterBadgeData { active })
+            self.updater_badge_manager.mint_non_fungible(
+                &badge_id,
+                UpdaterBadgeData {
+                    active,
+                    allowed_resources,
+                },
+            )
         }
 
-        pub fn update_updater_badge(&self, local_id: NonFungibleLocalId, active: bool) {
+        pub fn update_updater_badge_resources(
+            &self,
+            local_id: NonFungibleLocalId,
+            allowed_resources: Option<IndexSet<ResourceAddress>>,
+        ) {
+            self.updater_badge_manager.update_non_fungible_data(
+                &local_id,
+                "allowed_resources",
+                allowed_resources,
+            );
+        }
+
+        pub fn update_updater_badge(&mut self, local_id: NonFungibleLocalId, active: bool) {
             self.updater_badge_manager
                 .update_non_fungible_data(&local_id, "active", active);
+
+            // Prices submitted by a deactivated updater are no longer aggregated
+            if !active {
+                let source_id = match local_id {
+                    NonFungibleLocalId::Integer(id) => id.value(),
+                    _ => panic!("Invalid updater badge id"),
+                };
+
+                self.submissions.values_mut().for_each(|submissions| {
+                    submissions.remove(&source_id);
+                });
+            }
         }
 
+        // Override the aggregated price of a resource until the override is removed.
+        // As an emergency path, the admin price skips the price limits and is returned while the resource is suspended
         pub fn admin_update_price(&mut self, resource: ResourceAddress, price: Decimal) {
+            assert!(price > dec!(0), "Price must be positive.");
+
             let now = Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch;
-            self.prices.insert(
+
+            let old_aggregated_price = self.get_price(resource);
+
+            let old_price_info = self.admin_prices.insert(
                 resource,
                 PriceInfo {
                     timestamp: now,
                     price,
+                    confidence: None,
                 },
             );
+
+            self._record_price(
+                resource,
+                PriceUpdateType::AdminOverride,
+                ADMIN_SOURCE_ID,
+                old_price_info,
+                Some(price),
+                old_aggregated_price,
+            );
+        }
+
+        // Return to the aggregated price of the updaters and signers
+        pub fn remove_admin_price(&mut self, resource: ResourceAddress) {
+            let old_aggregated_price = self.get_price(resource);
+
+            let old_price_info = self
+                .admin_prices
+                .remove(&resource)
+                .expect("No admin price set for this resource.");
+
+            self._record_price(
+                resource,
+                PriceUpdateType::AdminOverrideRemoved,
+                ADMIN_SOURCE_ID,
+                Some(old_price_info),
+                None,
+                old_aggregated_price,
+            );
+        }
+
+        pub fn update_aggregation_config(
+            &mut self,
+            resource: ResourceAddress,
+            aggregation_config: AggregationConfig,
+        ) {
+            aggregation_config.check();
+
+            self.aggregation_configs
+                .insert(resource, aggregation_config);
+        }
+
+        pub fn update_price_limits(
+            &mut self,
+            resource: ResourceAddress,
+            price_limits: Option<PriceLimits>,
+        ) {
+            match price_limits {
+                Some(price_limits) => {
+                    price_limits.check();
+                    self.price_limits.insert(resource, price_limits);
+                }
+                None => {
+                    self.price_limits.remove(&resource);
+                }
+            }
+        }
+
+        pub fn resume_price(&mut self, resource: ResourceAddress) {
+            let old_aggregated_price = self.get_price(resource);
+
+            if self.suspended.remove(&resource) {
+                self._record_price(
+                    resource,
+                    PriceUpdateType::Resumption,
+                    ADMIN_SOURCE_ID,
+                    None,
+                    None,
+                    old_aggregated_price,
+                );
+            }
+        }
+
+        // Enable the submission history of a resource with the given capacity, or disable it and drop its entries
+        pub fn update_history_capacity(
+            &mut self,
+            resource: ResourceAddress,
+            capacity: Option<u32>,
+        ) {
+            match capacity {
+                Some(capacity) => {
+                    assert!(
+                        capacity > 0 && capacity <= MAX_HISTORY_CAPACITY,
+                        "History capacity must be in the range (0, {}].",
+                        MAX_HISTORY_CAPACITY
+                    );
+
+                    let mut history = self
+                        .histories
+                        .remove(&resource)
+                        .unwrap_or_else(|| PriceHistory::new(capacity));
+
+                    history.set_capacity(capacity);
+
+                    self.histories.insert(resource, history);
+                }
+                None => {
+                    self.histories.remove(&resource);
+                }
+            }
+        }
+
+        // Authorize a signer and return the source id of its prices
+        pub fn add_signer(&mut self, public_key: PublicKey) -> u64 {
+            assert!(
+                !self.signers.contains_key(&public_key),
+                "Signer is already authorized."
+            );
+
+            let source_id = self._get_new_id();
+
+            self.signers.insert(
+                public_key,
+                SignerState {
+                    source_id,
+                    last_nonce: 0,
+                },
+            );
+
+            source_id
+        }
+
+        pub fn remove_signer(&mut self, public_key: PublicKey) {
+            let signer_state = self.signers.remove(&public_key).expect("Signer not found.");
+
+            // Prices submitted by a removed signer are no longer aggregated
+            self.submissions.values_mut().for_each(|submissions| {
+                submissions.remove(&signer_state.source_id);
+            });
+        }
+
+        pub fn update_max_report_age(&mut self, max_report_age: i64) {
+            assert!(max_report_age > 0, "Max report age must be positive.");
+
+            self.max_report_age = max_report_age;
         }
 
         // * Updater Methods * //
@@ -123,6 +686,207 @@ mod price_feed {
             resource: ResourceAddress,
             price: Decimal,
         ) {
+            let (source_id, badge_data) = self._check_updater_badge(badge_proof);
+
+            let now = Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch;
+
+            self._update_price(
+                source_id,
+                badge_data.allowed_resources.as_ref(),
+                resource,
+                price,
+                now,
+            );
+        }
+
+        // Update the prices of multiple resources in a single call
+        pub fn update_prices(
+            &mut self,
+            badge_proof: Proof,
+            prices: Vec<(ResourceAddress, Decimal)>,
+        ) {
+            let (source_id, badge_data) = self._check_updater_badge(badge_proof);
+
+            let now = Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch;
+
+            for (resource, price) in prices {
+                self._update_price(
+                    source_id,
+                    badge_data.allowed_resources.as_ref(),
+                    resource,
+                    price,
+                    now,
+                );
+            }
+        }
+
+        // * Public Methods * //
+
+        // Submit prices authored by authorized signers. Can be relayed by anyone
+        pub fn submit_signed_prices(&mut self, reports: Vec<SignedPriceReport>) {
+            let now = Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch;
+
+            let component_address = Runtime::global_address();
+
+            for signed_report in reports {
+                let report = signed_report.report;
+
+                let signer_state = self
+                    .signers
+                    .get_mut(&signed_report.signer)
+                    .expect("Signer is not authorized.");
+
+                assert!(
+                    report.nonce > signer_state.last_nonce,
+                    "Report nonce has already been used."
+                );
+
+                // Clock precision is a minute, reports signed within the current minute are accepted
+                assert!(
+                    report.timestamp <= now + 60 && now - report.timestamp <= self.max_report_age,
+                    "Report timestamp is out of the accepted range."
+                );
+
+                assert!(
+                    verify_report_signature(
+                        &get_report_hash(component_address, &report),
+                        &signed_report.signer,
+                        &signed_report.signature,
+                    ),
+                    "Invalid report signature."
+                );
+
+                signer_state.last_nonce = report.nonce;
+
+                let source_id = signer_state.source_id;
+
+                // The price is as old as the report, floored to the clock for reports signed within the current minute
+                self._update_price(
+                    source_id,
+                    None,
+                    report.resource,
+                    report.price,
+                    report.timestamp.min(now),
+                );
+            }
+        }
+
+        // Return the admin price if set, otherwise the median of the fresh submissions.
+        // No price is returned if the quorum is not reached or if a source deviates from the median beyond the tolerance
+        pub fn get_price(&self, quote: ResourceAddress) -> Option<PriceInfo> {
+            if let Some(admin_price) = self.admin_prices.get(&quote) {
+                return Some(admin_price.clone());
+            }
+
+            if self.is_suspended(quote) {
+                return None;
+            }
+
+            let config = self.get_aggregation_config(quote);
+
+            let now = Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch;
+
+            let fresh_submissions: Vec<&PriceInfo> = self
+                .submissions
+                .get(&quote)?
+                .values()
+                .filter(|price_info| match config.max_age {
+                    Some(max_age) => now - price_info.timestamp <= max_age,
+                    None => true,
+                })
+                .collect();
+
+            if fresh_submissions.len() < config.quorum as usize {
+                return None;
+            }
+
+            let price = median(fresh_submissions.iter().map(|info| info.price).collect());
+
+            if let Some(max_deviation) = config.max_deviation {
+                let within_tolerance = price != dec!(0)
+                    && fresh_submissions.iter().all(|info| {
+                        ((info.price - price) / price)
+                            .checked_abs()
+                            .map_or(false, |deviation| deviation <= max_deviation)
+                    });
+
+                if !within_tolerance {
+                    return None;
+                }
+            }
+
+            // The aggregated price is as old as the oldest submission used
+            let timestamp = fresh_submissions.iter().map(|info| info.timestamp).min()?;
+
+            // Confidence is the largest distance of a fresh submission from the median
+            let confidence = if fresh_submissions.len() > 1 {
+                fresh_submissions
+                    .iter()
+                    .filter_map(|info| (info.price - price).checked_abs())
+                    .max()
+            } else {
+                None
+            };
+
+            Some(PriceInfo {
+                timestamp,
+                price,
+                confidence,
+            })
+        }
+
+        // Time-weighted average of the aggregated price over the last `window_minutes`
+        pub fn get_twap(&self, quote: ResourceAddress, window_minutes: i64) -> Option<PriceInfo> {
+            assert!(window_minutes > 0, "TWAP window must be positive");
+
+            let now = Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch;
+
+            self.observations.get(&quote)?.get_twap(window_minutes, now)
+        }
+
+        pub fn get_price_limits(&self, quote: ResourceAddress) -> Option<PriceLimits> {
+            self.price_limits.get(&quote).cloned()
+        }
+
+        pub fn is_suspended(&self, quote: ResourceAddress) -> bool {
+            self.suspended.contains(&quote)
+        }
+
+        pub fn get_signers(&self) -> IndexMap<PublicKey, SignerState> {
+            self.signers.clone()
+        }
+
+        // Price changes recorded between `from` and `to` (inclusive, in seconds), empty if the history is not enabled
+        pub fn get_price_history(
+            &self,
+            quote: ResourceAddress,
+            from: i64,
+            to: i64,
+        ) -> Vec<PriceHistoryEntry> {
+            self.histories
+                .get(&quote)
+                .map(|history| history.get_range(from, to))
+                .unwrap_or_default()
+        }
+
+        pub fn get_admin_price(&self, quote: ResourceAddress) -> Option<PriceInfo> {
+            self.admin_prices.get(&quote).cloned()
+        }
+
+        pub fn get_submissions(&self, quote: ResourceAddress) -> IndexMap<u64, PriceInfo> {
+            self.submissions.get(&quote).cloned().unwrap_or_default()
+        }
+
+        pub fn get_aggregation_config(&self, quote: ResourceAddress) -> AggregationConfig {
+            self.aggregation_configs
+                .get(&quote)
+                .cloned()
+                .unwrap_or_default()
+        }
+
+        // * Helpers * //
+
+        fn _check_updater_badge(&self, badge_proof: Proof) -> (u64, UpdaterBadgeData) {
             let local_id = badge_proof
                 .check(self.updater_badge_manager.address())
                 .as_non_fungible()
@@ -132,23 +896,198 @@ mod price_feed {
                 self.updater_badge_manager.get_non_fungible_data(&local_id);
 
             assert!(badge_data.active, "Updater badge is not active.");
+
+            let source_id = match local_id {
+                NonFungibleLocalId::Integer(id) => id.value(),
+                _ => panic!("Invalid updater badge id"),
+            };
+
+            (source_id, badge_data)
+        }
+
+        fn _update_price(
+            &mut self,
+            source_id: u64,
+            allowed_resources: Option<&IndexSet<ResourceAddress>>,
+            resource: ResourceAddress,
+            price: Decimal,
+            timestamp: i64,
+        ) {
+            if let Some(allowed_resources) = allowed_resources {
+                assert!(
+                    allowed_resources.contains(&resource),
+                    "Updater badge is not allowed to update this resource."
+                );
+            }
+
+            assert!(price > dec!(0), "Price must be positive.");
+
+            if let Err(error) = self._check_price_limits(resource, price) {
+                let suspend_on_breach = self
+                    .price_limits
+                    .get(&resource)
+                    .map_or(false, |price_limits| price_limits.suspend_on_breach);
+
+                // Suspending must not panic, otherwise the suspension would be rolled back
+                if suspend_on_breach {
+                    let old_aggregated_price = self.get_price(resource);
+
+                    let old_price_info = self
+                        .submissions
+                        .get(&resource)
+                        .and_then(|submissions| submissions.get(&source_id).cloned());
+
+                    if self.suspended.insert(resource) {
+                        self._record_price(
+                            resource,
+                            PriceUpdateType::Suspension,
+                            source_id,
+                            old_price_info,
+                            Some(price),
+                            old_aggregated_price,
+                        );
+                    }
+
+                    return;
+                }
+
+                panic!("{}", error);
+            }
+
+            self._submit_price(source_id, resource, price, timestamp);
+        }
+
+        fn _check_price_limits(
+            &self,
+            resource: ResourceAddress,
+            price: Decimal,
+        ) -> Result<(), String> {
+            let price_limits = match self.price_limits.get(&resource) {
+                Some(price_limits) => price_limits,
+                None => return Ok(()),
+            };
+
+            if price_limits
+                .min_price
+                .map_or(false, |min_price| price < min_price)
+            {
+                return Err("Price is below the min price".into());
+            }
+
+            if price_limits
+                .max_price
+                .map_or(false, |max_price| price > max_price)
+            {
+                return Err("Price is above the max price".into());
+            }
+
+            let observations = match self.observations.get(&resource) {
+                Some(observations) => observations,
+                None => return Ok(()),
+            };
+
+            if let (Some(max_change), Some(reference)) = (
+                price_limits.max_change_per_update,
+                observations.get_latest(),
+            ) {
+                if relative_change(reference.price, price) > max_change {
+                    return Err("Price change per update is above the limit".into());
+                }
+            }
+
             let now = Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch;
-            self.prices.insert(
-                resource,
+
+            if let (Some(max_change), Some(reference)) = (
+                price_limits.max_change_per_hour,
+                observations.get_reference(now - 3600),
+            ) {
+                if relative_change(reference.price, price) > max_change {
+                    return Err("Price change per hour is above the limit".into());
+                }
+            }
+
+            Ok(())
+        }
+
+        // Store the price of a source, `timestamp` is the time at which the price was observed
+        fn _submit_price(
+            &mut self,
+            source_id: u64,
+            resource: ResourceAddress,
+            price: Decimal,
+            timestamp: i64,
+        ) {
+            let old_aggregated_price = self.get_price(resource);
+
+            let old_price_info = self.submissions.entry(resource).or_default().insert(
+                source_id,
                 PriceInfo {
-                    timestamp: now,
+                    timestamp,
                     price,
+                    confidence: None,
                 },
             );
+
+            self._record_price(
+                resource,
+                PriceUpdateType::Submission,
+                source_id,
+                old_price_info,
+                Some(price),
+                old_aggregated_price,
+            );
         }
 
-        // * Public Methods * //
+        // Record a price change in the history and the observations and emit the update event
+        fn _record_price(
+            &mut self,
+            resource: ResourceAddress,
+            update_type: PriceUpdateType,
+            source_id: u64,
+            old_price_info: Option<PriceInfo>,
+            price: Option<Decimal>,
+            old_aggregated_price: Option<PriceInfo>,
+        ) {
+            let now = Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch;
 
-        pub fn get_price(&self, quote: ResourceAddress) -> Option<PriceInfo> {
-            self.prices.get(&quote).cloned()
-        }
+            let new_aggregated_price = self.get_price(resource);
 
-        // * Helpers * //
+            if let Some(mut history) = self.histories.get_mut(&resource) {
+                history.record(PriceHistoryEntry {
+                    timestamp: now,
+                    update_type,
+                    price,
+                    aggregated_price: new_aggregated_price
+                        .as_ref()
+                        .map(|price_info| price_info.price),
+                    source_id,
+                });
+            }
+
+            Runtime::emit_event(PriceUpdatedEvent {
+                resource,
+                update_type,
+                old_price: old_price_info.map(|price_info| price_info.price),
+                new_price: price,
+                old_aggregated_price: old_aggregated_price.map(|price_info| price_info.price),
+                new_aggregated_price: new_aggregated_price
+                    .as_ref()
+                    .map(|price_info| price_info.price),
+                source_id,
+                timestamp: now,
+            });
+
+            if let Some(price_info) = new_aggregated_price {
+                self.observations
+                    .entry(resource)
+                    .or_default()
+                    .record(PriceInfo {
+                        timestamp: now,
+                        price: price_info.price,
+                        confidence: None,
+                    });
+            }
+        }
 
         fn _get_new_id(&mut self) -> u64 {
             self.updater_counter += 1;
@@ -0,0 +1,251 @@
+use crate::helpers::init::*;
+use internal_price_feed::*;
+use radix_engine_interface::prelude::*;
+
+// Mint updater badges 1 to `count`
+fn mint_updater_badges(helper: &mut PriceFeedTestHelper, count: u64) {
+    for _ in 0..count {
+        helper.mint_updater_badge(None).expect_commit_success();
+    }
+}
+
+fn update_aggregation_config(
+    helper: &mut PriceFeedTestHelper,
+    resource: ResourceAddress,
+    aggregation_config: AggregationConfig,
+) -> TransactionReceipt {
+    helper.admin_call(
+        "update_aggregation_config",
+        manifest_args!(resource, aggregation_config),
+    )
+}
+
+#[test]
+fn test_median_price() {
+    let mut helper = PriceFeedTestHelper::new();
+    let resource = helper.create_resource();
+
+    mint_updater_badges(&mut helper, 4);
+
+    helper
+        .update_price(1, resource, dec!(10))
+        .expect_commit_success();
+
+    let price_info = helper.get_price(resource).unwrap();
+    assert_eq!(price_info.price, dec!(10));
+    assert_eq!(price_info.timestamp, T0);
+    assert_eq!(price_info.confidence, None);
+
+    helper
+        .update_price(2, resource, dec!(12))
+        .expect_commit_success();
+    helper
+        .update_price(3, resource, dec!(11))
+        .expect_commit_success();
+
+    // Odd number of submissions, the middle price is used
+    let price_info = helper.get_price(resource).unwrap();
+    assert_eq!(price_info.price, dec!(11));
+    assert_eq!(price_info.confidence, Some(dec!(1)));
+
+    // Even number of submissions, the two middle prices are averaged
+    helper
+        .update_price(4, resource, dec!(20))
+        .expect_commit_success();
+
+    let price_info = helper.get_price(resource).unwrap();
+    assert_eq!(price_info.price, dec!("11.5"));
+    assert_eq!(price_info.confidence, Some(dec!("8.5")));
+
+    // Each source has a single submission
+    helper
+        .update_price(4, resource, dec!(13))
+        .expect_commit_success();
+
+    assert_eq!(helper.get_price(resource).unwrap().price, dec!("11.5"));
+
+    // Submissions of a deactivated updater are dropped
+    helper
+        .admin_call(
+            "update_updater_badge",
+            manifest_args!(NonFungibleLocalId::integer(4), false),
+        )
+        .expect_commit_success();
+
+    assert_eq!(helper.get_price(resource).unwrap().price, dec!(11));
+
+    helper
+        .update_price(4, resource, dec!(13))
+        .expect_commit_failure();
+}
+
+#[test]
+fn test_quorum() {
+    let mut helper = PriceFeedTestHelper::new();
+    let resource = helper.create_resource();
+
+    mint_updater_badges(&mut helper, 2);
+
+    update_aggregation_config(
+        &mut helper,
+        resource,
+        AggregationConfig {
+            quorum: 0,
+            max_deviation: None,
+            max_age: None,
+        },
+    )
+    .expect_commit_failure();
+
+    update_aggregation_config(
+        &mut helper,
+        resource,
+        AggregationConfig {
+            quorum: 2,
+            max_deviation: None,
+            max_age: Some(600),
+        },
+    )
+    .expect_commit_success();
+
+    helper
+        .update_price(1, resource, dec!(10))
+        .expect_commit_success();
+
+    assert!(helper.get_price(resource).is_none());
+
+    helper
+        .update_price(2, resource, dec!(12))
+        .expect_commit_success();
+
+    assert_eq!(helper.get_price(resource).unwrap().price, dec!(11));
+
+    // A stale submission no longer counts toward the quorum
+    helper.set_time(T0 + 300);
+
+    helper
+        .update_price(2, resource, dec!(12))
+        .expect_commit_success();
+
+    helper.set_time(T0 + 660);
+
+    assert!(helper.get_price(resource).is_none());
+
+    helper
+        .update_price(1, resource, dec!(10))
+        .expect_commit_success();
+
+    // The aggregated price is as old as the oldest submission used
+    let price_info = helper.get_price(resource).unwrap();
+    assert_eq!(price_info.price, dec!(11));
+    assert_eq!(price_info.timestamp, T0 + 300);
+}
+
+#[test]
+fn test_max_deviation() {
+    let mut helper = PriceFeedTestHelper::new();
+    let resource = helper.create_resource();
+
+    mint_updater_badges(&mut helper, 3);
+
+    update_aggregation_config(
+        &mut helper,
+        resource,
+        AggregationConfig {
+            quorum: 1,
+            max_deviation: Some(dec!("0.1")),
+            max_age: None,
+        },
+    )
+    .expect_commit_success();
+
+    helper
+        .update_price(1, resource, dec!(10))
+        .expect_commit_success();
+    helper
+        .update_price(2, resource, dec!("10.5"))
+        .expect_commit_success();
+
+    assert_eq!(helper.get_price(resource).unwrap().price, dec!("10.25"));
+
+    // A source far from the median blocks the price
+    helper
+        .update_price(3, resource, dec!(20))
+        .expect_commit_success();
+
+    assert!(helper.get_price(resource).is_none());
+}
+
+#[test]
+fn test_default_max_age() {
+    let mut helper = PriceFeedTestHelper::new();
+    let resource = helper.create_resource();
+
+    mint_updater_badges(&mut helper, 1);
+
+    helper
+        .update_price(1, resource, dec!(10))
+        .expect_commit_success();
+
+    helper.set_time(T0 + DEFAULT_MAX_AGE);
+
+    assert!(helper.get_price(resource).is_some());
+
+    helper.set_time(T0 + DEFAULT_MAX_AGE + 60);
+
+    assert!(helper.get_price(resource).is_none());
+}
+
+#[test]
+fn test_admin_price_override() {
+    let mut helper = PriceFeedTestHelper::new();
+    let resource = helper.create_resource();
+
+    mint_updater_badges(&mut helper, 2);
+
+    update_aggregation_config(
+        &mut helper,
+        resource,
+        AggregationConfig {
+            quorum: 2,
+            max_deviation: None,
+            max_age: None,
+        },
+    )
+    .expect_commit_success();
+
+    helper
+        .update_price(1, resource, dec!(10))
+        .expect_commit_success();
+    helper
+        .update_price(2, resource, dec!(12))
+        .expect_commit_success();
+
+    // The admin price replaces the aggregated price, updater submissions are kept
+    helper
+        .admin_update_price(resource, dec!(0))
+        .expect_commit_failure();
+
+    helper
+        .admin_update_price(resource, dec!(5))
+        .expect_commit_success();
+
+    assert_eq!(helper.get_price(resource).unwrap().price, dec!(5));
+
+    helper
+        .update_price(1, resource, dec!(11))
+        .expect_commit_success();
+
+    assert_eq!(helper.get_price(resource).unwrap().price, dec!(5));
+
+    // Removing the override returns to the aggregated price
+    helper
+        .admin_call("remove_admin_price", manifest_args!(resource))
+        .expect_commit_success();
+
+    assert_eq!(helper.get_price(resource).unwrap().price, dec!("11.5"));
+
+    helper
+        .admin_call("remove_admin_price", manifest_args!(resource))
+        .expect_commit_failure();
+}
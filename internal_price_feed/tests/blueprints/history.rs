@@ -0,0 +1,226 @@
+use crate::helpers::init::*;
+use internal_price_feed::*;
+use radix_engine_interface::prelude::*;
+
+fn update_history_capacity(
+    helper: &mut PriceFeedTestHelper,
+    resource: ResourceAddress,
+    capacity: Option<u32>,
+) -> TransactionReceipt {
+    helper.admin_call(
+        "update_history_capacity",
+        manifest_args!(resource, capacity),
+    )
+}
+
+fn get_price_history(
+    helper: &mut PriceFeedTestHelper,
+    resource: ResourceAddress,
+    from: i64,
+    to: i64,
+) -> Vec<(i64, Option<Decimal>, u64)> {
+    helper
+        .call::<Vec<PriceHistoryEntry>>("get_price_history", manifest_args!(resource, from, to))
+        .into_iter()
+        .map(|entry| (entry.timestamp, entry.price, entry.source_id))
+        .collect()
+}
+
+#[test]
+fn test_price_updated_events() {
+    let mut helper = PriceFeedTestHelper::new();
+    let resource = helper.create_resource();
+
+    for _ in 0..2 {
+        helper.mint_updater_badge(None).expect_commit_success();
+    }
+
+    let receipt = helper.update_price(1, resource, dec!(10));
+    let event = helper.get_price_updated_events(&receipt).pop().unwrap();
+
+    assert_eq!(event.resource, resource);
+    assert_eq!(event.source_id, 1);
+    assert_eq!(event.timestamp, T0);
+    assert_eq!(event.old_price, None);
+    assert_eq!(event.update_type, PriceUpdateType::Submission);
+    assert_eq!(event.new_price, Some(dec!(10)));
+    assert_eq!(event.old_aggregated_price, None);
+    assert_eq!(event.new_aggregated_price, Some(dec!(10)));
+
+    // The old price is the previous submission of the same source
+    let receipt = helper.update_price(2, resource, dec!(12));
+    let event = helper.get_price_updated_events(&receipt).pop().unwrap();
+
+    assert_eq!(event.old_price, None);
+    assert_eq!(event.old_aggregated_price, Some(dec!(10)));
+    assert_eq!(event.new_aggregated_price, Some(dec!(11)));
+
+    let receipt = helper.update_price(1, resource, dec!(14));
+    let event = helper.get_price_updated_events(&receipt).pop().unwrap();
+
+    assert_eq!(event.old_price, Some(dec!(10)));
+    assert_eq!(event.old_aggregated_price, Some(dec!(11)));
+    assert_eq!(event.new_aggregated_price, Some(dec!(13)));
+
+    let receipt = helper.admin_update_price(resource, dec!(20));
+    let event = helper.get_price_updated_events(&receipt).pop().unwrap();
+
+    assert_eq!(event.source_id, ADMIN_SOURCE_ID);
+    assert_eq!(event.old_price, None);
+    assert_eq!(event.old_aggregated_price, Some(dec!(13)));
+    assert_eq!(event.new_aggregated_price, Some(dec!(20)));
+
+    // A batch emits an event per price
+    let other_resource = helper.create_resource();
+
+    let receipt = helper.update_prices(1, vec![(resource, dec!(15)), (other_resource, dec!(5))]);
+    let events = helper.get_price_updated_events(&receipt);
+
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[1].resource, other_resource);
+    assert_eq!(events[1].new_aggregated_price, Some(dec!(5)));
+}
+
+#[test]
+fn test_price_history() {
+    let mut helper = PriceFeedTestHelper::new();
+    let resource = helper.create_resource();
+
+    helper.mint_updater_badge(None).expect_commit_success();
+
+    update_history_capacity(&mut helper, resource, Some(0)).expect_commit_failure();
+    update_history_capacity(&mut helper, resource, Some(MAX_HISTORY_CAPACITY + 1))
+        .expect_commit_failure();
+
+    // Not recorded before the history is enabled
+    helper
+        .update_price(1, resource, dec!(9))
+        .expect_commit_success();
+
+    update_history_capacity(&mut helper, resource, Some(3)).expect_commit_success();
+
+    for (minute, price) in [(1, dec!(10)), (2, dec!(11)), (3, dec!(12)), (4, dec!(13))] {
+        helper.set_time(T0 + minute * 60);
+
+        helper
+            .update_price(1, resource, price)
+            .expect_commit_success();
+    }
+
+    // The oldest entry is dropped beyond the capacity
+    assert_eq!(
+        get_price_history(&mut helper, resource, T0, T0 + 240),
+        vec![
+            (T0 + 120, Some(dec!(11)), 1),
+            (T0 + 180, Some(dec!(12)), 1),
+            (T0 + 240, Some(dec!(13)), 1)
+        ]
+    );
+
+    // Range bounds are inclusive
+    assert_eq!(
+        get_price_history(&mut helper, resource, T0 + 180, T0 + 180),
+        vec![(T0 + 180, Some(dec!(12)), 1)]
+    );
+    assert!(get_price_history(&mut helper, resource, T0 + 300, T0 + 600).is_empty());
+
+    // Lowering the capacity keeps the latest entries
+    update_history_capacity(&mut helper, resource, Some(1)).expect_commit_success();
+
+    assert_eq!(
+        get_price_history(&mut helper, resource, T0, T0 + 240),
+        vec![(T0 + 240, Some(dec!(13)), 1)]
+    );
+
+    // Disabling the history drops its entries
+    update_history_capacity(&mut helper, resource, None).expect_commit_success();
+
+    assert!(get_price_history(&mut helper, resource, T0, T0 + 240).is_empty());
+}
+
+#[test]
+fn test_price_transitions() {
+    let mut helper = PriceFeedTestHelper::new();
+    let resource = helper.create_resource();
+
+    helper.mint_updater_badge(None).expect_commit_success();
+
+    helper
+        .admin_call(
+            "update_price_limits",
+            manifest_args!(
+                resource,
+                Some(PriceLimits {
+                    max_change_per_update: Some(dec!("0.1")),
+                    max_change_per_hour: None,
+                    min_price: None,
+                    max_price: None,
+                    suspend_on_breach: true,
+                })
+            ),
+        )
+        .expect_commit_success();
+
+    update_history_capacity(&mut helper, resource, Some(10)).expect_commit_success();
+
+    helper
+        .update_price(1, resource, dec!(10))
+        .expect_commit_success();
+    helper
+        .admin_update_price(resource, dec!(20))
+        .expect_commit_success();
+
+    // Removing the override returns to the updater price
+    let receipt = helper.admin_call("remove_admin_price", manifest_args!(resource));
+    let event = helper.get_price_updated_events(&receipt).pop().unwrap();
+
+    assert_eq!(event.update_type, PriceUpdateType::AdminOverrideRemoved);
+    assert_eq!(event.source_id, ADMIN_SOURCE_ID);
+    assert_eq!(event.old_price, Some(dec!(20)));
+    assert_eq!(event.new_price, None);
+    assert_eq!(event.old_aggregated_price, Some(dec!(20)));
+    assert_eq!(event.new_aggregated_price, Some(dec!(10)));
+
+    // A breaking price suspends the resource without being submitted
+    let receipt = helper.update_price(1, resource, dec!(20));
+    let event = helper.get_price_updated_events(&receipt).pop().unwrap();
+
+    assert_eq!(event.update_type, PriceUpdateType::Suspension);
+    assert_eq!(event.source_id, 1);
+    assert_eq!(event.old_price, Some(dec!(10)));
+    assert_eq!(event.new_price, Some(dec!(20)));
+    assert_eq!(event.old_aggregated_price, Some(dec!(10)));
+    assert_eq!(event.new_aggregated_price, None);
+
+    let receipt = helper.admin_call("resume_price", manifest_args!(resource));
+    let event = helper.get_price_updated_events(&receipt).pop().unwrap();
+
+    assert_eq!(event.update_type, PriceUpdateType::Resumption);
+    assert_eq!(event.old_aggregated_price, None);
+    assert_eq!(event.new_aggregated_price, Some(dec!(10)));
+
+    // Resuming a resource that is not suspended changes nothing
+    let receipt = helper.admin_call("resume_price", manifest_args!(resource));
+    assert!(helper.get_price_updated_events(&receipt).is_empty());
+
+    let history: Vec<(PriceUpdateType, Option<Decimal>, Option<Decimal>)> = helper
+        .call::<Vec<PriceHistoryEntry>>("get_price_history", manifest_args!(resource, T0, T0))
+        .into_iter()
+        .map(|entry| (entry.update_type, entry.price, entry.aggregated_price))
+        .collect();
+
+    assert_eq!(
+        history,
+        vec![
+            (PriceUpdateType::Submission, Some(dec!(10)), Some(dec!(10))),
+            (
+                PriceUpdateType::AdminOverride,
+                Some(dec!(20)),
+                Some(dec!(20))
+            ),
+            (PriceUpdateType::AdminOverrideRemoved, None, Some(dec!(10))),
+            (PriceUpdateType::Suspension, Some(dec!(20)), None),
+            (PriceUpdateType::Resumption, None, Some(dec!(10)))
+        ]
+    );
+}
@@ -0,0 +1,247 @@
+use crate::helpers::init::*;
+use internal_price_feed::*;
+use radix_engine_interface::prelude::*;
+
+const MINUTE: i64 = 60;
+
+fn no_price_limits() -> PriceLimits {
+    PriceLimits {
+        max_change_per_update: None,
+        max_change_per_hour: None,
+        min_price: None,
+        max_price: None,
+        suspend_on_breach: false,
+    }
+}
+
+// Resource priced by updater badge 1 with the given limits
+fn setup_price_limits(
+    helper: &mut PriceFeedTestHelper,
+    price_limits: PriceLimits,
+) -> ResourceAddress {
+    let resource = helper.create_resource();
+
+    helper.mint_updater_badge(None).expect_commit_success();
+
+    helper
+        .admin_call(
+            "update_price_limits",
+            manifest_args!(resource, Some(price_limits)),
+        )
+        .expect_commit_success();
+
+    resource
+}
+
+fn is_suspended(helper: &mut PriceFeedTestHelper, resource: ResourceAddress) -> bool {
+    helper.call("is_suspended", manifest_args!(resource))
+}
+
+#[test]
+fn test_invalid_price_limits() {
+    let mut helper = PriceFeedTestHelper::new();
+    let resource = helper.create_resource();
+
+    helper
+        .admin_call(
+            "update_price_limits",
+            manifest_args!(
+                resource,
+                Some(PriceLimits {
+                    max_change_per_update: Some(dec!(0)),
+                    ..no_price_limits()
+                })
+            ),
+        )
+        .expect_commit_failure();
+
+    helper
+        .admin_call(
+            "update_price_limits",
+            manifest_args!(
+                resource,
+                Some(PriceLimits {
+                    min_price: Some(dec!(20)),
+                    max_price: Some(dec!(10)),
+                    ..no_price_limits()
+                })
+            ),
+        )
+        .expect_commit_failure();
+}
+
+#[test]
+fn test_price_bounds() {
+    let mut helper = PriceFeedTestHelper::new();
+
+    let resource = setup_price_limits(
+        &mut helper,
+        PriceLimits {
+            min_price: Some(dec!(5)),
+            max_price: Some(dec!(20)),
+            ..no_price_limits()
+        },
+    );
+
+    helper
+        .update_price(1, resource, dec!(4))
+        .expect_commit_failure();
+    helper
+        .update_price(1, resource, dec!(21))
+        .expect_commit_failure();
+    helper
+        .update_price(1, resource, dec!(10))
+        .expect_commit_success();
+
+    assert_eq!(helper.get_price(resource).unwrap().price, dec!(10));
+
+    // Limits removed
+    helper
+        .admin_call(
+            "update_price_limits",
+            manifest_args!(resource, None::<PriceLimits>),
+        )
+        .expect_commit_success();
+
+    helper
+        .update_price(1, resource, dec!(21))
+        .expect_commit_success();
+}
+
+#[test]
+fn test_max_change_per_update() {
+    let mut helper = PriceFeedTestHelper::new();
+
+    let resource = setup_price_limits(
+        &mut helper,
+        PriceLimits {
+            max_change_per_update: Some(dec!("0.1")),
+            ..no_price_limits()
+        },
+    );
+
+    // No reference price for the first update
+    helper
+        .update_price(1, resource, dec!(10))
+        .expect_commit_success();
+
+    helper
+        .update_price(1, resource, dec!(11))
+        .expect_commit_success();
+
+    // 12.5 is more than 10% above 11
+    helper
+        .update_price(1, resource, dec!("12.5"))
+        .expect_commit_failure();
+
+    helper
+        .update_price(1, resource, dec!("9.8"))
+        .expect_commit_failure();
+
+    helper
+        .update_price(1, resource, dec!(12))
+        .expect_commit_success();
+}
+
+#[test]
+fn test_max_change_per_hour() {
+    let mut helper = PriceFeedTestHelper::new();
+
+    let resource = setup_price_limits(
+        &mut helper,
+        PriceLimits {
+            max_change_per_hour: Some(dec!("0.2")),
+            ..no_price_limits()
+        },
+    );
+
+    helper
+        .update_price(1, resource, dec!(10))
+        .expect_commit_success();
+
+    helper.set_time(T0 + 20 * MINUTE);
+
+    helper
+        .update_price(1, resource, dec!("11.5"))
+        .expect_commit_success();
+
+    // Small steps can not move the price more than 20% within an hour
+    helper.set_time(T0 + 40 * MINUTE);
+
+    helper
+        .update_price(1, resource, dec!("12.5"))
+        .expect_commit_failure();
+
+    // The reference is the price observed an hour ago
+    helper.set_time(T0 + 80 * MINUTE);
+
+    helper
+        .update_price(1, resource, dec!("13.5"))
+        .expect_commit_success();
+}
+
+#[test]
+fn test_suspend_and_resume() {
+    let mut helper = PriceFeedTestHelper::new();
+
+    let resource = setup_price_limits(
+        &mut helper,
+        PriceLimits {
+            max_change_per_update: Some(dec!("0.1")),
+            suspend_on_breach: true,
+            ..no_price_limits()
+        },
+    );
+
+    helper
+        .update_price(1, resource, dec!(10))
+        .expect_commit_success();
+
+    // The breaking update is not rejected, the resource is suspended instead
+    helper
+        .update_price(1, resource, dec!(20))
+        .expect_commit_success();
+
+    assert!(is_suspended(&mut helper, resource));
+    assert!(helper.get_price(resource).is_none());
+
+    // The breaking price was not submitted
+    helper
+        .admin_call("resume_price", manifest_args!(resource))
+        .expect_commit_success();
+
+    assert!(!is_suspended(&mut helper, resource));
+    assert_eq!(helper.get_price(resource).unwrap().price, dec!(10));
+}
+
+#[test]
+fn test_admin_price_skips_limits() {
+    let mut helper = PriceFeedTestHelper::new();
+
+    let resource = setup_price_limits(
+        &mut helper,
+        PriceLimits {
+            max_change_per_update: Some(dec!("0.1")),
+            max_price: Some(dec!(20)),
+            suspend_on_breach: true,
+            ..no_price_limits()
+        },
+    );
+
+    helper
+        .update_price(1, resource, dec!(10))
+        .expect_commit_success();
+    helper
+        .update_price(1, resource, dec!(30))
+        .expect_commit_success();
+
+    assert!(is_suspended(&mut helper, resource));
+
+    // The admin price is not limited and is returned while the resource is suspended
+    helper
+        .admin_update_price(resource, dec!(50))
+        .expect_commit_success();
+
+    assert!(is_suspended(&mut helper, resource));
+    assert_eq!(helper.get_price(resource).unwrap().price, dec!(50));
+}
@@ -0,0 +1,5 @@
+pub mod aggregation;
+pub mod history;
+pub mod limits;
+pub mod signed_reports;
+pub mod updaters;
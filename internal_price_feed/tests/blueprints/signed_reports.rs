@@ -0,0 +1,277 @@
+use crate::helpers::init::*;
+use internal_price_feed::*;
+use radix_engine_interface::prelude::*;
+use scrypto_test::prelude::*;
+
+enum SignerKey {
+    Ed25519(Ed25519PrivateKey),
+    Secp256k1(Secp256k1PrivateKey),
+}
+impl SignerKey {
+    fn public_key(&self) -> PublicKey {
+        match self {
+            SignerKey::Ed25519(private_key) => private_key.public_key().into(),
+            SignerKey::Secp256k1(private_key) => private_key.public_key().into(),
+        }
+    }
+
+    fn sign(&self, report_hash: &Hash) -> Vec<u8> {
+        match self {
+            SignerKey::Ed25519(private_key) => private_key.sign(report_hash).0.to_vec(),
+            // The recovery id comes first and is not part of the (r, s) signature
+            SignerKey::Secp256k1(private_key) => private_key.sign(report_hash).0[1..].to_vec(),
+        }
+    }
+}
+
+fn add_signer(helper: &mut PriceFeedTestHelper, signer_key: &SignerKey) -> u64 {
+    helper
+        .admin_call("add_signer", manifest_args!(signer_key.public_key()))
+        .expect_commit_success()
+        .output::<u64>(2)
+}
+
+fn sign_report(
+    helper: &PriceFeedTestHelper,
+    signer_key: &SignerKey,
+    report: PriceReport,
+) -> SignedPriceReport {
+    let report_hash = get_report_hash(helper.price_feed_component_address, &report);
+
+    SignedPriceReport {
+        report,
+        signer: signer_key.public_key(),
+        signature: signer_key.sign(&report_hash),
+    }
+}
+
+fn submit_signed_prices(
+    helper: &mut PriceFeedTestHelper,
+    reports: Vec<SignedPriceReport>,
+) -> TransactionReceipt {
+    let manifest_builder = ManifestBuilder::new().lock_fee_from_faucet().call_method(
+        helper.price_feed_component_address,
+        "submit_signed_prices",
+        manifest_args!(reports),
+    );
+
+    helper.execute(manifest_builder)
+}
+
+fn get_submission(
+    helper: &mut PriceFeedTestHelper,
+    resource: ResourceAddress,
+    source_id: u64,
+) -> PriceInfo {
+    helper
+        .call::<IndexMap<u64, PriceInfo>>("get_submissions", manifest_args!(resource))
+        .get(&source_id)
+        .cloned()
+        .unwrap()
+}
+
+#[test]
+fn test_valid_signed_reports() {
+    let mut helper = PriceFeedTestHelper::new();
+    let resource = helper.create_resource();
+
+    let ed25519_key = SignerKey::Ed25519(Ed25519PrivateKey::from_u64(1).unwrap());
+    let secp256k1_key = SignerKey::Secp256k1(Secp256k1PrivateKey::from_u64(2).unwrap());
+
+    let ed25519_source_id = add_signer(&mut helper, &ed25519_key);
+    let secp256k1_source_id = add_signer(&mut helper, &secp256k1_key);
+
+    helper.set_time(T0 + 180);
+
+    let reports = vec![
+        sign_report(
+            &helper,
+            &ed25519_key,
+            PriceReport {
+                resource,
+                price: dec!(10),
+                timestamp: T0 + 60,
+                nonce: 1,
+            },
+        ),
+        sign_report(
+            &helper,
+            &secp256k1_key,
+            PriceReport {
+                resource,
+                price: dec!(12),
+                timestamp: T0 + 120,
+                nonce: 1,
+            },
+        ),
+    ];
+
+    submit_signed_prices(&mut helper, reports).expect_commit_success();
+
+    assert_eq!(helper.get_price(resource).unwrap().price, dec!(11));
+
+    // Submissions are as old as the reports
+    assert_eq!(
+        get_submission(&mut helper, resource, ed25519_source_id).timestamp,
+        T0 + 60
+    );
+    assert_eq!(
+        get_submission(&mut helper, resource, secp256k1_source_id).timestamp,
+        T0 + 120
+    );
+
+    // A report signed within the current minute is floored to the clock
+    let report = sign_report(
+        &helper,
+        &ed25519_key,
+        PriceReport {
+            resource,
+            price: dec!(10),
+            timestamp: T0 + 200,
+            nonce: 2,
+        },
+    );
+
+    submit_signed_prices(&mut helper, vec![report]).expect_commit_success();
+
+    assert_eq!(
+        get_submission(&mut helper, resource, ed25519_source_id).timestamp,
+        T0 + 180
+    );
+}
+
+#[test]
+fn test_wrong_signer() {
+    let mut helper = PriceFeedTestHelper::new();
+    let resource = helper.create_resource();
+
+    let signer_key = SignerKey::Ed25519(Ed25519PrivateKey::from_u64(1).unwrap());
+    let other_key = SignerKey::Ed25519(Ed25519PrivateKey::from_u64(2).unwrap());
+
+    add_signer(&mut helper, &signer_key);
+
+    let report = PriceReport {
+        resource,
+        price: dec!(10),
+        timestamp: T0,
+        nonce: 1,
+    };
+
+    // Signed by an unauthorized key
+    let signed_report = sign_report(&helper, &other_key, report.clone());
+
+    submit_signed_prices(&mut helper, vec![signed_report.clone()]).expect_commit_failure();
+
+    // Signed by another key than the claimed signer
+    submit_signed_prices(
+        &mut helper,
+        vec![SignedPriceReport {
+            signer: signer_key.public_key(),
+            ..signed_report
+        }],
+    )
+    .expect_commit_failure();
+
+    // Report changed after signing
+    let mut signed_report = sign_report(&helper, &signer_key, report);
+    signed_report.report.price = dec!(20);
+
+    submit_signed_prices(&mut helper, vec![signed_report]).expect_commit_failure();
+
+    assert!(helper.get_price(resource).is_none());
+}
+
+#[test]
+fn test_replayed_nonce() {
+    let mut helper = PriceFeedTestHelper::new();
+    let resource = helper.create_resource();
+
+    let signer_key = SignerKey::Secp256k1(Secp256k1PrivateKey::from_u64(1).unwrap());
+
+    add_signer(&mut helper, &signer_key);
+
+    let signed_report = sign_report(
+        &helper,
+        &signer_key,
+        PriceReport {
+            resource,
+            price: dec!(10),
+            timestamp: T0,
+            nonce: 5,
+        },
+    );
+
+    submit_signed_prices(&mut helper, vec![signed_report.clone()]).expect_commit_success();
+    submit_signed_prices(&mut helper, vec![signed_report]).expect_commit_failure();
+
+    // Nonces must increase
+    let signed_report = sign_report(
+        &helper,
+        &signer_key,
+        PriceReport {
+            resource,
+            price: dec!(20),
+            timestamp: T0,
+            nonce: 4,
+        },
+    );
+
+    submit_signed_prices(&mut helper, vec![signed_report]).expect_commit_failure();
+
+    assert_eq!(helper.get_price(resource).unwrap().price, dec!(10));
+}
+
+#[test]
+fn test_stale_report() {
+    let mut helper = PriceFeedTestHelper::new();
+    let resource = helper.create_resource();
+
+    let signer_key = SignerKey::Ed25519(Ed25519PrivateKey::from_u64(1).unwrap());
+
+    add_signer(&mut helper, &signer_key);
+
+    helper
+        .admin_call("update_max_report_age", manifest_args!(300i64))
+        .expect_commit_success();
+
+    let stale_report = sign_report(
+        &helper,
+        &signer_key,
+        PriceReport {
+            resource,
+            price: dec!(10),
+            timestamp: T0 - 360,
+            nonce: 1,
+        },
+    );
+
+    submit_signed_prices(&mut helper, vec![stale_report]).expect_commit_failure();
+
+    let future_report = sign_report(
+        &helper,
+        &signer_key,
+        PriceReport {
+            resource,
+            price: dec!(10),
+            timestamp: T0 + 120,
+            nonce: 1,
+        },
+    );
+
+    submit_signed_prices(&mut helper, vec![future_report]).expect_commit_failure();
+
+    let report = sign_report(
+        &helper,
+        &signer_key,
+        PriceReport {
+            resource,
+            price: dec!(10),
+            timestamp: T0 - 300,
+            nonce: 1,
+        },
+    );
+
+    submit_signed_prices(&mut helper, vec![report]).expect_commit_success();
+
+    assert_eq!(helper.get_price(resource).unwrap().timestamp, T0 - 300);
+}
@@ -0,0 +1,72 @@
+use crate::helpers::init::*;
+use radix_engine_interface::prelude::*;
+
+#[test]
+fn test_update_prices() {
+    let mut helper = PriceFeedTestHelper::new();
+    let resource_1 = helper.create_resource();
+    let resource_2 = helper.create_resource();
+
+    helper.mint_updater_badge(None).expect_commit_success();
+
+    helper
+        .update_prices(1, vec![(resource_1, dec!(10)), (resource_2, dec!(20))])
+        .expect_commit_success();
+
+    assert_eq!(helper.get_price(resource_1).unwrap().price, dec!(10));
+    assert_eq!(helper.get_price(resource_2).unwrap().price, dec!(20));
+
+    // The batch is rejected as a whole when any price is invalid
+    helper
+        .update_prices(1, vec![(resource_1, dec!(11)), (resource_2, dec!(0))])
+        .expect_commit_failure();
+
+    assert_eq!(helper.get_price(resource_1).unwrap().price, dec!(10));
+    assert_eq!(helper.get_price(resource_2).unwrap().price, dec!(20));
+}
+
+#[test]
+fn test_allowed_resources() {
+    let mut helper = PriceFeedTestHelper::new();
+    let resource_1 = helper.create_resource();
+    let resource_2 = helper.create_resource();
+
+    let mut allowed_resources = IndexSet::new();
+    allowed_resources.insert(resource_1);
+
+    helper
+        .mint_updater_badge(Some(allowed_resources))
+        .expect_commit_success();
+
+    helper
+        .update_price(1, resource_1, dec!(10))
+        .expect_commit_success();
+    helper
+        .update_price(1, resource_2, dec!(20))
+        .expect_commit_failure();
+
+    helper
+        .update_prices(1, vec![(resource_1, dec!(11)), (resource_2, dec!(20))])
+        .expect_commit_failure();
+
+    assert_eq!(helper.get_price(resource_1).unwrap().price, dec!(10));
+    assert!(helper.get_price(resource_2).is_none());
+
+    // Removing the restriction allows the badge to update any resource
+    helper
+        .admin_call(
+            "update_updater_badge_resources",
+            manifest_args!(
+                NonFungibleLocalId::integer(1),
+                None::<IndexSet<ResourceAddress>>
+            ),
+        )
+        .expect_commit_success();
+
+    helper
+        .update_prices(1, vec![(resource_1, dec!(11)), (resource_2, dec!(20))])
+        .expect_commit_success();
+
+    assert_eq!(helper.get_price(resource_1).unwrap().price, dec!(11));
+    assert_eq!(helper.get_price(resource_2).unwrap().price, dec!(20));
+}
@@ -0,0 +1,209 @@
+use internal_price_feed::*;
+use radix_engine::vm::NoExtension;
+use radix_engine_interface::prelude::*;
+use scrypto_test::prelude::*;
+use scrypto_unit::*;
+
+/// Start time of the tests, in seconds and rounded to the minute like the clock
+pub const T0: i64 = 1_699_999_980;
+
+pub struct PriceFeedTestHelper {
+    pub test_runner: TestRunner<NoExtension, InMemorySubstateDatabase>,
+    pub public_key: Secp256k1PublicKey,
+    pub account_address: ComponentAddress,
+    pub price_feed_component_address: ComponentAddress,
+    pub admin_badge_resource_address: ResourceAddress,
+    pub updater_badge_resource_address: ResourceAddress,
+    round: u64,
+}
+
+impl PriceFeedTestHelper {
+    // Price feed with its admin badge held by the helper account, the clock is set to T0
+    pub fn new() -> PriceFeedTestHelper {
+        let mut test_runner = TestRunnerBuilder::new().build();
+
+        let (public_key, _, account_address) = test_runner.new_allocated_account();
+
+        let package_address = test_runner.compile_and_publish(this_package!());
+
+        let manifest = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_function(
+                package_address,
+                "PriceFeed",
+                "instantiate",
+                manifest_args!(),
+            )
+            .deposit_batch(account_address)
+            .build();
+
+        let receipt = test_runner.execute_manifest(
+            manifest,
+            vec![NonFungibleGlobalId::from_public_key(&public_key)],
+        );
+        println!("{:?}\n", receipt);
+        let result = receipt.expect_commit_success();
+
+        let price_feed_component_address = result.new_component_addresses()[0];
+
+        let resource_addresses_created = result.new_resource_addresses();
+
+        let mut helper = PriceFeedTestHelper {
+            test_runner,
+            public_key,
+            account_address,
+            price_feed_component_address,
+            admin_badge_resource_address: resource_addresses_created[0],
+            updater_badge_resource_address: resource_addresses_created[1],
+            round: 0,
+        };
+
+        helper.set_time(T0);
+
+        helper
+    }
+
+    // Move the clock to the given time in seconds
+    pub fn set_time(&mut self, timestamp: i64) {
+        self.round += 1;
+
+        self.test_runner
+            .advance_to_round_at_timestamp(Round::of(self.round), timestamp * 1000);
+    }
+
+    pub fn execute(&mut self, manifest_builder: ManifestBuilder) -> TransactionReceipt {
+        let receipt = self.test_runner.execute_manifest(
+            manifest_builder.build(),
+            vec![NonFungibleGlobalId::from_public_key(&self.public_key)],
+        );
+
+        println!("{:?}\n", receipt);
+
+        receipt
+    }
+
+    // Call a price feed method with the admin badge proof in the auth zone
+    pub fn admin_call(&mut self, method_name: &str, args: ManifestArgs) -> TransactionReceipt {
+        let manifest_builder = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .create_proof_from_account_of_non_fungible(
+                self.account_address,
+                NonFungibleGlobalId::new(
+                    self.admin_badge_resource_address,
+                    NonFungibleLocalId::integer(1),
+                ),
+            )
+            .call_method(self.price_feed_component_address, method_name, args)
+            .deposit_batch(self.account_address);
+
+        self.execute(manifest_builder)
+    }
+
+    // Call a price feed method without proof and return its output
+    pub fn call<T: ScryptoDecode>(&mut self, method_name: &str, args: ManifestArgs) -> T {
+        let manifest_builder = ManifestBuilder::new().lock_fee_from_faucet().call_method(
+            self.price_feed_component_address,
+            method_name,
+            args,
+        );
+
+        self.execute(manifest_builder)
+            .expect_commit_success()
+            .output::<T>(1)
+    }
+
+    pub fn mint_updater_badge(
+        &mut self,
+        allowed_resources: Option<IndexSet<ResourceAddress>>,
+    ) -> TransactionReceipt {
+        self.admin_call(
+            "mint_updater_badge",
+            manifest_args!(true, allowed_resources),
+        )
+    }
+
+    // Call an updater method with the updater badge in the auth zone and its proof as first argument
+    pub fn updater_call(
+        &mut self,
+        badge_id: u64,
+        method_name: &str,
+        args: impl FnOnce(ManifestProof) -> ManifestArgs,
+    ) -> TransactionReceipt {
+        let badge_global_id = NonFungibleGlobalId::new(
+            self.updater_badge_resource_address,
+            NonFungibleLocalId::integer(badge_id),
+        );
+
+        let price_feed_component_address = self.price_feed_component_address;
+
+        let manifest_builder = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .create_proof_from_account_of_non_fungible(
+                self.account_address,
+                badge_global_id.clone(),
+            )
+            .create_proof_from_account_of_non_fungible(self.account_address, badge_global_id)
+            .pop_from_auth_zone("badge_proof")
+            .with_name_lookup(|builder, lookup| {
+                let proof = lookup.proof("badge_proof");
+
+                builder.call_method(price_feed_component_address, method_name, args(proof))
+            });
+
+        self.execute(manifest_builder)
+    }
+
+    pub fn update_price(
+        &mut self,
+        badge_id: u64,
+        resource: ResourceAddress,
+        price: Decimal,
+    ) -> TransactionReceipt {
+        self.updater_call(badge_id, "update_price", |proof| {
+            manifest_args!(proof, resource, price)
+        })
+    }
+
+    pub fn update_prices(
+        &mut self,
+        badge_id: u64,
+        prices: Vec<(ResourceAddress, Decimal)>,
+    ) -> TransactionReceipt {
+        self.updater_call(badge_id, "update_prices", |proof| {
+            manifest_args!(proof, prices)
+        })
+    }
+
+    pub fn admin_update_price(
+        &mut self,
+        resource: ResourceAddress,
+        price: Decimal,
+    ) -> TransactionReceipt {
+        self.admin_call("admin_update_price", manifest_args!(resource, price))
+    }
+
+    pub fn get_price(&mut self, resource: ResourceAddress) -> Option<PriceInfo> {
+        self.call("get_price", manifest_args!(resource))
+    }
+
+    // Price updated events emitted by a committed transaction
+    pub fn get_price_updated_events(&self, receipt: &TransactionReceipt) -> Vec<PriceUpdatedEvent> {
+        receipt
+            .expect_commit_success()
+            .application_events
+            .iter()
+            .filter(|(event_type_identifier, _)| {
+                self.test_runner.event_name(event_type_identifier) == "PriceUpdatedEvent"
+            })
+            .map(|(_, event_data)| scrypto_decode::<PriceUpdatedEvent>(event_data).unwrap())
+            .collect()
+    }
+
+    // A new resource to price
+    pub fn create_resource(&mut self) -> ResourceAddress {
+        let account_address = self.account_address;
+
+        self.test_runner
+            .create_fungible_resource(dec!(1), 18, account_address)
+    }
+}
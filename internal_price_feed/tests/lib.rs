@@ -0,0 +1,3 @@
+pub mod blueprints;
+pub mod helpers;
+pub mod modules;
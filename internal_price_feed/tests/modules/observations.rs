@@ -0,0 +1,102 @@
+use internal_price_feed::*;
+use scrypto::*;
+use scrypto_test::prelude::*;
+
+const MINUTE: i64 = 60;
+
+fn observe(observations: &mut PriceObservations, timestamp: i64, price: Decimal) {
+    observations.record(PriceInfo {
+        timestamp,
+        price,
+        confidence: None,
+    });
+}
+
+#[test]
+fn test_twap_without_observation() {
+    let observations = PriceObservations::default();
+
+    assert!(observations.get_twap(10, 10 * MINUTE).is_none());
+}
+
+#[test]
+fn test_twap_single_observation() {
+    let mut observations = PriceObservations::default();
+
+    observe(&mut observations, 0, dec!(10));
+
+    // The single price is returned, with or without elapsed time
+    let twap = observations.get_twap(10, 0).unwrap();
+    assert_eq!(twap.price, dec!(10));
+    assert_eq!(twap.timestamp, 0);
+
+    let twap = observations.get_twap(10, 5 * MINUTE).unwrap();
+    assert_eq!(twap.price, dec!(10));
+    assert_eq!(twap.timestamp, 0);
+}
+
+#[test]
+fn test_twap_time_weighting() {
+    let mut observations = PriceObservations::default();
+
+    observe(&mut observations, 0, dec!(10));
+    observe(&mut observations, MINUTE, dec!(40));
+
+    // 10 for 1 minute and 40 for 3 minutes
+    let twap = observations.get_twap(4, 4 * MINUTE).unwrap();
+    assert_eq!(twap.price, dec!("32.5"));
+    assert_eq!(twap.timestamp, MINUTE);
+
+    // Observations before the window only count from the window start
+    observe(&mut observations, 2 * MINUTE, dec!(25));
+
+    let twap = observations.get_twap(3, 4 * MINUTE).unwrap();
+    assert_eq!(twap.price, dec!(30));
+
+    let twap = observations.get_twap(2, 4 * MINUTE).unwrap();
+    assert_eq!(twap.price, dec!(25));
+}
+
+#[test]
+fn test_twap_window_longer_than_history() {
+    let mut observations = PriceObservations::default();
+
+    observe(&mut observations, 0, dec!(10));
+    observe(&mut observations, MINUTE, dec!(40));
+
+    // The average only covers the available history
+    let twap = observations.get_twap(60, 4 * MINUTE).unwrap();
+    assert_eq!(twap.price, dec!("32.5"));
+}
+
+#[test]
+fn test_observations_ring_buffer() {
+    let mut observations = PriceObservations::default();
+
+    // A second observation within the same minute replaces the first one
+    observe(&mut observations, 0, dec!(10));
+    observe(&mut observations, 0, dec!(20));
+
+    assert_eq!(observations.observations.len(), 1);
+    assert_eq!(observations.get_latest().unwrap().price, dec!(20));
+
+    for minute in 1..(MAX_OBSERVATIONS as i64 + 10) {
+        observe(&mut observations, minute * MINUTE, Decimal::from(minute));
+    }
+
+    // The oldest observations are dropped
+    let chronological = observations.get_chronological();
+    assert_eq!(chronological.len(), MAX_OBSERVATIONS);
+    assert_eq!(chronological.first().unwrap().timestamp, 10 * MINUTE);
+    assert_eq!(
+        chronological.last().unwrap().timestamp,
+        (MAX_OBSERVATIONS as i64 + 9) * MINUTE
+    );
+
+    // Latest observation at or before a time, or the oldest one
+    assert_eq!(
+        observations.get_reference(20 * MINUTE + 30).unwrap().price,
+        dec!(20)
+    );
+    assert_eq!(observations.get_reference(0).unwrap().price, dec!(10));
+}
@@ -71,7 +71,7 @@ echo "CALL_FUNCTION
     Address(\"$LENDING_MARKET_PACKAGE\")
     \"LendingMarket\"
     \"instantiate\"
-    Tuple(10u8,)
+    Tuple(10u8, Enum<0u8>())
 ;" >> tx.rtm
 
 echo "CALL_METHOD
@@ -113,9 +113,14 @@ echo "CALL_METHOD Address(\"$LENDING_MARKET_COMPONENT_ADDRESS\") \"create_lendin
         Enum<0u8>(),
         Enum<0u8>(),
         Enum<0u8>(),
+        Enum<0u8>(),
         5i64,
+        Enum<0u8>(),
         15i64,
-        240i64
+        240i64,
+        Enum<0u8>(),
+        Enum<0u8>(),
+        Enum<0u8>()
     )
     Tuple(
         Decimal(\"0.05\"),
@@ -138,7 +143,9 @@ echo "CALL_METHOD Address(\"$LENDING_MARKET_COMPONENT_ADDRESS\") \"create_lendin
         Map<Address, Decimal>(),
         Map<U8, Decimal>(),
         Decimal(\"0.7\")
-    );" >> tx.rtm
+    )
+    Enum<0u8>()
+    Enum<0u8>();" >> tx.rtm
 
 echo "CALL_METHOD Address(\"$LENDING_MARKET_COMPONENT_ADDRESS\") \"create_lending_pool\" Address(\"$PRICE_FEED_COMPONENT_ADDRESS\") Address(\"$USDC_RESOURCE_ADDRESS\") 
     Tuple(
@@ -152,9 +159,14 @@ echo "CALL_METHOD Address(\"$LENDING_MARKET_COMPONENT_ADDRESS\") \"create_lendin
         Enum<0u8>(),
         Enum<0u8>(),
         Enum<0u8>(),
+        Enum<0u8>(),
         5i64,
+        Enum<0u8>(),
         15i64,
-        240i64
+        240i64,
+        Enum<0u8>(),
+        Enum<0u8>(),
+        Enum<0u8>()
     )
     Tuple(
         Decimal(\"0.05\"),
@@ -177,7 +189,9 @@ echo "CALL_METHOD Address(\"$LENDING_MARKET_COMPONENT_ADDRESS\") \"create_lendin
         Map<Address, Decimal>(),
         Map<U8, Decimal>(),
         Decimal(\"0\")
-    );" >> tx.rtm
+    )
+    Enum<0u8>()
+    Enum<0u8>();" >> tx.rtm
 echo "CALL_METHOD
     Address(\"$OWNER_ADDRESS\")
     \"deposit_batch\"
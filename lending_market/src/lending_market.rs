@@ -1,6 +1,7 @@
 use crate::modules::{
-    cdp_data::*, cdp_health_checker::*, interest_strategy::*, liquidation_threshold::*,
-    market_config::*, operation_status::*, pool_config::*, pool_state::*, utils::*,
+    cdp_data::*, cdp_health_checker::*, deleverage_order::*, fixed_rate_loan::*,
+    interest_strategy::*, liquidation_threshold::*, market_config::*, operation_status::*,
+    pool_config::*, pool_state::*, price_source::*, redemption_queue::*, utils::*,
 };
 use crate::resources::*;
 use scrypto::prelude::*;
@@ -19,7 +20,13 @@ pub enum UpdateDelegateeCDPnput {
 }
 
 #[blueprint]
-#[events(CDPUpdatedEvent, LendingPoolUpdatedEvent)]
+#[events(
+    CDPUpdatedEvent,
+    LendingPoolUpdatedEvent,
+    FlashloanTakenEvent,
+    FlashloanRepaidEvent,
+    PriceFeedUsedEvent
+)]
 mod lending_market {
 
     extern_blueprint!(
@@ -36,12 +43,18 @@ mod lending_market {
                 admin_rule: AccessRule,
                 contribute_rule: AccessRule,
                 redeem_rule: AccessRule,
+                fee_config: PoolFeeConfig,
+                dapp_definition: Option<ComponentAddress>,
             ) -> (Global<SingleResourcePool>, ResourceAddress);
 
             fn contribute(&self, assets: Bucket) -> Bucket;
 
             fn redeem(&self, pool_units: Bucket) -> Bucket;
 
+            fn protected_redeem(&mut self, pool_units: Bucket) -> Bucket;
+
+            fn queued_redeem(&mut self, pool_units: Bucket) -> Bucket;
+
             fn protected_deposit(&mut self, assets: Bucket, deposit_type: DepositType);
 
             fn protected_withdraw(
@@ -55,7 +68,19 @@ mod lending_market {
 
             fn get_pool_unit_ratio(&self) -> PreciseDecimal;
 
-            fn get_pooled_amount(&self) -> (Decimal,Decimal);
+            fn get_pooled_amount(&self) -> (Decimal, Decimal, Decimal);
+
+            fn get_min_amounts(&self) -> (Decimal, Decimal);
+
+            fn update_min_amounts(&mut self, min_contribution_amount: Decimal, min_redemption_units: Decimal);
+
+            fn update_fee_config(&mut self, fee_config: PoolFeeConfig);
+
+            fn withdraw_collected_fees(&mut self) -> Bucket;
+
+            fn update_yield_strategy(&mut self, yield_strategy: Option<ComponentAddress>, max_strategy_share: Decimal);
+
+            fn manage_yield_strategy(&mut self, action: YieldStrategyAction);
 
         }
     );
@@ -74,10 +99,20 @@ mod lending_market {
             create_lending_pool => restrict_to: [admin];
 
             update_price_feed => restrict_to: [admin];
+            update_price_source => restrict_to: [admin];
+            update_fallback_price_feed => restrict_to: [admin];
             update_market_config => restrict_to: [admin];
             update_pool_config => restrict_to: [admin];
             update_liquidation_threshold => restrict_to: [admin];
             update_interest_strategy => restrict_to: [admin];
+            update_interest_model => restrict_to: [admin];
+            update_flashloan_fee_strategy => restrict_to: [admin];
+            update_flashloan_fee_tier => restrict_to: [admin];
+            update_redemption_queue_rate_premium => restrict_to: [admin];
+            update_pool_min_amounts => restrict_to: [admin];
+            update_pool_fee_config => restrict_to: [admin];
+            update_pool_yield_strategy => restrict_to: [admin];
+            manage_pool_yield_strategy => restrict_to: [admin];
 
             admin_update_operating_status => restrict_to: [admin];
 
@@ -85,6 +120,12 @@ mod lending_market {
 
             update_pool_state => PUBLIC;
 
+            get_pool_indexes => PUBLIC;
+            get_pool_rates => PUBLIC;
+            get_rate_curve => PUBLIC;
+            get_interest_rates => PUBLIC;
+            get_flashloan_stats => PUBLIC;
+
             /* Reserve Collector methods*/
 
             collect_reserve => restrict_to: [reserve_collector];
@@ -102,6 +143,12 @@ mod lending_market {
             update_cdp => PUBLIC;
             update_delegatee_cdp => PUBLIC;
 
+            // Deleverage order methods
+
+            create_deleverage_order => PUBLIC;
+            cancel_deleverage_order => PUBLIC;
+            execute_deleverage_order => PUBLIC;
+
             // Flashloan methods
 
             take_batch_flashloan => PUBLIC;
@@ -111,12 +158,26 @@ mod lending_market {
 
             contribute => PUBLIC;
             redeem => PUBLIC;
+            request_redeem => PUBLIC;
+            claim_redemption => PUBLIC;
 
             add_collateral => PUBLIC;
             remove_collateral => PUBLIC;
             borrow => PUBLIC;
             repay => PUBLIC;
 
+            // Fixed-term loan methods
+
+            borrow_fixed_term => PUBLIC;
+            repay_fixed_term => PUBLIC;
+            convert_fixed_term_loans => PUBLIC;
+
+            // Stable rate loan methods
+
+            borrow_stable => PUBLIC;
+            repay_stable => PUBLIC;
+            rebalance_stable_rate => PUBLIC;
+
             // Liquidation methods
 
             refinance => PUBLIC;
@@ -192,8 +253,23 @@ mod lending_market {
         ///
         market_config: MarketConfig,
 
+        /// Flashloan fee discount rate granted to holders of the badge resource
+        flashloan_fee_tiers: IndexMap<ResourceAddress, Decimal>,
+
         ///
         delegatee_cdp_ids: KeyValueStore<(NonFungibleLocalId, u64), NonFungibleLocalId>,
+
+        /// Stop-loss orders attached to CDPs, indexed by order id
+        deleverage_orders: KeyValueStore<u64, (NonFungibleLocalId, DeleverageOrder)>,
+
+        ///
+        deleverage_order_counter: u64,
+
+        /// Tickets of the pool units queued for redemption
+        redemption_ticket_res_manager: ResourceManager,
+
+        ///
+        redemption_ticket_counter: u64,
     }
 
     impl LendingMarket {
@@ -201,6 +277,16 @@ mod lending_market {
             // Check inputs
             market_config.check().expect("Invalid market config");
 
+            // Prices are quoted per unit of the base currency
+            if let Some(base_currency) = market_config.base_currency {
+                assert!(
+                    ResourceManager::from_address(base_currency)
+                        .resource_type()
+                        .is_fungible(),
+                    "Base currency must be fungible"
+                );
+            }
+
             // Get address reservation for the lending market component
             let (market_component_address_reservation, market_component_address) =
                 Runtime::allocate_component_address(LendingMarket::blueprint_id());
@@ -232,7 +318,11 @@ mod lending_market {
 
             // * Create transient resource manager * //
             let transient_res_manager =
-                create_transient_res_manager(admin_rule.clone(), component_rule);
+                create_transient_res_manager(admin_rule.clone(), component_rule.clone());
+
+            // * Create redemption ticket resource manager * //
+            let redemption_ticket_res_manager =
+                create_redemption_ticket_res_manager(admin_rule.clone(), component_rule);
 
             // *  Instantiate our component with the previously created resources and addresses * //
             Self {
@@ -247,7 +337,12 @@ mod lending_market {
                 listed_assets: IndexSet::new(),
                 operating_status: OperatingStatus::new(),
                 market_config,
+                flashloan_fee_tiers: IndexMap::new(),
                 delegatee_cdp_ids: KeyValueStore::new(),
+                deleverage_orders: KeyValueStore::new(),
+                deleverage_order_counter: 0,
+                redemption_ticket_res_manager,
+                redemption_ticket_counter: 0,
             }
             .instantiate()
             .prepare_to_globalize(OwnerRole::None)
@@ -281,6 +376,8 @@ mod lending_market {
             pool_config: PoolConfig,
             interest_strategy_break_points: (Decimal, Vec<ISInputBreakPoint>),
             liquidation_threshold: LiquidationThreshold,
+            dapp_definition: Option<ComponentAddress>,
+            price_source: Option<PriceSource>,
         ) {
             assert!(
                 self.listed_assets.get(&pool_res_address).is_none(),
@@ -313,10 +410,18 @@ mod lending_market {
 
             let (pool, pool_unit_res_address) = Blueprint::<SingleResourcePool>::instantiate(
                 pool_res_address,
-                OwnerRole::None,
+                // The market admin manages the pool unit metadata and dApp definition link
+                OwnerRole::Fixed(self.admin_rule.clone()),
                 component_rule.clone(),
                 component_rule.clone(),
                 component_rule,
+                // Fees can be enabled with update_pool_fee_config once the pool is listed
+                PoolFeeConfig {
+                    contribution_fee_rate: dec!(0),
+                    redemption_fee_rate: dec!(0),
+                    fee_destination: PoolFeeDestination::Pool,
+                },
+                dapp_definition,
             );
 
             let mut interest_strategy = InterestStrategy::new();
@@ -329,8 +434,14 @@ mod lending_market {
                 )
                 .expect("Invalid interest strategy breakpoints");
 
-            let last_price_info =
-                get_price(price_feed_component, pool_res_address).expect("Price not found");
+            // Assets without a direct feed can be listed with another price source
+            let price_source = price_source.unwrap_or(PriceSource::Feed);
+
+            let base_currency = self.market_config.get_base_currency();
+
+            let last_price_info = price_source
+                .get_price(price_feed_component, pool_res_address, base_currency, None)
+                .expect("Price not found");
 
             let pool_state = LendingPoolState {
                 pool,
@@ -339,18 +450,40 @@ mod lending_market {
                 pool_res_address,
 
                 price: last_price_info.price,
+                collateral_price: last_price_info.price,
+                loan_price: last_price_info.price,
+                price_confidence: last_price_info.confidence.unwrap_or(Decimal::ZERO),
 
                 price_updated_at: Clock::current_time(TimePrecision::Minute)
                     .seconds_since_unix_epoch,
 
                 total_loan: 0.into(),
                 total_loan_unit: 0.into(),
+                fixed_term_debt: FixedRateLoan::new(
+                    Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch,
+                ),
+                stable_rate_debt: FixedRateLoan::new(
+                    Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch,
+                ),
+                blended_interest_rate: 0.into(),
+                total_flashloan_volume: 0.into(),
+                total_flashloan_lp_fees: 0.into(),
+                total_flashloan_reserve_fees: 0.into(),
+                redemption_queue: RedemptionQueue::new(pool_unit_res_address, pool_res_address),
                 interest_rate: 0.into(),
+                target_interest_rate: 0.into(),
                 interest_updated_at: Clock::current_time(TimePrecision::Minute)
                     .seconds_since_unix_epoch,
 
+                borrow_index: PreciseDecimal::ONE,
+                supply_index: PreciseDecimal::ONE,
+
                 price_feed_comp: price_feed_component,
+                fallback_price_feed_comp: None,
+                price_source,
+                base_currency,
                 interest_strategy,
+                flashloan_fee_strategy: None,
                 liquidation_threshold,
                 pool_config,
                 operating_status: OperatingStatus::new(),
@@ -380,7 +513,10 @@ mod lending_market {
 
                     let price = pool_state.price;
 
-                    let fee = pool_state.reserve.take_all();
+                    let mut fee = pool_state.reserve.take_all();
+
+                    // Contribution and redemption fees kept in the pool fee vault
+                    fee.put(pool_state.pool.withdraw_collected_fees());
 
                     (price, fee)
                 })
@@ -394,11 +530,55 @@ mod lending_market {
         ) {
             let mut pool_state = self._get_pool_state(&pool_res_address, None, None);
 
-            get_price(price_feed, pool_res_address).expect("Price not found");
+            pool_state
+                .price_source
+                .get_price(price_feed, pool_res_address, pool_state.base_currency, None)
+                .expect("Price not found");
 
             pool_state.price_feed_comp = price_feed;
         }
 
+        pub fn update_price_source(
+            &mut self,
+            pool_res_address: ResourceAddress,
+            price_source: PriceSource,
+        ) {
+            let mut pool_state = self._get_pool_state(&pool_res_address, None, None);
+
+            price_source
+                .get_price(
+                    pool_state.price_feed_comp,
+                    pool_res_address,
+                    pool_state.base_currency,
+                    None,
+                )
+                .expect("Price not found");
+
+            pool_state.price_source = price_source;
+        }
+
+        pub fn update_fallback_price_feed(
+            &mut self,
+            pool_res_address: ResourceAddress,
+            fallback_price_feed: Option<Global<AnyComponent>>,
+        ) {
+            let mut pool_state = self._get_pool_state(&pool_res_address, None, None);
+
+            if let Some(fallback_price_feed) = fallback_price_feed {
+                pool_state
+                    .price_source
+                    .get_price(
+                        fallback_price_feed,
+                        pool_res_address,
+                        pool_state.base_currency,
+                        None,
+                    )
+                    .expect("Price not found");
+            }
+
+            pool_state.fallback_price_feed_comp = fallback_price_feed;
+        }
+
         pub fn update_liquidation_threshold(
             &mut self,
             pool_res_address: ResourceAddress,
@@ -412,6 +592,7 @@ mod lending_market {
                 .expect("Invalid liquidation threshold");
         }
 
+        // Set a piecewise-linear interest model, the max rate of the pool is kept
         pub fn update_interest_strategy(
             &mut self,
             pool_res_address: ResourceAddress,
@@ -426,6 +607,132 @@ mod lending_market {
                 .expect("Invalid interest strategy breakpoints");
         }
 
+        // Select the interest model of a pool. The optional max rate caps the rate of any model
+        pub fn update_interest_model(
+            &mut self,
+            pool_res_address: ResourceAddress,
+            interest_model: InterestModelInput,
+            max_rate: Option<Decimal>,
+        ) {
+            let mut pool_state = self._get_pool_state(&pool_res_address, None, None);
+
+            pool_state
+                .interest_strategy
+                .set_model(interest_model, max_rate)
+                .expect("Invalid interest strategy");
+        }
+
+        // Set or remove the utilization based flashloan fee curve of a pool.
+        // The curve uses the same break points as the interest strategy
+        pub fn update_flashloan_fee_strategy(
+            &mut self,
+            pool_res_address: ResourceAddress,
+            flashloan_fee_break_points: Option<(Decimal, Vec<ISInputBreakPoint>)>,
+        ) {
+            let mut pool_state = self._get_pool_state(&pool_res_address, None, None);
+
+            pool_state.flashloan_fee_strategy =
+                flashloan_fee_break_points.map(|(initial_rate, break_points)| {
+                    let mut flashloan_fee_strategy = InterestStrategy::new();
+
+                    flashloan_fee_strategy
+                        .set_breakpoints(initial_rate, break_points)
+                        .expect("Invalid flashloan fee strategy breakpoints");
+
+                    flashloan_fee_strategy
+                });
+        }
+
+        // Set or remove the flashloan fee discount granted to a badge resource.
+        // A discount rate of 1 makes flashloans fee exempt for the badge holders
+        pub fn update_flashloan_fee_tier(
+            &mut self,
+            badge_res_address: ResourceAddress,
+            fee_discount_rate: Option<Decimal>,
+        ) {
+            match fee_discount_rate {
+                Some(fee_discount_rate) => {
+                    assert!(
+                        is_valid_rate(fee_discount_rate),
+                        "INVALID_INPUT: Fee discount rate must be in the range [0, 1]"
+                    );
+
+                    self.flashloan_fee_tiers
+                        .insert(badge_res_address, fee_discount_rate);
+                }
+                None => {
+                    self.flashloan_fee_tiers.remove(&badge_res_address);
+                }
+            }
+        }
+
+        // Set the rate added to the pool interest rate while redemptions are queued
+        pub fn update_redemption_queue_rate_premium(
+            &mut self,
+            pool_res_address: ResourceAddress,
+            rate_premium: Decimal,
+        ) {
+            assert!(
+                rate_premium >= dec!(0),
+                "INVALID_INPUT: Rate premium must be non-negative"
+            );
+
+            let mut pool_state = self._get_pool_state(&pool_res_address, None, None);
+
+            pool_state.redemption_queue.rate_premium = rate_premium;
+        }
+
+        // Set the minimum contribution amount and redemption units accepted by a pool
+        pub fn update_pool_min_amounts(
+            &mut self,
+            pool_res_address: ResourceAddress,
+            min_contribution_amount: Decimal,
+            min_redemption_units: Decimal,
+        ) {
+            self._get_pool_state(&pool_res_address, None, None)
+                .pool
+                .update_min_amounts(min_contribution_amount, min_redemption_units);
+        }
+
+        // Set the contribution and redemption fees of a pool
+        pub fn update_pool_fee_config(
+            &mut self,
+            pool_res_address: ResourceAddress,
+            fee_config: PoolFeeConfig,
+        ) {
+            self._get_pool_state(&pool_res_address, None, None)
+                .pool
+                .update_fee_config(fee_config);
+        }
+
+        // Set the strategy earning yield on the idle liquidity of a pool
+        pub fn update_pool_yield_strategy(
+            &mut self,
+            pool_res_address: ResourceAddress,
+            yield_strategy: Option<ComponentAddress>,
+            max_strategy_share: Decimal,
+        ) {
+            self._get_pool_state(&pool_res_address, None, None)
+                .pool
+                .update_yield_strategy(yield_strategy, max_strategy_share);
+        }
+
+        // Allocate, recall or sync the liquidity of a pool yield strategy
+        pub fn manage_pool_yield_strategy(
+            &mut self,
+            pool_res_address: ResourceAddress,
+            action: YieldStrategyAction,
+        ) {
+            let mut pool_state = self._get_pool_state(&pool_res_address, None, None);
+
+            let unit_ratio = pool_state.pool.get_pool_unit_ratio();
+
+            pool_state.pool.manage_yield_strategy(action);
+
+            // Sync moves the pool unit value with the strategy gains and losses
+            pool_state.sync_supply_index(unit_ratio);
+        }
+
         pub fn update_market_config(&mut self, value: UpdateMarketConfigInput) {
             self.market_config
                 .update(value)
@@ -458,6 +765,87 @@ mod lending_market {
             );
         }
 
+        // Get the borrow and supply indexes of a pool as of its last interest update
+        pub fn get_pool_indexes(
+            &self,
+            pool_res_address: ResourceAddress,
+        ) -> (PreciseDecimal, PreciseDecimal) {
+            let pool_state = self
+                .pool_states
+                .get(&pool_res_address)
+                .expect("Pool state not found for provided resource");
+
+            (pool_state.borrow_index, pool_state.supply_index)
+        }
+
+        // Get the pool rates currently applied and, if provided, the interest strategy rates at a projected utilization.
+        // Rates are computed from the last updated pool state and exclude flashloan fees
+        pub fn get_pool_rates(
+            &self,
+            pool_res_address: ResourceAddress,
+            projected_utilization: Option<Decimal>,
+        ) -> (PoolRates, Option<PoolRates>) {
+            let pool_state = self
+                .pool_states
+                .get(&pool_res_address)
+                .expect("Pool state not found for provided resource");
+
+            let current_rates = pool_state.get_applied_pool_rates();
+
+            let projected_rates = projected_utilization.map(|utilization| {
+                pool_state
+                    .get_pool_rates(utilization)
+                    .expect("Error getting projected pool rates")
+            });
+
+            (current_rates, projected_rates)
+        }
+
+        // Sample the interest strategy rate curve at evenly spaced utilizations between 0 and 1.
+        // These are target rates, the applied rate follows them within the configured max rate change
+        pub fn get_rate_curve(
+            &self,
+            pool_res_address: ResourceAddress,
+            samples: u16,
+        ) -> Vec<PoolRates> {
+            let pool_state = self
+                .pool_states
+                .get(&pool_res_address)
+                .expect("Pool state not found for provided resource");
+
+            pool_state
+                .interest_strategy
+                .get_rate_curve(samples, pool_state.pool_config.protocol_interest_fee_rate)
+                .expect("Error sampling the rate curve")
+        }
+
+        // Get the target interest rate given by the interest strategy and the rate applied to loans
+        pub fn get_interest_rates(&self, pool_res_address: ResourceAddress) -> (Decimal, Decimal) {
+            let pool_state = self
+                .pool_states
+                .get(&pool_res_address)
+                .expect("Pool state not found for provided resource");
+
+            (pool_state.target_interest_rate, pool_state.interest_rate)
+        }
+
+        // Get the total flashloan volume of a pool and the fees paid to the lenders and to the reserve
+        pub fn get_flashloan_stats(
+            &self,
+            pool_res_address: ResourceAddress,
+        ) -> (Decimal, Decimal, Decimal) {
+            let pool_state = self
+                .pool_states
+                .get(&pool_res_address)
+                .expect("Pool state not found for provided resource");
+
+            (
+                pool_state.total_flashloan_volume,
+                pool_state.total_flashloan_lp_fees,
+                pool_state.total_flashloan_reserve_fees,
+            )
+        }
+
         ///
         fn _update_operating_status(
             &mut self,
@@ -523,6 +911,8 @@ mod lending_market {
                 collaterals: IndexMap::new(),
                 loans: IndexMap::new(),
                 delegatee_loans: IndexMap::new(),
+                fixed_term_loans: IndexMap::new(),
+                stable_rate_loans: IndexMap::new(),
                 minted_at: now,
                 updated_at: now,
             };
@@ -600,6 +990,8 @@ mod lending_market {
                 collaterals: IndexMap::new(),
                 loans: IndexMap::new(),
                 delegatee_loans: IndexMap::new(),
+                fixed_term_loans: IndexMap::new(),
+                stable_rate_loans: IndexMap::new(),
                 minted_at: now,
                 updated_at: now,
             };
@@ -783,14 +1175,226 @@ mod lending_market {
             single_save_cdp_macro!(self, delegatee_cdp_data);
         }
 
+        ///* DELEVERAGE ORDER METHODS * ///
+
+        // Register a stop-loss order on a CDP. The order can be executed by anyone once the CDP LTV
+        // goes above the order trigger
+        pub fn create_deleverage_order(&mut self, cdp_proof: Proof, order: DeleverageOrder) -> u64 {
+            order.check().expect("Invalid deleverage order");
+
+            assert!(
+                self.listed_assets.contains(&order.loan_res_address)
+                    && self.listed_assets.contains(&order.collateral_res_address),
+                "Deleverage order resources must be listed"
+            );
+
+            let cdp_id = self._validate_cdp_proof(cdp_proof);
+
+            let cdp_data = WrappedCDPData::new(&self.cdp_res_manager, &cdp_id);
+
+            // Delegatee CDPs do not hold collateral
+            assert!(
+                !cdp_data.is_delegatee(),
+                "Delegatee CDP can not create deleverage order"
+            );
+
+            let order_id = self._get_new_deleverage_order_id();
+
+            self.deleverage_orders.insert(order_id, (cdp_id, order));
+
+            order_id
+        }
+
+        pub fn cancel_deleverage_order(&mut self, cdp_proof: Proof, order_id: u64) {
+            let cdp_id = self._validate_cdp_proof(cdp_proof);
+
+            let (order_cdp_id, _) = self
+                .deleverage_orders
+                .remove(&order_id)
+                .expect("Deleverage order not found");
+
+            assert!(
+                order_cdp_id == cdp_id,
+                "Deleverage order not linked to provided CDP"
+            );
+        }
+
+        // Sell the order collateral, repay the order loan and send back the keeper fee.
+        // Swap proceeds not used for the repayment are added back to the CDP as collateral
+        pub fn execute_deleverage_order(&mut self, order_id: u64) -> Bucket {
+            self._check_operating_status(OperatingService::RemoveCollateral);
+            self._check_operating_status(OperatingService::Repay);
+
+            let (cdp_id, order) = self
+                .deleverage_orders
+                .remove(&order_id)
+                .expect("Deleverage order not found");
+
+            let (mut cdp_data, mut delegator_cdp_data) = self._get_cdp_data(&cdp_id, true);
+
+            let initial_ltv = {
+                let mut cdp_health_checker = CDPHealthChecker::new(
+                    &cdp_data,
+                    delegator_cdp_data.as_ref(),
+                    &mut self.pool_states,
+                );
+
+                cdp_health_checker
+                    .can_deleverage(order.trigger_ltv)
+                    .expect("Error checking CDP");
+
+                cdp_health_checker
+                    .get_loan_to_value_ratio()
+                    .expect("Error checking CDP")
+            };
+
+            let loan_price = self
+                ._get_pool_state(&order.loan_res_address, Some(OperatingService::Repay), None)
+                .loan_price;
+
+            let (collaterals, collateral_price) = {
+                let mut pool_state = self._get_pool_state(
+                    &order.collateral_res_address,
+                    Some(OperatingService::RemoveCollateral),
+                    None,
+                );
+
+                let unit_ratio = pool_state.pool.get_pool_unit_ratio();
+
+                // Sell enough collateral to cover the loan amount after slippage
+                let collateral_amount =
+                    (order.loan_amount * loan_price * (Decimal::ONE + order.max_slippage_rate))
+                        / pool_state.collateral_price;
+
+                let collateral_units = (collateral_amount * unit_ratio)
+                    .checked_truncate(RoundingMode::ToNearestMidpointToEven)
+                    .unwrap()
+                    .min(cdp_data.get_collateral_units(order.collateral_res_address));
+
+                cdp_data
+                    .update_collateral(order.collateral_res_address, -collateral_units)
+                    .expect("Error updating collateral for CDP");
+
+                let pool_units = pool_state
+                    .remove_pool_units_from_collateral(collateral_units)
+                    .expect("Error redeeming pool units from collateral");
+
+                (
+                    pool_state.protected_redeem_proxy(pool_units),
+                    pool_state.collateral_price,
+                )
+            };
+
+            let collateral_value = collaterals.amount() * collateral_price;
+
+            let mut proceeds = Global::<AnyComponent>::from(order.swap_component)
+                .call_raw::<Bucket>("swap", scrypto_args!(collaterals, order.loan_res_address));
+
+            assert!(
+                proceeds.resource_address() == order.loan_res_address,
+                "Swap returned an unexpected resource"
+            );
+
+            assert!(
+                proceeds.amount() * loan_price
+                    >= collateral_value * (Decimal::ONE - order.max_slippage_rate),
+                "Swap slippage exceeds the order limit"
+            );
+
+            let keeper_fee = proceeds.take_advanced(
+                proceeds.amount() * order.keeper_fee_rate,
+                WithdrawStrategy::Rounded(RoundingMode::ToNearestMidpointToEven),
+            );
+
+            let payment = proceeds.take_advanced(
+                order.loan_amount.min(proceeds.amount()),
+                WithdrawStrategy::Rounded(RoundingMode::ToNearestMidpointToEven),
+            );
+
+            let (remainders, _) = self._repay_internal(
+                &mut cdp_data,
+                &mut delegator_cdp_data,
+                vec![payment],
+                None,
+                false,
+            );
+
+            // Payment left after the variable rate loan goes to the fixed rate loans
+            let remainders: Vec<Bucket> = remainders
+                .into_iter()
+                .map(|mut remainder| {
+                    if !remainder.is_empty() {
+                        self._repay_cdp_fixed_rate_loans(&mut cdp_data, &mut remainder);
+                    }
+
+                    remainder
+                })
+                .collect();
+
+            let mut leftovers: Vec<Bucket> = Vec::new();
+
+            for bucket in remainders.into_iter().chain(std::iter::once(proceeds)) {
+                if bucket.is_empty() {
+                    bucket.drop_empty();
+                } else {
+                    leftovers.push(bucket);
+                }
+            }
+
+            single_save_cdp_macro!(self, cdp_data);
+
+            if !leftovers.is_empty() {
+                self._add_collateral_internal(cdp_id.clone(), leftovers);
+            }
+
+            // Slippage and keeper fee must not leave the CDP in a worse state than before the order
+            let (cdp_data, delegator_cdp_data) = self._get_cdp_data(&cdp_id, true);
+
+            let final_ltv = CDPHealthChecker::new(
+                &cdp_data,
+                delegator_cdp_data.as_ref(),
+                &mut self.pool_states,
+            )
+            .get_loan_to_value_ratio()
+            .expect("Error checking CDP");
+
+            assert!(
+                final_ltv <= initial_ltv,
+                "Deleverage order execution must not increase the CDP LTV"
+            );
+
+            emit_cdp_event!(cdp_id, CDPUpdatedEvenType::Deleverage);
+
+            keeper_fee
+        }
+
         // / * Flashloan methods * ///
 
         pub fn take_batch_flashloan(
             &mut self,
             loan_amounts: IndexMap<ResourceAddress, Decimal>,
+            badge_proof: Option<Proof>,
         ) -> (Vec<Bucket>, Bucket) {
             self._check_operating_status(OperatingService::Flashloan);
 
+            let fee_discount_rate = match badge_proof {
+                Some(badge_proof) => {
+                    // The proof resource is only used to look up the fee tiers, which are all set by the admin
+                    let checked_proof = badge_proof.skip_checking();
+
+                    assert!(
+                        checked_proof.amount() > Decimal::ZERO,
+                        "INVALID_INPUT: Empty badge proof"
+                    );
+
+                    *self
+                        .flashloan_fee_tiers
+                        .get(&checked_proof.resource_address())
+                        .expect("No flashloan fee tier found for provided badge")
+                }
+                None => Decimal::ZERO,
+            };
+
             let mut loans: Vec<Bucket> = Vec::new();
             let mut terms: IndexMap<ResourceAddress, BatchFlashloanItem> = IndexMap::new();
 
@@ -809,19 +1413,33 @@ mod lending_market {
                     .check_operating_status(OperatingService::Flashloan)
                     .expect("Flashloan is not enabled for the pool");
 
-                let fee_amount = (*amount) * pool_state.pool_config.flashloan_fee_rate;
+                let fee_amount = (*amount)
+                    * pool_state
+                        .get_flashloan_fee_rate(*amount)
+                        .expect("Error getting flashloan fee rate")
+                    * (Decimal::ONE - fee_discount_rate);
+
+                let protocol_fee_amount =
+                    fee_amount * pool_state.pool_config.protocol_flashloan_fee_rate;
 
                 let loan_term = BatchFlashloanItem {
                     fee_amount,
+                    fee_discount_rate,
                     loan_amount: *amount,
                     paid_back: false,
                 };
 
-                let loan = pool_state.pool.protected_withdraw(
-                    *amount,
-                    WithdrawType::TemporaryUse,
-                    WithdrawStrategy::Rounded(RoundingMode::ToNearestMidpointToEven),
-                );
+                let loan = pool_state
+                    .withdraw_for_flashloan(*amount)
+                    .expect("Error in withdraw_for_flashloan");
+
+                Runtime::emit_event(FlashloanTakenEvent {
+                    pool_res_address: *pool_res_address,
+                    amount: *amount,
+                    fee_amount,
+                    protocol_fee_amount,
+                    lp_fee_amount: fee_amount - protocol_fee_amount,
+                });
 
                 loans.push(loan);
                 terms.insert(*pool_res_address, loan_term);
@@ -890,6 +1508,8 @@ mod lending_market {
 
                 let lp_fee_amount = loan_term.fee_amount - protocol_fee_amount;
 
+                let unit_ratio = pool_state.pool.get_pool_unit_ratio();
+
                 pool_state.pool.protected_deposit(
                     payment.take_advanced(
                         lp_fee_amount,
@@ -898,11 +1518,25 @@ mod lending_market {
                     DepositType::LiquiditySupply,
                 );
 
+                pool_state.sync_supply_index(unit_ratio);
+
                 pool_state.reserve.put(payment.take_advanced(
                     protocol_fee_amount,
                     WithdrawStrategy::Rounded(RoundingMode::ToNearestMidpointToEven),
                 ));
 
+                pool_state.total_flashloan_volume += loan_term.loan_amount;
+                pool_state.total_flashloan_lp_fees += lp_fee_amount;
+                pool_state.total_flashloan_reserve_fees += protocol_fee_amount;
+
+                Runtime::emit_event(FlashloanRepaidEvent {
+                    pool_res_address,
+                    amount: loan_term.loan_amount,
+                    fee_amount: loan_term.fee_amount,
+                    protocol_fee_amount,
+                    lp_fee_amount,
+                });
+
                 loan_term.paid_back = true;
 
                 remainders.push(payment);
@@ -924,7 +1558,7 @@ mod lending_market {
         pub fn contribute(&mut self, assets: Bucket) -> Bucket {
             self._check_operating_status(OperatingService::Contribute);
 
-            let pool_state = self._get_pool_state(
+            let mut pool_state = self._get_pool_state(
                 &assets.resource_address(),
                 Some(OperatingService::Contribute),
                 None,
@@ -947,6 +1581,67 @@ mod lending_market {
                 .redeem_proxy(pool_units)
         }
 
+        // Queue pool units for redemption when the pool liquidity is not enough to redeem them.
+        // The returned ticket is used to claim the redeemed assets, net of the redemption fee, as the queue is filled
+        pub fn request_redeem(&mut self, pool_units: Bucket) -> Bucket {
+            self._check_operating_status(OperatingService::Redeem);
+
+            let pool_res_address = *self
+                .reverse_pool_unit_refs
+                .get(&pool_units.resource_address())
+                .expect("Pool unit not found");
+
+            let units = pool_units.amount();
+
+            let mut pool_state =
+                self._get_pool_state(&pool_res_address, Some(OperatingService::Redeem), None);
+
+            let request_index = pool_state
+                .push_redemption_request(pool_units)
+                .expect("Error queuing redemption request");
+
+            drop(pool_state);
+
+            self.redemption_ticket_counter += 1;
+
+            self.redemption_ticket_res_manager.mint_non_fungible(
+                &NonFungibleLocalId::Integer(self.redemption_ticket_counter.into()),
+                RedemptionTicketData {
+                    pool_res_address,
+                    request_index,
+                    units,
+                },
+            )
+        }
+
+        // Claim the assets redeemed for a ticket so far.
+        // The ticket is burned once its request is fully filled, otherwise it is returned
+        pub fn claim_redemption(&mut self, ticket: Bucket) -> (Bucket, Option<Bucket>) {
+            assert!(
+                ticket.resource_address() == self.redemption_ticket_res_manager.address(),
+                "Invalid redemption ticket"
+            );
+
+            let ticket_data: RedemptionTicketData = ticket.as_non_fungible().non_fungible().data();
+
+            // The assets were redeemed when the queue was filled, no pool state update is needed
+            let (assets, filled) = self
+                .pool_states
+                .get_mut(&ticket_data.pool_res_address)
+                .expect("Pool state not found")
+                .redemption_queue
+                .claim(ticket_data.request_index)
+                .expect("Error claiming redemption");
+
+            if filled {
+                self.redemption_ticket_res_manager.burn(ticket);
+
+                (assets, None)
+            } else {
+                (assets, Some(ticket))
+            }
+        }
+
         pub fn add_collateral(&mut self, cdp_proof: Proof, deposits: Vec<Bucket>) {
             let cdp_id = self._validate_cdp_proof(cdp_proof);
 
@@ -987,6 +1682,7 @@ mod lending_market {
                         .remove_pool_units_from_collateral(withdraw_collateral_units)
                         .expect("Error redeeming pool units from collateral");
 
+                    // User withdrawals pay the redemption fee and respect the pool minimum
                     let returned_assets = if !keep_deposit_unit {
                         pool_state.redeem_proxy(deposit_units)
                     } else {
@@ -1124,6 +1820,246 @@ mod lending_market {
             (remainders, payment_value)
         }
 
+        // Take a loan at a rate locked until maturity. `duration` is in minutes and
+        // an additional borrow can not end after the maturity of the existing loan, it is locked to that maturity
+        pub fn borrow_fixed_term(
+            &mut self,
+            cdp_proof: Proof,
+            pool_res_address: ResourceAddress,
+            amount: Decimal,
+            duration: i64,
+        ) -> Bucket {
+            let cdp_id = self._validate_cdp_proof(cdp_proof);
+
+            let (mut cdp_data, _) = self._get_cdp_data(&cdp_id, false);
+
+            let max_duration = self
+                .pool_states
+                .get(&pool_res_address)
+                .expect("Pool state not found for provided resource")
+                .pool_config
+                .fixed_term_loan
+                .as_ref()
+                .expect("Fixed-term loans are not enabled for this pool")
+                .max_duration;
+
+            assert!(
+                duration > 0 && duration <= max_duration,
+                "INVALID_INPUT: Duration must be in the range (0, {}]",
+                max_duration
+            );
+
+            let (borrowed_assets, rate) = self._take_fixed_rate_loan(
+                &cdp_data,
+                pool_res_address,
+                FixedRateLoanType::FixedTerm,
+                amount,
+            );
+
+            let now = Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch;
+
+            let maturity = now + duration * SECOND_PER_MINUTE;
+
+            let mut fixed_term_loan = match cdp_data.get_fixed_term_loan(pool_res_address) {
+                Some(fixed_term_loan) => {
+                    assert!(
+                        maturity <= fixed_term_loan.maturity,
+                        "INVALID_INPUT: Additional fixed-term borrows can not end after the maturity of the existing loan"
+                    );
+
+                    fixed_term_loan
+                }
+                None => FixedTermLoan {
+                    loan: FixedRateLoan::new(now),
+                    maturity,
+                },
+            };
+
+            fixed_term_loan
+                .loan
+                .increase(amount, rate, now)
+                .expect("Error updating fixed-term loan");
+
+            cdp_data.update_fixed_term_loan(pool_res_address, Some(fixed_term_loan));
+
+            CDPHealthChecker::new(&cdp_data, None, &mut self.pool_states)
+                .check_cdp()
+                .expect("Error checking CDP");
+
+            single_save_cdp_macro!(self, cdp_data);
+
+            emit_cdp_event!(cdp_id, CDPUpdatedEvenType::Borrow);
+
+            borrowed_assets
+        }
+
+        // Repay a fixed-term loan, early repayment is allowed. Returns the unused payment
+        pub fn repay_fixed_term(&mut self, cdp_proof: Proof, mut payment: Bucket) -> Bucket {
+            let cdp_id = self._validate_cdp_proof(cdp_proof);
+
+            let (mut cdp_data, _) = self._get_cdp_data(&cdp_id, false);
+
+            let pool_res_address = payment.resource_address();
+
+            let mut fixed_term_loan = cdp_data
+                .get_fixed_term_loan(pool_res_address)
+                .expect("No fixed-term loan found for provided resource");
+
+            self._repay_fixed_rate_loan(
+                FixedRateLoanType::FixedTerm,
+                &mut fixed_term_loan.loan,
+                &mut payment,
+            );
+
+            if fixed_term_loan.loan.principal == Decimal::ZERO {
+                cdp_data.update_fixed_term_loan(pool_res_address, None);
+            } else {
+                cdp_data.update_fixed_term_loan(pool_res_address, Some(fixed_term_loan));
+            }
+
+            single_save_cdp_macro!(self, cdp_data);
+
+            emit_cdp_event!(cdp_id, CDPUpdatedEvenType::Repay);
+
+            payment
+        }
+
+        // Convert the matured fixed-term loans of a CDP to variable rate loans. Can be called by anyone
+        pub fn convert_fixed_term_loans(&mut self, cdp_id: NonFungibleLocalId) {
+            let (mut cdp_data, _) = self._get_cdp_data(&cdp_id, false);
+
+            let converted_count = self._convert_fixed_rate_loans(&mut cdp_data, true);
+
+            assert!(converted_count > 0, "No matured fixed-term loan to convert");
+
+            single_save_cdp_macro!(self, cdp_data);
+
+            emit_cdp_event!(cdp_id, CDPUpdatedEvenType::ConvertFixedTermLoan);
+        }
+
+        // Take a loan at a rate locked until the loan is rebalanced.
+        // Additional borrows are blended with the existing stable rate loan
+        pub fn borrow_stable(
+            &mut self,
+            cdp_proof: Proof,
+            pool_res_address: ResourceAddress,
+            amount: Decimal,
+        ) -> Bucket {
+            let cdp_id = self._validate_cdp_proof(cdp_proof);
+
+            let (mut cdp_data, _) = self._get_cdp_data(&cdp_id, false);
+
+            let (borrowed_assets, rate) = self._take_fixed_rate_loan(
+                &cdp_data,
+                pool_res_address,
+                FixedRateLoanType::Stable,
+                amount,
+            );
+
+            let now = Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch;
+
+            let mut stable_rate_loan = cdp_data
+                .get_stable_rate_loan(pool_res_address)
+                .unwrap_or(FixedRateLoan::new(now));
+
+            stable_rate_loan
+                .increase(amount, rate, now)
+                .expect("Error updating stable rate loan");
+
+            cdp_data.update_stable_rate_loan(pool_res_address, Some(stable_rate_loan));
+
+            CDPHealthChecker::new(&cdp_data, None, &mut self.pool_states)
+                .check_cdp()
+                .expect("Error checking CDP");
+
+            single_save_cdp_macro!(self, cdp_data);
+
+            emit_cdp_event!(cdp_id, CDPUpdatedEvenType::Borrow);
+
+            borrowed_assets
+        }
+
+        // Repay a stable rate loan. Returns the unused payment
+        pub fn repay_stable(&mut self, cdp_proof: Proof, mut payment: Bucket) -> Bucket {
+            let cdp_id = self._validate_cdp_proof(cdp_proof);
+
+            let (mut cdp_data, _) = self._get_cdp_data(&cdp_id, false);
+
+            let pool_res_address = payment.resource_address();
+
+            let mut stable_rate_loan = cdp_data
+                .get_stable_rate_loan(pool_res_address)
+                .expect("No stable rate loan found for provided resource");
+
+            self._repay_fixed_rate_loan(
+                FixedRateLoanType::Stable,
+                &mut stable_rate_loan,
+                &mut payment,
+            );
+
+            if stable_rate_loan.principal == Decimal::ZERO {
+                cdp_data.update_stable_rate_loan(pool_res_address, None);
+            } else {
+                cdp_data.update_stable_rate_loan(pool_res_address, Some(stable_rate_loan));
+            }
+
+            single_save_cdp_macro!(self, cdp_data);
+
+            emit_cdp_event!(cdp_id, CDPUpdatedEvenType::Repay);
+
+            payment
+        }
+
+        // Move a stable rate loan up to the current stable rate when the pool rebalance conditions are met.
+        // Can be called by anyone, so the rate of a loan is never lowered
+        pub fn rebalance_stable_rate(
+            &mut self,
+            cdp_id: NonFungibleLocalId,
+            pool_res_address: ResourceAddress,
+        ) {
+            let (mut cdp_data, _) = self._get_cdp_data(&cdp_id, false);
+
+            let mut stable_rate_loan = cdp_data
+                .get_stable_rate_loan(pool_res_address)
+                .expect("No stable rate loan found for provided resource");
+
+            let now = Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch;
+
+            let mut pool_state = self._get_pool_state(&pool_res_address, None, None);
+
+            pool_state
+                .check_stable_rate_rebalance()
+                .expect("Error checking stable rate rebalance");
+
+            let new_rate = pool_state
+                .get_fixed_rate(FixedRateLoanType::Stable, Decimal::ZERO)
+                .expect("Error getting stable rate");
+
+            assert!(
+                stable_rate_loan.principal * new_rate > stable_rate_loan.rate_weighted_principal,
+                "Stable rate loans can only be rebalanced to a higher rate"
+            );
+
+            let previous_rate_weighted_principal = stable_rate_loan
+                .update_rate(new_rate, now)
+                .expect("Error updating stable rate loan");
+
+            pool_state
+                .rebalance_stable_rate(
+                    previous_rate_weighted_principal,
+                    stable_rate_loan.rate_weighted_principal,
+                )
+                .expect("Error rebalancing stable rate");
+
+            drop(pool_state);
+
+            cdp_data.update_stable_rate_loan(pool_res_address, Some(stable_rate_loan));
+
+            single_save_cdp_macro!(self, cdp_data);
+
+            emit_cdp_event!(cdp_id, CDPUpdatedEvenType::RebalanceStableRate);
+        }
+
         pub fn refinance(
             &mut self,
             cdp_id: NonFungibleLocalId,
@@ -1131,6 +2067,9 @@ mod lending_market {
         ) -> (Vec<Bucket>, Decimal) {
             let (mut cdp_data, mut delegator_cdp_data) = self._get_cdp_data(&cdp_id, true);
 
+            // Fixed rate loans are repaid as variable rate loans
+            self._convert_fixed_rate_loans(&mut cdp_data, false);
+
             CDPHealthChecker::new(
                 &cdp_data,
                 delegator_cdp_data.as_ref(),
@@ -1169,6 +2108,9 @@ mod lending_market {
 
             let (mut cdp_data, mut delegator_cdp_data) = self._get_cdp_data(&cdp_id, true);
 
+            // Fixed rate loans are liquidated as variable rate loans
+            self._convert_fixed_rate_loans(&mut cdp_data, false);
+
             let mut cdp_health_checker = CDPHealthChecker::new(
                 &cdp_data,
                 delegator_cdp_data.as_ref(),
@@ -1252,6 +2194,9 @@ mod lending_market {
 
             let (mut cdp_data, mut delegator_cdp_data) = self._get_cdp_data(&cdp_id, true);
 
+            // Fixed rate loans are liquidated as variable rate loans
+            self._convert_fixed_rate_loans(&mut cdp_data, false);
+
             CDPHealthChecker::new(
                 &cdp_data,
                 delegator_cdp_data.as_ref(),
@@ -1365,7 +2310,7 @@ mod lending_market {
                     .checked_truncate(RoundingMode::ToNearestMidpointToEven)
                     .unwrap();
 
-                let mut max_collateral_value = max_collateral_amount * pool_state.price;
+                let mut max_collateral_value = max_collateral_amount * pool_state.collateral_price;
 
                 max_collateral_value = max_collateral_value.min(bonus_rate * temp_requested_value);
 
@@ -1373,7 +2318,8 @@ mod lending_market {
 
                 returned_collaterals_value += max_collateral_value / bonus_rate;
 
-                let collateral_units = ((max_collateral_value / pool_state.price) * unit_ratio)
+                let collateral_units = ((max_collateral_value / pool_state.collateral_price)
+                    * unit_ratio)
                     .checked_truncate(RoundingMode::ToNearestMidpointToEven)
                     .unwrap();
 
@@ -1385,7 +2331,7 @@ mod lending_market {
                     .remove_pool_units_from_collateral(collateral_units)
                     .expect("Error redeeming pool units from collateral");
 
-                let mut collaterals = pool_state.redeem_proxy(pool_unit);
+                let mut collaterals = pool_state.protected_redeem_proxy(pool_unit);
                 let protocol_fee_amount = collaterals.amount()
                     * pool_state.pool_config.protocol_liquidation_fee_rate
                     * pool_state.pool_config.liquidation_bonus_rate;
@@ -1426,26 +2372,24 @@ mod lending_market {
                 |(mut remainders, mut total_payment_value), mut payment| {
                     let pool_res_address = payment.resource_address();
 
-                    let mut pool_state = self._get_pool_state(&pool_res_address, None, None);
+                    let mut pool_state = self._get_pool_state(
+                        &pool_res_address,
+                        (!for_liquidation).then_some(OperatingService::Repay),
+                        None,
+                    );
 
-                    // ! Liquidation
+                    // ! Liquidation, the repay status is checked when getting the pool state
                     if for_liquidation {
                         pool_state
                             .check_operating_status(OperatingService::Liquidation)
                             .expect("Liquidation is not enabled for the pool");
-
-                    // ! Repay
-                    } else {
-                        pool_state
-                            .check_operating_status(OperatingService::Repay)
-                            .expect("Borrow is not enabled for the pool");
                     }
 
                     let unit_ratio = pool_state
                         .get_loan_unit_ratio()
                         .expect("Error getting loan unit ratio for provided resource");
 
-                    let (_, pool_borrowed_amount) = pool_state.pool.get_pooled_amount();
+                    let (_, pool_borrowed_amount) = pool_state.get_pooled_amount();
 
                     let position_loan_units = cdp_data.get_loan_unit(pool_res_address);
 
@@ -1460,8 +2404,8 @@ mod lending_market {
 
                     max_loan_amount = max_loan_amount.min(payment.amount());
 
-                    let mut max_loan_value = (max_loan_amount * pool_state.price)
-                        .min(pool_borrowed_amount * pool_state.price);
+                    let mut max_loan_value = (max_loan_amount * pool_state.loan_price)
+                        .min(pool_borrowed_amount * pool_state.loan_price);
 
                     // ! Liquidation
                     if payment_value.is_some() {
@@ -1474,7 +2418,7 @@ mod lending_market {
                         );
                     };
 
-                    max_loan_amount = max_loan_value / pool_state.price;
+                    max_loan_amount = max_loan_value / pool_state.loan_price;
 
                     let delta_loan_unit = pool_state
                         .deposit_for_repay(payment.take_advanced(
@@ -1516,6 +2460,172 @@ mod lending_market {
             (remainders, total_payment_value)
         }
 
+        // Borrow from a pool at a locked rate and return the borrowed assets with the locked rate
+        fn _take_fixed_rate_loan(
+            &mut self,
+            cdp_data: &WrappedCDPData,
+            pool_res_address: ResourceAddress,
+            loan_type: FixedRateLoanType,
+            amount: Decimal,
+        ) -> (Bucket, Decimal) {
+            self._check_operating_status(OperatingService::Borrow);
+
+            assert!(
+                !cdp_data.is_delegatee(),
+                "Fixed rate loans are not available for delegatee CDPs"
+            );
+
+            let mut pool_state =
+                self._get_pool_state(&pool_res_address, Some(OperatingService::Borrow), None);
+
+            let rate = pool_state
+                .get_fixed_rate(loan_type, amount)
+                .expect("Error getting fixed rate");
+
+            let borrowed_assets = pool_state
+                .withdraw_for_fixed_rate_borrow(loan_type, amount, rate)
+                .expect("Error in withdraw_for_fixed_rate_borrow");
+
+            (borrowed_assets, rate)
+        }
+
+        // Repay a fixed rate loan with the payment, the unused payment stays in the bucket
+        fn _repay_fixed_rate_loan(
+            &mut self,
+            loan_type: FixedRateLoanType,
+            loan: &mut FixedRateLoan,
+            payment: &mut Bucket,
+        ) {
+            self._check_operating_status(OperatingService::Repay);
+
+            let now = Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch;
+
+            let loan_amount = loan
+                .get_amount(now)
+                .expect("Error getting fixed rate loan amount");
+
+            // Rounding up to the resource divisibility fully repays the loan
+            let repayment = payment.take_advanced(
+                loan_amount.min(payment.amount()),
+                WithdrawStrategy::Rounded(RoundingMode::AwayFromZero),
+            );
+
+            let repaid_loan = loan
+                .repay(repayment.amount(), now)
+                .expect("Error updating fixed rate loan");
+
+            self._get_pool_state(
+                &repayment.resource_address(),
+                Some(OperatingService::Repay),
+                None,
+            )
+            .deposit_for_fixed_rate_repay(loan_type, repayment, repaid_loan)
+            .expect("Error in deposit_for_fixed_rate_repay");
+        }
+
+        // Repay the fixed-term then the stable rate loan of the payment resource.
+        // The CDP data is not saved and the unused payment stays in the bucket
+        fn _repay_cdp_fixed_rate_loans(
+            &mut self,
+            cdp_data: &mut WrappedCDPData,
+            payment: &mut Bucket,
+        ) {
+            let pool_res_address = payment.resource_address();
+
+            if let Some(mut fixed_term_loan) = cdp_data.get_fixed_term_loan(pool_res_address) {
+                self._repay_fixed_rate_loan(
+                    FixedRateLoanType::FixedTerm,
+                    &mut fixed_term_loan.loan,
+                    payment,
+                );
+
+                if fixed_term_loan.loan.principal == Decimal::ZERO {
+                    cdp_data.update_fixed_term_loan(pool_res_address, None);
+                } else {
+                    cdp_data.update_fixed_term_loan(pool_res_address, Some(fixed_term_loan));
+                }
+            }
+
+            if payment.is_empty() {
+                return;
+            }
+
+            if let Some(mut stable_rate_loan) = cdp_data.get_stable_rate_loan(pool_res_address) {
+                self._repay_fixed_rate_loan(
+                    FixedRateLoanType::Stable,
+                    &mut stable_rate_loan,
+                    payment,
+                );
+
+                if stable_rate_loan.principal == Decimal::ZERO {
+                    cdp_data.update_stable_rate_loan(pool_res_address, None);
+                } else {
+                    cdp_data.update_stable_rate_loan(pool_res_address, Some(stable_rate_loan));
+                }
+            }
+        }
+
+        // Move fixed rate loans to the variable rate loans of the CDP.
+        // Either only the matured fixed-term loans, or all the fixed-term and stable rate loans
+        fn _convert_fixed_rate_loans(
+            &mut self,
+            cdp_data: &mut WrappedCDPData,
+            only_matured: bool,
+        ) -> usize {
+            let now = Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch;
+
+            let data = cdp_data.get_data();
+
+            let mut loans: Vec<(ResourceAddress, FixedRateLoanType, FixedRateLoan)> = data
+                .fixed_term_loans
+                .into_iter()
+                .filter(|(_, fixed_term_loan)| !only_matured || fixed_term_loan.is_matured(now))
+                .map(|(pool_res_address, fixed_term_loan)| {
+                    (
+                        pool_res_address,
+                        FixedRateLoanType::FixedTerm,
+                        fixed_term_loan.loan,
+                    )
+                })
+                .collect();
+
+            if !only_matured {
+                loans.extend(
+                    data.stable_rate_loans
+                        .into_iter()
+                        .map(|(pool_res_address, loan)| {
+                            (pool_res_address, FixedRateLoanType::Stable, loan)
+                        }),
+                );
+            }
+
+            let converted_count = loans.len();
+
+            for (pool_res_address, loan_type, mut loan) in loans {
+                let closed_loan = loan.close(now).expect("Error closing fixed rate loan");
+
+                let delta_loan_units = self
+                    ._get_pool_state(&pool_res_address, None, None)
+                    .convert_fixed_rate_to_variable(loan_type, closed_loan)
+                    .expect("Error converting fixed rate loan");
+
+                cdp_data
+                    .update_loan(pool_res_address, delta_loan_units)
+                    .expect("Error updating loan");
+
+                match loan_type {
+                    FixedRateLoanType::FixedTerm => {
+                        cdp_data.update_fixed_term_loan(pool_res_address, None)
+                    }
+                    FixedRateLoanType::Stable => {
+                        cdp_data.update_stable_rate_loan(pool_res_address, None)
+                    }
+                }
+            }
+
+            converted_count
+        }
+
         fn _get_pool_state(
             &mut self,
             pool_res_address: &ResourceAddress,
@@ -1524,15 +2634,27 @@ mod lending_market {
         ) -> KeyValueEntryRefMut<'_, LendingPoolState> {
             let mut pool_state = self.pool_states.get_mut(pool_res_address).unwrap();
 
+            // Repay and add collateral can not lower the CDP health and may proceed on the last known price
+            let allow_last_known_price = matches!(
+                operating_service,
+                Some(OperatingService::Repay) | Some(OperatingService::AddCollateral)
+            );
+
             if let Some(operating_status) = operating_service {
                 pool_state
                     .check_operating_status(operating_status)
                     .expect("Invalid operating status");
             }
 
-            pool_state
-                .update_interest_and_price(bypass_debounce)
-                .expect("Error updating pool state");
+            if allow_last_known_price {
+                pool_state
+                    .update_interest_and_last_known_price(bypass_debounce)
+                    .expect("Error updating pool state");
+            } else {
+                pool_state
+                    .update_interest_and_price(bypass_debounce)
+                    .expect("Error updating pool state");
+            }
 
             pool_state
         }
@@ -1563,6 +2685,11 @@ mod lending_market {
             self.cdp_counter
         }
 
+        fn _get_new_deleverage_order_id(&mut self) -> u64 {
+            self.deleverage_order_counter += 1;
+            self.deleverage_order_counter
+        }
+
         fn _validate_cdp_proof(&self, cdp: Proof) -> NonFungibleLocalId {
             let validated_cdp = cdp.check(self.cdp_res_manager.address());
             validated_cdp.as_non_fungible().non_fungible_local_id()
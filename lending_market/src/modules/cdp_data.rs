@@ -1,3 +1,4 @@
+use super::fixed_rate_loan::*;
 use scrypto::prelude::*;
 
 #[derive(ScryptoSbor)]
@@ -8,6 +9,9 @@ pub enum CDPUpdatedEvenType {
     Repay,
     Liquidate,
     Refinance,
+    Deleverage,
+    ConvertFixedTermLoan,
+    RebalanceStableRate,
 }
 
 #[derive(ScryptoSbor, ScryptoEvent)]
@@ -74,6 +78,12 @@ pub struct CollaterizedDebtPositionData {
 
     #[mutable]
     pub delegatee_loans: IndexMap<ResourceAddress, Decimal>,
+
+    #[mutable]
+    pub fixed_term_loans: IndexMap<ResourceAddress, FixedTermLoan>,
+
+    #[mutable]
+    pub stable_rate_loans: IndexMap<ResourceAddress, FixedRateLoan>,
 }
 
 #[derive(ScryptoSbor, NonFungibleData, Clone)]
@@ -84,6 +94,8 @@ pub struct WrappedCDPData {
     pub collateral_updated: bool,
     pub loan_updated: bool,
     pub delegatee_loan_updated: bool,
+    pub fixed_term_loan_updated: bool,
+    pub stable_rate_loan_updated: bool,
 }
 
 impl WrappedCDPData {
@@ -96,6 +108,8 @@ impl WrappedCDPData {
             collateral_updated: false,
             loan_updated: false,
             delegatee_loan_updated: false,
+            fixed_term_loan_updated: false,
+            stable_rate_loan_updated: false,
         }
     }
 
@@ -130,6 +144,14 @@ impl WrappedCDPData {
         Self::get_units(&self.cdp_data.loans, loan)
     }
 
+    pub fn get_fixed_term_loan(&self, loan: ResourceAddress) -> Option<FixedTermLoan> {
+        self.cdp_data.fixed_term_loans.get(&loan).cloned()
+    }
+
+    pub fn get_stable_rate_loan(&self, loan: ResourceAddress) -> Option<FixedRateLoan> {
+        self.cdp_data.stable_rate_loans.get(&loan).cloned()
+    }
+
     //
 
     pub fn increase_delegatee_count(&mut self) -> Result<(u64, u64), String> {
@@ -226,6 +248,46 @@ impl WrappedCDPData {
         result
     }
 
+    /// Set the fixed-term loan of a resource, `None` removes the loan
+    pub fn update_fixed_term_loan(
+        &mut self,
+        res_address: ResourceAddress,
+        fixed_term_loan: Option<FixedTermLoan>,
+    ) {
+        match fixed_term_loan {
+            Some(fixed_term_loan) => {
+                self.cdp_data
+                    .fixed_term_loans
+                    .insert(res_address, fixed_term_loan);
+            }
+            None => {
+                self.cdp_data.fixed_term_loans.remove(&res_address);
+            }
+        }
+
+        self.fixed_term_loan_updated = true;
+    }
+
+    /// Set the stable rate loan of a resource, `None` removes the loan
+    pub fn update_stable_rate_loan(
+        &mut self,
+        res_address: ResourceAddress,
+        stable_rate_loan: Option<FixedRateLoan>,
+    ) {
+        match stable_rate_loan {
+            Some(stable_rate_loan) => {
+                self.cdp_data
+                    .stable_rate_loans
+                    .insert(res_address, stable_rate_loan);
+            }
+            None => {
+                self.cdp_data.stable_rate_loans.remove(&res_address);
+            }
+        }
+
+        self.stable_rate_loan_updated = true;
+    }
+
     pub fn save_cdp(
         &self,
         res_manager: &ResourceManager,
@@ -269,10 +331,30 @@ impl WrappedCDPData {
             updated = true;
         }
 
+        if self.fixed_term_loan_updated {
+            res_manager.update_non_fungible_data(
+                &self.cdp_id,
+                "fixed_term_loans",
+                self.cdp_data.fixed_term_loans.clone(),
+            );
+            updated = true;
+        }
+
+        if self.stable_rate_loan_updated {
+            res_manager.update_non_fungible_data(
+                &self.cdp_id,
+                "stable_rate_loans",
+                self.cdp_data.stable_rate_loans.clone(),
+            );
+            updated = true;
+        }
+
         if updated {
             let position_count = self.cdp_data.collaterals.len()
                 + self.cdp_data.loans.len()
-                + self.cdp_data.delegatee_loans.len();
+                + self.cdp_data.delegatee_loans.len()
+                + self.cdp_data.fixed_term_loans.len()
+                + self.cdp_data.stable_rate_loans.len();
 
             assert!(position_count as u8 <= max_cdp_position);
 
@@ -6,6 +6,8 @@ pub enum LoadPositionType {
     Loan,
     DelegatorLoan,
     DelegatorCollateral,
+    FixedRateLoan,
+    DelegatorFixedRateLoan,
 }
 
 pub enum LoadDataType {
@@ -23,6 +25,12 @@ pub struct PositionData {
     pub delegator_amount: Decimal,
     pub delegator_value: Decimal,
 
+    /// Loan amount not tracked with units, included in `amount`
+    pub fixed_rate_amount: Decimal,
+
+    /// Delegator loan amount not tracked with units, included in `delegator_amount`
+    pub delegator_fixed_rate_amount: Decimal,
+
     pub unit_ratio: PreciseDecimal,
 }
 impl PositionData {
@@ -39,11 +47,24 @@ impl PositionData {
         Ok(())
     }
 
+    pub fn load_fixed_rate_amount(
+        &mut self,
+        amount: Decimal,
+        load_type: LoadDataType,
+    ) -> Result<(), String> {
+        match load_type {
+            LoadDataType::Own => self.fixed_rate_amount += amount,
+            LoadDataType::Delegator => self.delegator_fixed_rate_amount += amount,
+        }
+
+        Ok(())
+    }
+
     pub fn update_data(&mut self, price: Decimal) -> Result<(), String> {
         self.amount = match (self.units / self.unit_ratio)
             .checked_truncate(RoundingMode::ToNearestMidpointToEven)
         {
-            Some(amount) => amount,
+            Some(amount) => amount + self.fixed_rate_amount,
             None => return Err("Error calculating position amount".to_string()),
         };
 
@@ -52,7 +73,7 @@ impl PositionData {
         self.delegator_amount = match (self.delegator_units / self.unit_ratio)
             .checked_truncate(RoundingMode::ToNearestMidpointToEven)
         {
-            Some(amount) => amount,
+            Some(amount) => amount + self.delegator_fixed_rate_amount,
             None => return Err("Error calculating position delegator amount".to_string()),
         };
 
@@ -67,6 +88,7 @@ impl PositionData {
 pub struct ExtendedCollateralPositionData {
     pub pool_res_address: ResourceAddress,
     pub price: Decimal,
+    pub price_confidence: Decimal,
     pub asset_type: u8,
     pub liquidation_threshold: LiquidationThreshold,
     pub liquidation_bonus_rate: Decimal,
@@ -88,8 +110,15 @@ impl ExtendedCollateralPositionData {
         Ok(())
     }
 
-    pub fn update_data(&mut self) -> Result<(), String> {
-        self.data.update_data(self.price)
+    /// Conservative valuation uses the lower bound of the price confidence interval
+    pub fn update_data(&mut self, conservative: bool) -> Result<(), String> {
+        let price = if conservative {
+            (self.price - self.price_confidence).max(Decimal::ZERO)
+        } else {
+            self.price
+        };
+
+        self.data.update_data(price)
     }
 }
 
@@ -98,6 +127,7 @@ impl ExtendedCollateralPositionData {
 pub struct ExtendedLoanPositionData {
     pub pool_res_address: ResourceAddress,
     pub price: Decimal,
+    pub price_confidence: Decimal,
     pub asset_type: u8,
     pub loan_close_factor: Decimal,
     pub data: PositionData,
@@ -119,11 +149,34 @@ impl ExtendedLoanPositionData {
         Ok(())
     }
 
+    pub fn load_fixed_rate_amount(
+        &mut self,
+        amount: Decimal,
+        load_type: LoadDataType,
+        pool_state: &mut KeyValueEntryRefMut<'_, LendingPoolState>,
+    ) -> Result<(), String> {
+        self.data.load_fixed_rate_amount(amount, load_type)?;
+
+        if self.data.unit_ratio == pdec!(0) {
+            self.data.unit_ratio = pool_state.get_loan_unit_ratio()?;
+        };
+
+        Ok(())
+    }
+
+    /// Conservative valuation uses the upper bound of the price confidence interval
     pub fn update_data(
         &mut self,
         collateral_positions: &IndexMap<ResourceAddress, ExtendedCollateralPositionData>,
+        conservative: bool,
     ) -> Result<(), String> {
-        self.data.update_data(self.price)?;
+        let price = if conservative {
+            self.price + self.price_confidence
+        } else {
+            self.price
+        };
+
+        self.data.update_data(price)?;
 
         self.discounted_collateral_value = collateral_positions.iter().fold(
             Decimal::ZERO,
@@ -255,6 +308,23 @@ impl CDPHealthChecker {
                         &mut pool_state,
                     )?;
                 }
+                // Fixed rate loans are loaded as amounts instead of units
+                LoadPositionType::FixedRateLoan => {
+                    let loan_position = extended_cdp._get_loan_position(&mut pool_state)?;
+                    loan_position.load_fixed_rate_amount(
+                        units,
+                        LoadDataType::Own,
+                        &mut pool_state,
+                    )?;
+                }
+                LoadPositionType::DelegatorFixedRateLoan => {
+                    let loan_position = extended_cdp._get_loan_position(&mut pool_state)?;
+                    loan_position.load_fixed_rate_amount(
+                        units,
+                        LoadDataType::Delegator,
+                        &mut pool_state,
+                    )?;
+                }
             }
 
             Ok(())
@@ -276,6 +346,30 @@ impl CDPHealthChecker {
                 load_data(pool_res_address, *units, LoadPositionType::Loan)
             })?;
 
+        let now = Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch;
+
+        // Load the fixed-term and stable rate loan positions with the interest accrued until now
+        cdp_data
+            .fixed_term_loans
+            .iter()
+            .try_for_each(|(pool_res_address, fixed_term_loan)| {
+                load_data(
+                    pool_res_address,
+                    fixed_term_loan.loan.get_amount(now)?,
+                    LoadPositionType::FixedRateLoan,
+                )
+            })?;
+
+        cdp_data.stable_rate_loans.iter().try_for_each(
+            |(pool_res_address, stable_rate_loan)| {
+                load_data(
+                    pool_res_address,
+                    stable_rate_loan.get_amount(now)?,
+                    LoadPositionType::FixedRateLoan,
+                )
+            },
+        )?;
+
         // If the CDP is a delegator, also load his delegatee loans
 
         if cdp_type.is_delegator() {
@@ -310,6 +404,26 @@ impl CDPHealthChecker {
                     load_data(pool_res_address, *units, LoadPositionType::DelegatorLoan)
                 })?;
 
+            delegator_cdp_data.fixed_term_loans.iter().try_for_each(
+                |(pool_res_address, fixed_term_loan)| {
+                    load_data(
+                        pool_res_address,
+                        fixed_term_loan.loan.get_amount(now)?,
+                        LoadPositionType::DelegatorFixedRateLoan,
+                    )
+                },
+            )?;
+
+            delegator_cdp_data.stable_rate_loans.iter().try_for_each(
+                |(pool_res_address, stable_rate_loan)| {
+                    load_data(
+                        pool_res_address,
+                        stable_rate_loan.get_amount(now)?,
+                        LoadPositionType::DelegatorFixedRateLoan,
+                    )
+                },
+            )?;
+
             delegator_cdp_data.delegatee_loans.iter().try_for_each(
                 |(pool_res_address, delegatee_loan_units)| {
                     let self_loan_unit = wrapped_cdp_data.get_loan_unit(*pool_res_address);
@@ -327,8 +441,9 @@ impl CDPHealthChecker {
         Ok(extended_cdp)
     }
 
+    // Used before borrowing or removing collateral, positions are valued on the conservative side of the price confidence
     pub fn check_cdp(&mut self) -> Result<(), String> {
-        self._update_health_check_data()?;
+        self._update_health_check_data(true)?;
 
         if self.total_loan_to_value_ratio > Decimal::ONE {
             return Err("LTV need to be lower 1".to_string());
@@ -359,8 +474,15 @@ impl CDPHealthChecker {
         Ok(())
     }
 
+    /// Get the CDP LTV valued at the price midpoint
+    pub fn get_loan_to_value_ratio(&mut self) -> Result<Decimal, String> {
+        self._update_health_check_data(false)?;
+
+        Ok(self.total_loan_to_value_ratio)
+    }
+
     pub fn can_liquidate(&mut self) -> Result<(), String> {
-        self._update_health_check_data()?;
+        self._update_health_check_data(false)?;
 
         if self.total_loan_to_value_ratio <= Decimal::ONE {
             return Err("This CDP can not be liquidated: LTV ratio lower than 1".into());
@@ -369,8 +491,18 @@ impl CDPHealthChecker {
         Ok(())
     }
 
+    pub fn can_deleverage(&mut self, trigger_ltv: Decimal) -> Result<(), String> {
+        self._update_health_check_data(false)?;
+
+        if self.total_loan_to_value_ratio <= trigger_ltv {
+            return Err("This CDP can not be deleveraged: LTV ratio lower than the trigger".into());
+        }
+
+        Ok(())
+    }
+
     pub fn can_refinance(&mut self) -> Result<(), String> {
-        self._update_health_check_data()?;
+        self._update_health_check_data(false)?;
 
         if self.total_loan_to_value_ratio != Decimal::MAX {
             return Err("This CDP can not be refinanced: LTV ratio greater than 1".into());
@@ -396,7 +528,9 @@ impl CDPHealthChecker {
                     asset_type: pool_state.pool_config.asset_type,
                     liquidation_bonus_rate: pool_state.pool_config.liquidation_bonus_rate,
                     liquidation_threshold: pool_state.liquidation_threshold.clone(),
-                    price: pool_state.price,
+                    price: pool_state.collateral_price,
+                    price_confidence: pool_state.collateral_price
+                        * pool_state.get_relative_price_confidence(),
                     data: PositionData {
                         units: dec!(0),
                         amount: dec!(0),
@@ -404,6 +538,8 @@ impl CDPHealthChecker {
                         delegator_units: dec!(0),
                         delegator_amount: dec!(0),
                         delegator_value: dec!(0),
+                        fixed_rate_amount: dec!(0),
+                        delegator_fixed_rate_amount: dec!(0),
                         unit_ratio: pdec!(0),
                     },
                 },
@@ -431,7 +567,10 @@ impl CDPHealthChecker {
                 ExtendedLoanPositionData {
                     pool_res_address: pool_state.pool_res_address,
 
-                    price: pool_state.price,
+                    price: pool_state.loan_price,
+
+                    price_confidence: pool_state.loan_price
+                        * pool_state.get_relative_price_confidence(),
 
                     asset_type: pool_state.pool_config.asset_type,
 
@@ -444,6 +583,8 @@ impl CDPHealthChecker {
                         delegator_units: dec!(0),
                         delegator_amount: dec!(0),
                         delegator_value: dec!(0),
+                        fixed_rate_amount: dec!(0),
+                        delegator_fixed_rate_amount: dec!(0),
                         unit_ratio: pdec!(0),
                     },
 
@@ -457,11 +598,13 @@ impl CDPHealthChecker {
             .unwrap())
     }
 
-    fn _update_health_check_data(&mut self) -> Result<(), String> {
+    fn _update_health_check_data(&mut self, conservative: bool) -> Result<(), String> {
         // Update the collateral positions data and calculate the total solvency value
         self.collateral_positions
             .iter_mut()
-            .try_for_each(|(_, extended_collateral)| extended_collateral.update_data())?;
+            .try_for_each(|(_, extended_collateral)| {
+                extended_collateral.update_data(conservative)
+            })?;
 
         // Update the loan positions data and calculate the total loan value.
         // We also calculate the  discounted collateral value for each loan position weighted by the loan value
@@ -482,7 +625,7 @@ impl CDPHealthChecker {
                         mut self_loan_value,
                         mut self_closable_loan_value,
                     )| {
-                        extended_loan.update_data(&self.collateral_positions)?;
+                        extended_loan.update_data(&self.collateral_positions, conservative)?;
 
                         //
 
@@ -0,0 +1,53 @@
+use crate::modules::utils::is_valid_rate;
+use scrypto::prelude::*;
+
+/// Stop-loss order attached to a CDP. Once the CDP LTV goes above `trigger_ltv`, any keeper can
+/// sell collateral through `swap_component` to repay up to `loan_amount` of the loan.
+#[derive(ScryptoSbor, Clone)]
+pub struct DeleverageOrder {
+    /// LTV above which the order can be executed
+    pub trigger_ltv: Decimal,
+
+    /// Borrowed resource to repay
+    pub loan_res_address: ResourceAddress,
+
+    /// Maximum loan amount to repay with the swap proceeds
+    pub loan_amount: Decimal,
+
+    /// Collateral resource sold to repay the loan
+    pub collateral_res_address: ResourceAddress,
+
+    /// Component exposing a `swap(Bucket, ResourceAddress) -> Bucket` method
+    pub swap_component: ComponentAddress,
+
+    /// Maximum value lost in the swap, also used to size the collateral sold
+    pub max_slippage_rate: Decimal,
+
+    /// Share of the swap proceeds paid to the keeper executing the order
+    pub keeper_fee_rate: Decimal,
+}
+impl DeleverageOrder {
+    pub fn check(&self) -> Result<(), String> {
+        if self.trigger_ltv <= dec!(0) {
+            return Err("Trigger LTV must be greater than 0".into());
+        }
+
+        if self.loan_amount <= dec!(0) {
+            return Err("Loan amount must be greater than 0".into());
+        }
+
+        if self.loan_res_address == self.collateral_res_address {
+            return Err("Loan and collateral resources must be different".into());
+        }
+
+        if !is_valid_rate(self.max_slippage_rate) {
+            return Err("Max slippage rate must be between 0 and 1".into());
+        }
+
+        if !is_valid_rate(self.keeper_fee_rate) {
+            return Err("Keeper fee rate must be between 0 and 1".into());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,233 @@
+use crate::modules::utils::{MINUTE_PER_YEAR, SECOND_PER_MINUTE};
+use scrypto::prelude::*;
+
+/// Loan kinds accruing interest at a locked rate instead of the pool variable rate
+#[derive(ScryptoSbor, Clone, Copy, Debug, PartialEq)]
+pub enum FixedRateLoanType {
+    /// Rate locked until maturity
+    FixedTerm,
+    /// Rate locked until rebalanced, see `StableRateLoanConfig`
+    Stable,
+}
+
+/// Loan accruing simple interest at the rates locked when the amounts are borrowed.
+/// Also used by the pools to track the aggregate of their fixed rate loans.
+/// Interest is not compounded so the aggregate stays exactly equal to the sum of the loans:
+/// every change of a loan is applied as is to the aggregate with `FixedRateLoanChange`.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq)]
+pub struct FixedRateLoan {
+    /// Borrowed amount not repaid yet
+    pub principal: Decimal,
+
+    /// Sum of the borrowed amounts multiplied by their locked yearly rate
+    pub rate_weighted_principal: Decimal,
+
+    /// Interest accrued until `updated_at` multiplied by the number of minutes in a year.
+    /// Kept scaled so that accruing interest does not round
+    pub scaled_interest: Decimal,
+
+    /// Timestamp until which the interest is accrued
+    pub updated_at: i64,
+}
+
+/// Part of a fixed rate loan removed by a repayment or a conversion
+#[derive(ScryptoSbor, Clone, Debug, PartialEq)]
+pub struct FixedRateLoanChange {
+    pub principal: Decimal,
+    pub rate_weighted_principal: Decimal,
+    pub scaled_interest: Decimal,
+}
+impl FixedRateLoanChange {
+    /// Get the removed amount, principal and interest
+    pub fn get_amount(&self) -> Decimal {
+        self.principal + self.scaled_interest / MINUTE_PER_YEAR
+    }
+}
+
+impl FixedRateLoan {
+    pub fn new(now: i64) -> Self {
+        Self {
+            principal: Decimal::ZERO,
+            rate_weighted_principal: Decimal::ZERO,
+            scaled_interest: Decimal::ZERO,
+            updated_at: now,
+        }
+    }
+
+    /// Get the principal weighted average yearly rate
+    pub fn get_rate(&self) -> Decimal {
+        if self.principal == Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+
+        self.rate_weighted_principal / self.principal
+    }
+
+    /// Get the interest accrued until `now`
+    pub fn get_interest(&self, now: i64) -> Result<Decimal, String> {
+        Ok(self._get_scaled_interest(now)? / MINUTE_PER_YEAR)
+    }
+
+    /// Get the loan amount including the interest accrued until `now`
+    pub fn get_amount(&self, now: i64) -> Result<Decimal, String> {
+        Ok(self.principal + self.get_interest(now)?)
+    }
+
+    /// Accrue the interest until `now` and return the newly accrued interest
+    pub fn accrue_interest(&mut self, now: i64) -> Result<Decimal, String> {
+        let period_in_minute = (now - self.updated_at) / SECOND_PER_MINUTE;
+
+        if period_in_minute <= 0 {
+            return Ok(Decimal::ZERO);
+        }
+
+        let previous_interest = self.scaled_interest / MINUTE_PER_YEAR;
+
+        self.scaled_interest = self._get_scaled_interest(now)?;
+        self.updated_at += period_in_minute * SECOND_PER_MINUTE;
+
+        Ok(self.scaled_interest / MINUTE_PER_YEAR - previous_interest)
+    }
+
+    /// Add an amount borrowed at `rate`
+    pub fn increase(&mut self, amount: Decimal, rate: Decimal, now: i64) -> Result<(), String> {
+        if amount < Decimal::ZERO {
+            return Err("Fixed rate loan increase must be positive".into());
+        }
+
+        self.accrue_interest(now)?;
+
+        self.principal += amount;
+        self.rate_weighted_principal += amount * rate;
+
+        Ok(())
+    }
+
+    /// Repay the accrued interest then the principal and return the repaid part of the loan.
+    /// An amount above the loan amount repays the whole loan, the surplus can only come from
+    /// rounding the payment to the resource divisibility
+    pub fn repay(&mut self, amount: Decimal, now: i64) -> Result<FixedRateLoanChange, String> {
+        if amount < Decimal::ZERO {
+            return Err("Fixed rate loan repayment must be positive".into());
+        }
+
+        self.accrue_interest(now)?;
+
+        if amount >= self.get_amount(now)? {
+            return self.close(now);
+        }
+
+        let interest = self.scaled_interest / MINUTE_PER_YEAR;
+
+        let interest_payment = amount.min(interest);
+        let principal_payment = amount - interest_payment;
+
+        let change = FixedRateLoanChange {
+            principal: principal_payment,
+            rate_weighted_principal: if principal_payment == Decimal::ZERO {
+                Decimal::ZERO
+            } else {
+                self.rate_weighted_principal * principal_payment / self.principal
+            },
+            scaled_interest: interest_payment * MINUTE_PER_YEAR,
+        };
+
+        self.remove(&change, now)?;
+
+        Ok(change)
+    }
+
+    /// Remove the whole loan and return it
+    pub fn close(&mut self, now: i64) -> Result<FixedRateLoanChange, String> {
+        self.accrue_interest(now)?;
+
+        let change = FixedRateLoanChange {
+            principal: self.principal,
+            rate_weighted_principal: self.rate_weighted_principal,
+            scaled_interest: self.scaled_interest,
+        };
+
+        self.principal = Decimal::ZERO;
+        self.rate_weighted_principal = Decimal::ZERO;
+        self.scaled_interest = Decimal::ZERO;
+
+        Ok(change)
+    }
+
+    /// Remove a part of the loan, used by the pools to apply the changes of the underlying loans
+    pub fn remove(&mut self, change: &FixedRateLoanChange, now: i64) -> Result<(), String> {
+        self.accrue_interest(now)?;
+
+        if change.principal > self.principal
+            || change.rate_weighted_principal > self.rate_weighted_principal
+            || change.scaled_interest > self.scaled_interest
+        {
+            return Err("Fixed rate loan change exceeds the loan".into());
+        }
+
+        self.principal -= change.principal;
+        self.rate_weighted_principal -= change.rate_weighted_principal;
+        self.scaled_interest -= change.scaled_interest;
+
+        Ok(())
+    }
+
+    /// Lock the principal at a new rate and return the previous rate weighted principal
+    pub fn update_rate(&mut self, rate: Decimal, now: i64) -> Result<Decimal, String> {
+        self.accrue_interest(now)?;
+
+        let previous_rate_weighted_principal = self.rate_weighted_principal;
+
+        self.rate_weighted_principal = self.principal * rate;
+
+        Ok(previous_rate_weighted_principal)
+    }
+
+    /// Replace a rate weighted principal of an underlying loan, used by the pools
+    pub fn replace_rate_weighted_principal(
+        &mut self,
+        previous_rate_weighted_principal: Decimal,
+        rate_weighted_principal: Decimal,
+        now: i64,
+    ) -> Result<(), String> {
+        self.accrue_interest(now)?;
+
+        if previous_rate_weighted_principal > self.rate_weighted_principal {
+            return Err("Fixed rate loan change exceeds the loan".into());
+        }
+
+        self.rate_weighted_principal = self.rate_weighted_principal
+            - previous_rate_weighted_principal
+            + rate_weighted_principal;
+
+        Ok(())
+    }
+
+    fn _get_scaled_interest(&self, now: i64) -> Result<Decimal, String> {
+        let period_in_minute = (now - self.updated_at) / SECOND_PER_MINUTE;
+
+        if period_in_minute <= 0 {
+            return Ok(self.scaled_interest);
+        }
+
+        // The rate weighted principal times a whole number of minutes does not round
+        self.rate_weighted_principal
+            .checked_mul(Decimal::from(period_in_minute))
+            .and_then(|interest| interest.checked_add(self.scaled_interest))
+            .ok_or("Error calculating fixed rate loan interest".into())
+    }
+}
+
+/// Fixed rate loan that must be repaid or converted to a variable rate loan at maturity
+#[derive(ScryptoSbor, Clone, Debug, PartialEq)]
+pub struct FixedTermLoan {
+    pub loan: FixedRateLoan,
+
+    /// Timestamp after which the loan can be converted to a variable rate loan by anyone
+    pub maturity: i64,
+}
+impl FixedTermLoan {
+    pub fn is_matured(&self, now: i64) -> bool {
+        now >= self.maturity
+    }
+}
@@ -1,6 +1,12 @@
-use super::utils::is_valid_rate;
+use super::utils::{is_valid_rate, MINUTE_PER_YEAR};
 use scrypto::prelude::*;
 
+/// Maximum number of samples of a rate curve, bounds the cost of sampling it
+pub const MAX_RATE_CURVE_SAMPLES: u16 = 101;
+
+// Largest exponent accepted by the exponential models, keeps e^x well within PreciseDecimal range
+const MAX_EXP_INPUT: i64 = 20;
+
 #[derive(ScryptoSbor, Clone, Debug)]
 pub struct ISInputBreakPoint {
     pub usage: Decimal,
@@ -14,16 +20,226 @@ pub struct ISInternalBreakPoint {
     slop: Decimal,
 }
 
+/// Borrow and supply rates of a pool at a given utilization.
+/// The supply rate only accounts for interest income, flashloan fees are not included
+#[derive(ScryptoSbor, Clone, Debug, PartialEq)]
+pub struct PoolRates {
+    pub utilization: Decimal,
+    pub borrow_rate: Decimal,
+    pub supply_rate: Decimal,
+}
+
+/// Exponential curve: rate = base_rate + multiplier * (e^(growth_rate * usage) - 1)
+#[derive(ScryptoSbor, Clone, Debug)]
+pub struct ExponentialModel {
+    pub base_rate: Decimal,
+    pub multiplier: Decimal,
+    pub growth_rate: Decimal,
+}
+impl ExponentialModel {
+    pub fn check(&self) -> Result<(), String> {
+        if self.base_rate < dec!(0) {
+            return Err("The base rate must be greater than or equal to 0".into());
+        }
+
+        if self.multiplier < dec!(0) {
+            return Err("The multiplier must be greater than or equal to 0".into());
+        }
+
+        if self.growth_rate < dec!(0) || self.growth_rate > MAX_EXP_INPUT.into() {
+            return Err(format!(
+                "The growth rate must be between 0 and {}, inclusive",
+                MAX_EXP_INPUT
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn get_interest_rate(&self, usage: Decimal) -> Decimal {
+        let growth = exp(PreciseDecimal::from(self.growth_rate * usage)) - PreciseDecimal::ONE;
+
+        self.base_rate
+            + self.multiplier
+                * growth
+                    .checked_truncate(RoundingMode::ToNearestMidpointToEven)
+                    .unwrap()
+    }
+}
+
+/// Kinked curve around a target utilization. The rate at target drifts up while the utilization
+/// stays above target and down while it stays below, at `adjustment_speed` per year.
+#[derive(ScryptoSbor, Clone, Debug)]
+pub struct AdaptiveModel {
+    /// Utilization the model steers the pool toward
+    pub target_usage: Decimal,
+
+    /// Borrow rate at the target utilization, updated over time
+    pub rate_at_target: Decimal,
+
+    ///
+    pub min_rate_at_target: Decimal,
+
+    ///
+    pub max_rate_at_target: Decimal,
+
+    /// Yearly drift speed of the rate at target when the utilization is 100% away from target
+    pub adjustment_speed: Decimal,
+
+    /// Rate at 100% usage is `rate_at_target * curve_steepness`,
+    /// rate at 0% usage is `rate_at_target / curve_steepness`
+    pub curve_steepness: Decimal,
+}
+impl AdaptiveModel {
+    pub fn check(&self) -> Result<(), String> {
+        if self.target_usage <= dec!(0) || self.target_usage >= dec!(1) {
+            return Err("The target usage must be between 0 and 1, exclusive".into());
+        }
+
+        if self.min_rate_at_target < dec!(0) || self.min_rate_at_target > self.max_rate_at_target {
+            return Err("The rate at target bounds must be positive and ordered".into());
+        }
+
+        if self.rate_at_target < self.min_rate_at_target
+            || self.rate_at_target > self.max_rate_at_target
+        {
+            return Err("The rate at target must be within its bounds".into());
+        }
+
+        if self.adjustment_speed < dec!(0) || self.adjustment_speed > MAX_EXP_INPUT.into() {
+            return Err(format!(
+                "The adjustment speed must be between 0 and {}, inclusive",
+                MAX_EXP_INPUT
+            ));
+        }
+
+        if self.curve_steepness < dec!(1) {
+            return Err("The curve steepness must be greater than or equal to 1".into());
+        }
+
+        Ok(())
+    }
+
+    // Normalized distance to the target utilization, between -1 and 1
+    fn get_usage_error(&self, usage: Decimal) -> Decimal {
+        if usage > self.target_usage {
+            (usage - self.target_usage) / (Decimal::ONE - self.target_usage)
+        } else {
+            (usage - self.target_usage) / self.target_usage
+        }
+    }
+
+    fn get_interest_rate(&self, usage: Decimal) -> Decimal {
+        let error = self.get_usage_error(usage);
+
+        let coefficient = if error < dec!(0) {
+            Decimal::ONE - Decimal::ONE / self.curve_steepness
+        } else {
+            self.curve_steepness - Decimal::ONE
+        };
+
+        self.rate_at_target * (Decimal::ONE + coefficient * error)
+    }
+
+    fn update_rate_at_target(&mut self, usage: Decimal, period_in_minute: i64) {
+        let exponent = (self.adjustment_speed * self.get_usage_error(usage) * period_in_minute
+            / MINUTE_PER_YEAR)
+            .clamp(-Decimal::from(MAX_EXP_INPUT), Decimal::from(MAX_EXP_INPUT));
+
+        let rate_at_target = PreciseDecimal::from(self.rate_at_target) * exp(exponent.into());
+
+        self.rate_at_target = rate_at_target
+            .checked_truncate(RoundingMode::ToNearestMidpointToEven)
+            .unwrap()
+            .clamp(self.min_rate_at_target, self.max_rate_at_target);
+    }
+}
+
+#[derive(ScryptoSbor, Clone, Debug)]
+pub enum InterestModelInput {
+    /// Piecewise-linear curve defined by an initial rate and break points
+    Linear(Decimal, Vec<ISInputBreakPoint>),
+    Exponential(ExponentialModel),
+    Adaptive(AdaptiveModel),
+}
+
+#[derive(ScryptoSbor, Clone, Debug)]
+pub enum InterestModel {
+    Linear(Vec<ISInternalBreakPoint>),
+    Exponential(ExponentialModel),
+    Adaptive(AdaptiveModel),
+}
+impl Default for InterestModel {
+    fn default() -> Self {
+        InterestModel::Linear(Vec::new())
+    }
+}
+
 #[derive(ScryptoSbor, Default, Clone)]
 pub struct InterestStrategy {
-    break_points: Vec<ISInternalBreakPoint>,
+    model: InterestModel,
+
+    /// Upper bound applied to the rate returned by any model
+    max_rate: Option<Decimal>,
 }
 
 impl InterestStrategy {
     pub fn new() -> Self {
         Self {
-            break_points: Vec::new(),
+            model: InterestModel::default(),
+            max_rate: None,
+        }
+    }
+
+    pub fn get_model(&self) -> &InterestModel {
+        &self.model
+    }
+
+    pub fn set_model(
+        &mut self,
+        input: InterestModelInput,
+        max_rate: Option<Decimal>,
+    ) -> Result<(), String> {
+        if let Some(max_rate) = max_rate {
+            if max_rate < dec!(0) {
+                return Err("The max rate must be greater than or equal to 0".into());
+            }
+        }
+
+        match input {
+            InterestModelInput::Linear(initial_rate, break_points) => {
+                self.set_breakpoints(initial_rate, break_points)?;
+            }
+            InterestModelInput::Exponential(model) => {
+                model.check()?;
+                self.model = InterestModel::Exponential(model);
+            }
+            InterestModelInput::Adaptive(model) => {
+                model.check()?;
+                self.model = InterestModel::Adaptive(model);
+            }
+        }
+
+        self.max_rate = max_rate;
+
+        Ok(())
+    }
+
+    /// Let time dependent models react to the utilization observed over the elapsed period
+    pub fn update_rate_at_target(
+        &mut self,
+        usage: Decimal,
+        period_in_minute: i64,
+    ) -> Result<(), String> {
+        if !is_valid_rate(usage) {
+            return Err("Usage must be between 0 and 1, inclusive".into());
+        }
+
+        if let InterestModel::Adaptive(model) = &mut self.model {
+            model.update_rate_at_target(usage, period_in_minute);
         }
+
+        Ok(())
     }
 
     pub fn set_breakpoints(
@@ -75,7 +291,7 @@ impl InterestStrategy {
             });
         }
 
-        self.break_points = break_points;
+        self.model = InterestModel::Linear(break_points);
 
         Ok(())
     }
@@ -85,20 +301,111 @@ impl InterestStrategy {
             return Err("Usage must be between 0 and 1, inclusive".into());
         }
 
-        let len = self.break_points.len();
-        let mut j = len - 1;
+        let interest_rate = match &self.model {
+            InterestModel::Linear(break_points) => {
+                let len = break_points.len();
+                let mut j = len - 1;
+
+                for i in 0..len - 1 {
+                    if break_points[i].usage <= usage && usage < break_points[i + 1].usage {
+                        j = i;
+                        break;
+                    }
+                }
+
+                let break_point = &break_points[j];
 
-        for i in 0..len - 1 {
-            if self.break_points[i].usage <= usage && usage < self.break_points[i + 1].usage {
-                j = i;
-                break;
+                break_point.rate + ((usage - break_point.usage) * break_point.slop)
             }
+            InterestModel::Exponential(model) => model.get_interest_rate(usage),
+            InterestModel::Adaptive(model) => model.get_interest_rate(usage),
+        };
+
+        let interest_rate = match self.max_rate {
+            Some(max_rate) => interest_rate.min(max_rate),
+            None => interest_rate,
+        };
+
+        Ok(interest_rate)
+    }
+
+    /// Get the borrow and supply rates at the given utilization.
+    /// Supply rate = borrow rate * utilization * (1 - protocol interest fee rate)
+    pub fn get_pool_rates(
+        &self,
+        utilization: Decimal,
+        protocol_interest_fee_rate: Decimal,
+    ) -> Result<PoolRates, String> {
+        let borrow_rate = self.get_interest_rate(utilization)?;
+
+        Ok(PoolRates {
+            utilization,
+            borrow_rate,
+            supply_rate: borrow_rate * utilization * (Decimal::ONE - protocol_interest_fee_rate),
+        })
+    }
+
+    /// Sample the pool rates at `samples` evenly spaced utilizations between 0 and 1, inclusive
+    pub fn get_rate_curve(
+        &self,
+        samples: u16,
+        protocol_interest_fee_rate: Decimal,
+    ) -> Result<Vec<PoolRates>, String> {
+        if samples < 2 {
+            return Err("The rate curve must contain at least two samples".into());
         }
 
-        let break_point = &self.break_points[j];
+        if samples > MAX_RATE_CURVE_SAMPLES {
+            return Err(format!(
+                "The rate curve must contain at most {} samples",
+                MAX_RATE_CURVE_SAMPLES
+            ));
+        }
 
-        let interest_rate = break_point.rate + ((usage - break_point.usage) * break_point.slop);
+        let step = Decimal::ONE / (samples - 1);
 
-        Ok(interest_rate)
+        (0..samples)
+            .map(|i| {
+                let utilization = if i == samples - 1 {
+                    Decimal::ONE
+                } else {
+                    step * i
+                };
+
+                self.get_pool_rates(utilization, protocol_interest_fee_rate)
+            })
+            .collect()
+    }
+}
+
+// e^x computed with a Taylor series on x scaled below 1, then squared back
+fn exp(x: PreciseDecimal) -> PreciseDecimal {
+    if x < PreciseDecimal::ZERO {
+        return PreciseDecimal::ONE / exp(-x);
+    }
+
+    let mut scaled_x = x;
+    let mut squaring_count = 0;
+    while scaled_x > PreciseDecimal::ONE {
+        scaled_x = scaled_x / 2;
+        squaring_count += 1;
+    }
+
+    let mut term = PreciseDecimal::ONE;
+    let mut result = PreciseDecimal::ONE;
+    for i in 1..30 {
+        term = term * scaled_x / i;
+
+        if term == PreciseDecimal::ZERO {
+            break;
+        }
+
+        result = result + term;
     }
+
+    for _ in 0..squaring_count {
+        result = result * result;
+    }
+
+    result
 }
@@ -8,8 +8,16 @@ pub enum UpdateMarketConfigInput {
 #[derive(ScryptoSbor, Clone)]
 pub struct MarketConfig {
     pub max_cdp_position: u8,
+
+    /// Resource in which prices are quoted, XRD when not set.
+    /// Not updatable as pool prices and value based limits depend on it
+    pub base_currency: Option<ResourceAddress>,
 }
 impl MarketConfig {
+    pub fn get_base_currency(&self) -> ResourceAddress {
+        self.base_currency.unwrap_or(XRD)
+    }
+
     pub fn check(&self) -> Result<(), String> {
         if self.max_cdp_position == 0 {
             return Err("Max CDP position must be greater than 0".into());
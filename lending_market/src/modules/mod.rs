@@ -1,9 +1,13 @@
 pub mod cdp_data;
 pub mod cdp_health_checker;
+pub mod deleverage_order;
+pub mod fixed_rate_loan;
 pub mod interest_strategy;
 pub mod liquidation_threshold;
 pub mod market_config;
 pub mod operation_status;
 pub mod pool_config;
 pub mod pool_state;
+pub mod price_source;
+pub mod redemption_queue;
 pub mod utils;
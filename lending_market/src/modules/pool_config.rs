@@ -1,7 +1,7 @@
 use crate::modules::utils::is_valid_rate;
 use scrypto::prelude::*;
 
-#[derive(ScryptoSbor)]
+#[derive(ScryptoSbor, ManifestSbor)]
 pub enum UpdatePoolConfigInput {
     ProtocolInterestFeeRate(Decimal),
     ProtocolFlashloanFeeRate(Decimal),
@@ -12,20 +12,77 @@ pub enum UpdatePoolConfigInput {
     DepositLimit(Option<Decimal>),
     BorrowLimit(Option<Decimal>),
     UtilizationLimit(Option<Decimal>),
+    FlashloanLimit(Option<FlashloanLimit>),
 
     AssetType(u8),
     LiquidationBonusRate(Decimal),
     LoanCloseFactor(Decimal),
 
     InterestUpdatePeriod(i64),
+    MaxInterestRateChange(Option<Decimal>),
     PriceUpdatePeriod(i64),
     PriceExpirationPeriod(i64),
+
+    FixedTermLoan(Option<FixedTermLoanConfig>),
+    StableRateLoan(Option<StableRateLoanConfig>),
+    Twap(Option<TwapConfig>),
 }
 
 pub enum CheckPoolConfigLimitInput {
     DepositLimit(Decimal),
     BorrowLimit(Decimal),
     UtilizationLimit(Decimal),
+    /// Requested flashloan amount and pool available liquidity
+    FlashloanLimit(Decimal, Decimal),
+}
+
+#[derive(ScryptoSbor, ManifestSbor, Clone, Debug)]
+pub enum FlashloanLimit {
+    /// Maximum amount that can be taken in a single flashloan
+    Amount(Decimal),
+    /// Maximum share of the pool available liquidity that can be taken in a single flashloan
+    LiquidityRatio(Decimal),
+}
+
+#[derive(ScryptoSbor, ManifestSbor, Clone, Debug)]
+pub struct FixedTermLoanConfig {
+    /// Added to the variable rate at origination to get the locked rate
+    pub rate_premium: Decimal,
+    /// Maximum loan duration in minutes
+    pub max_duration: i64,
+}
+
+#[derive(ScryptoSbor, ManifestSbor, Clone, Debug)]
+pub struct StableRateLoanConfig {
+    /// Added to the variable rate at origination or rebalance to get the locked rate
+    pub rate_premium: Decimal,
+    /// Stable rate loans can be rebalanced up when the supply rate is below this threshold
+    pub rebalance_supply_rate_threshold: Decimal,
+    /// Stable rate loans can be rebalanced up when the utilization is above this threshold
+    pub rebalance_utilization_threshold: Decimal,
+}
+
+#[derive(ScryptoSbor, ManifestSbor, Clone, Debug)]
+pub struct TwapConfig {
+    /// TWAP window in minutes
+    pub window: i64,
+    /// Use the lower of the spot price and the TWAP for collaterals and the higher for loans.
+    /// Otherwise the TWAP is used for both
+    pub conservative: bool,
+}
+impl TwapConfig {
+    /// Get the (collateral price, loan price) from the spot price and the TWAP
+    pub fn get_collateral_and_loan_prices(
+        &self,
+        spot_price: Decimal,
+        twap_price: Decimal,
+    ) -> (Decimal, Decimal) {
+        if self.conservative {
+            (spot_price.min(twap_price), spot_price.max(twap_price))
+        } else {
+            (twap_price, twap_price)
+        }
+    }
 }
 
 #[derive(ScryptoSbor, Clone)]
@@ -44,10 +101,22 @@ pub struct PoolConfig {
     pub deposit_limit: Option<Decimal>,
     pub borrow_limit: Option<Decimal>,
     pub utilization_limit: Option<Decimal>,
+    pub flashloan_limit: Option<FlashloanLimit>,
 
     pub interest_update_period: i64,
+    /// Maximum change of the applied interest rate per interest update period
+    pub max_interest_rate_change: Option<Decimal>,
     pub price_update_period: i64,
     pub price_expiration_period: i64,
+
+    /// Fixed-term loans are disabled when not set
+    pub fixed_term_loan: Option<FixedTermLoanConfig>,
+
+    /// Stable rate loans are disabled when not set
+    pub stable_rate_loan: Option<StableRateLoanConfig>,
+
+    /// Spot price is used when not set
+    pub twap: Option<TwapConfig>,
 }
 impl PoolConfig {
     pub fn check(&self) -> Result<(), String> {
@@ -87,10 +156,26 @@ impl PoolConfig {
             return Err("Utilization limit must be between 0 and 1".into());
         }
 
+        match self.flashloan_limit {
+            Some(FlashloanLimit::Amount(amount)) if amount < dec!(0) => {
+                return Err("Flashloan amount limit must be positive".into());
+            }
+            Some(FlashloanLimit::LiquidityRatio(ratio)) if !is_valid_rate(ratio) => {
+                return Err("Flashloan liquidity ratio limit must be between 0 and 1".into());
+            }
+            _ => {}
+        }
+
         if self.interest_update_period <= 0 {
             return Err("Interest update period must be greater than 0".into());
         }
 
+        if self.max_interest_rate_change.is_some()
+            && self.max_interest_rate_change.unwrap() <= dec!(0)
+        {
+            return Err("Max interest rate change must be greater than 0".into());
+        }
+
         if self.price_update_period <= 0 {
             return Err("Price update period must be greater than 0".into());
         }
@@ -103,6 +188,38 @@ impl PoolConfig {
             return Err("Price expiration period must be greater than price update period".into());
         }
 
+        if let Some(fixed_term_loan) = &self.fixed_term_loan {
+            if fixed_term_loan.rate_premium < dec!(0) {
+                return Err("Fixed-term loan rate premium must be positive".into());
+            }
+
+            if fixed_term_loan.max_duration <= 0 {
+                return Err("Fixed-term loan max duration must be greater than 0".into());
+            }
+        }
+
+        if let Some(stable_rate_loan) = &self.stable_rate_loan {
+            if stable_rate_loan.rate_premium < dec!(0) {
+                return Err("Stable rate loan rate premium must be positive".into());
+            }
+
+            if stable_rate_loan.rebalance_supply_rate_threshold < dec!(0) {
+                return Err("Stable rate rebalance supply rate threshold must be positive".into());
+            }
+
+            if !is_valid_rate(stable_rate_loan.rebalance_utilization_threshold) {
+                return Err(
+                    "Stable rate rebalance utilization threshold must be between 0 and 1".into(),
+                );
+            }
+        }
+
+        if let Some(twap) = &self.twap {
+            if twap.window <= 0 {
+                return Err("TWAP window must be greater than 0".into());
+            }
+        }
+
         Ok(())
     }
 
@@ -120,6 +237,10 @@ impl PoolConfig {
                 self.utilization_limit = utilization_limit;
             }
 
+            UpdatePoolConfigInput::FlashloanLimit(flashloan_limit) => {
+                self.flashloan_limit = flashloan_limit;
+            }
+
             UpdatePoolConfigInput::FlashloanFeeRate(flashloan_fee_rate) => {
                 self.flashloan_fee_rate = flashloan_fee_rate;
             }
@@ -152,6 +273,10 @@ impl PoolConfig {
                 self.interest_update_period = interest_update_period;
             }
 
+            UpdatePoolConfigInput::MaxInterestRateChange(max_interest_rate_change) => {
+                self.max_interest_rate_change = max_interest_rate_change;
+            }
+
             UpdatePoolConfigInput::PriceUpdatePeriod(price_update_period) => {
                 self.price_update_period = price_update_period;
             }
@@ -159,6 +284,18 @@ impl PoolConfig {
             UpdatePoolConfigInput::PriceExpirationPeriod(price_expiration_period) => {
                 self.price_expiration_period = price_expiration_period;
             }
+
+            UpdatePoolConfigInput::FixedTermLoan(fixed_term_loan) => {
+                self.fixed_term_loan = fixed_term_loan;
+            }
+
+            UpdatePoolConfigInput::StableRateLoan(stable_rate_loan) => {
+                self.stable_rate_loan = stable_rate_loan;
+            }
+
+            UpdatePoolConfigInput::Twap(twap) => {
+                self.twap = twap;
+            }
         };
 
         self.check()?;
@@ -198,6 +335,23 @@ impl PoolConfig {
                     }
                 }
             }
+
+            CheckPoolConfigLimitInput::FlashloanLimit(amount, available_amount) => {
+                let limit = match self.flashloan_limit {
+                    Some(FlashloanLimit::Amount(limit)) => Some(limit),
+                    Some(FlashloanLimit::LiquidityRatio(ratio)) => Some(available_amount * ratio),
+                    None => None,
+                };
+
+                if let Some(limit) = limit {
+                    if amount > limit {
+                        return Err(
+                            "Flashloan limit reached. Please try again with a smaller amount."
+                                .into(),
+                        );
+                    }
+                }
+            }
         };
 
         Ok(())
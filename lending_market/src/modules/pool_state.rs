@@ -1,6 +1,9 @@
 use super::operation_status::*;
 use crate::lending_market::lending_market::*;
-use crate::modules::{interest_strategy::*, liquidation_threshold::*, pool_config::*, utils::*};
+use crate::modules::{
+    fixed_rate_loan::*, interest_strategy::*, liquidation_threshold::*, pool_config::*,
+    price_source::*, redemption_queue::*, utils::*,
+};
 use scrypto::blueprints::consensus_manager::*;
 use scrypto::prelude::*;
 
@@ -17,6 +20,43 @@ pub enum LendingPoolUpdatedEventType {
 pub struct LendingPoolUpdatedEvent {
     pub pool_res_address: ResourceAddress,
     pub event_type: LendingPoolUpdatedEventType,
+    pub borrow_index: PreciseDecimal,
+    pub supply_index: PreciseDecimal,
+}
+
+/// Price feed from which the pool price was taken
+#[derive(ScryptoSbor, PartialEq, Debug)]
+pub enum PriceFeedSource {
+    Primary,
+    Fallback,
+    /// No feed provided a fresh price, the last known price was kept
+    LastKnown,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct PriceFeedUsedEvent {
+    pub pool_res_address: ResourceAddress,
+    pub source: PriceFeedSource,
+    pub price: Decimal,
+    pub price_updated_at: i64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct FlashloanTakenEvent {
+    pub pool_res_address: ResourceAddress,
+    pub amount: Decimal,
+    pub fee_amount: Decimal,
+    pub protocol_fee_amount: Decimal,
+    pub lp_fee_amount: Decimal,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct FlashloanRepaidEvent {
+    pub pool_res_address: ResourceAddress,
+    pub amount: Decimal,
+    pub fee_amount: Decimal,
+    pub protocol_fee_amount: Decimal,
+    pub lp_fee_amount: Decimal,
 }
 
 #[derive(ScryptoSbor)]
@@ -35,18 +75,37 @@ pub struct LendingPoolState {
 
     ///* State *///
 
-    ///
+    /// Spot price from the price feed
     pub price: Decimal,
 
+    /// Price used to value collaterals, depends on the pool TWAP config
+    pub collateral_price: Decimal,
+
+    /// Price used to value loans, depends on the pool TWAP config
+    pub loan_price: Decimal,
+
+    /// Spot price confidence from the price feed, zero when not provided
+    pub price_confidence: Decimal,
+
     ///
     pub price_updated_at: i64,
 
-    ///
+    /// Interest rate applied to the loans, follows the target rate within the configured max change
     pub interest_rate: Decimal,
 
+    /// Interest rate given by the interest strategy at the last update
+    pub target_interest_rate: Decimal,
+
     ///
     pub interest_updated_at: i64,
 
+    /// Cumulative borrow interest factor since the pool creation
+    pub borrow_index: PreciseDecimal,
+
+    /// Cumulative pool unit value growth since the pool creation: interest, flashloan and pool fees
+    /// and yield strategy gains and losses
+    pub supply_index: PreciseDecimal,
+
     ///* Loan State *///
 
     ///
@@ -55,14 +114,51 @@ pub struct LendingPoolState {
     ///
     pub total_loan_unit: Decimal,
 
+    /// Aggregate of the fixed-term loans, its rate is the amount weighted average rate
+    pub fixed_term_debt: FixedRateLoan,
+
+    /// Aggregate of the stable rate loans, its rate is the amount weighted average rate
+    pub stable_rate_debt: FixedRateLoan,
+
+    /// Amount weighted average rate of all loan kinds at the last interest update
+    pub blended_interest_rate: Decimal,
+
+    ///* Flashloan State *///
+
+    /// Cumulative amount lent through flashloans
+    pub total_flashloan_volume: Decimal,
+
+    /// Cumulative flashloan fees added to the pool liquidity
+    pub total_flashloan_lp_fees: Decimal,
+
+    /// Cumulative flashloan fees sent to the reserve
+    pub total_flashloan_reserve_fees: Decimal,
+
+    ///* Redemption Queue *///
+
+    /// Redemptions waiting for liquidity, filled as loans are repaid
+    pub redemption_queue: RedemptionQueue,
+
     ///* Configs *///
 
     ///
     pub price_feed_comp: Global<AnyComponent>,
 
+    /// Secondary price feed used when the primary feed price is missing or too old
+    pub fallback_price_feed_comp: Option<Global<AnyComponent>>,
+
+    /// How the pool resource price is derived from the price feed
+    pub price_source: PriceSource,
+
+    /// Market base currency in which the pool resource price is quoted
+    pub base_currency: ResourceAddress,
+
     ///
     pub interest_strategy: InterestStrategy,
 
+    /// Optional utilization based flashloan fee curve replacing the flat flashloan fee rate
+    pub flashloan_fee_strategy: Option<InterestStrategy>,
+
     ///
     pub liquidation_threshold: LiquidationThreshold,
 
@@ -101,12 +197,113 @@ impl LendingPoolState {
         Ok(ratio)
     }
 
+    /// Get the pool liquidity that is not lent out, including the liquidity moved to the
+    /// yield strategy, and the borrowed amount
+    pub fn get_pooled_amount(&self) -> (Decimal, Decimal) {
+        let (pool_available_amount, pool_borrowed_amount, pool_strategy_amount) =
+            self.pool.get_pooled_amount();
+
+        (
+            pool_available_amount + pool_strategy_amount,
+            pool_borrowed_amount,
+        )
+    }
+
+    /// Get the spot price confidence relative to the spot price.
+    /// Collateral and loan prices may come from the TWAP, the confidence is applied to them as a ratio
+    pub fn get_relative_price_confidence(&self) -> Decimal {
+        if self.price > Decimal::ZERO {
+            self.price_confidence / self.price
+        } else {
+            Decimal::ZERO
+        }
+    }
+
+    /// Get the current pool utilization: borrowed amount / total liquidity
+    pub fn get_utilization(&self) -> Decimal {
+        let (pool_available_amount, pool_borrowed_amount) = self.get_pooled_amount();
+
+        let pool_total_liquidity = pool_available_amount + pool_borrowed_amount;
+
+        if pool_total_liquidity == 0.into() {
+            Decimal::ZERO
+        } else {
+            pool_borrowed_amount / pool_total_liquidity
+        }
+    }
+
+    /// Get the borrow and supply rates given by the interest strategy at the given utilization.
+    /// The borrow rate is the variable rate, the supply rate accounts for the fixed rate loans
+    pub fn get_pool_rates(&self, utilization: Decimal) -> Result<PoolRates, String> {
+        let mut pool_rates = self
+            .interest_strategy
+            .get_pool_rates(utilization, self.pool_config.protocol_interest_fee_rate)?;
+
+        pool_rates.supply_rate = self.get_blended_interest_rate(pool_rates.borrow_rate)
+            * utilization
+            * (Decimal::ONE - self.pool_config.protocol_interest_fee_rate);
+
+        Ok(pool_rates)
+    }
+
+    /// Get the pool rates currently applied. When the rate change per update period is capped,
+    /// the applied borrow rate follows the interest strategy rate with a lag
+    pub fn get_applied_pool_rates(&self) -> PoolRates {
+        let utilization = self.get_utilization();
+
+        PoolRates {
+            utilization,
+            borrow_rate: self.interest_rate,
+            supply_rate: self.get_blended_interest_rate(self.interest_rate)
+                * utilization
+                * (Decimal::ONE - self.pool_config.protocol_interest_fee_rate),
+        }
+    }
+
+    /// Get the fee rate applied to a flashloan of the given amount.
+    /// When a flashloan fee strategy is set, the rate follows the pool utilization after the loan is taken
+    pub fn get_flashloan_fee_rate(&self, amount: Decimal) -> Result<Decimal, String> {
+        let (pool_available_amount, pool_borrowed_amount) = self.get_pooled_amount();
+
+        let pool_total_liquidity = pool_available_amount + pool_borrowed_amount;
+
+        match &self.flashloan_fee_strategy {
+            Some(flashloan_fee_strategy) if pool_total_liquidity != 0.into() => {
+                let pool_utilization =
+                    ((pool_borrowed_amount + amount) / pool_total_liquidity).min(Decimal::ONE);
+
+                Ok(flashloan_fee_strategy
+                    .get_interest_rate(pool_utilization)?
+                    .min(Decimal::ONE))
+            }
+            _ => Ok(self.pool_config.flashloan_fee_rate),
+        }
+    }
+
     ///* CORE LOGIC AND UTILITY METHODS *///
 
-    pub fn contribute_proxy(&self, assets: Bucket) -> Result<Bucket, String> {
+    pub fn withdraw_for_flashloan(&self, amount: Decimal) -> Result<Bucket, String> {
+        let (pool_available_amount, _, _) = self.pool.get_pooled_amount();
+
+        // Check if the flashloan limit is reached
+        self.pool_config
+            .check_limit(CheckPoolConfigLimitInput::FlashloanLimit(
+                amount,
+                pool_available_amount,
+            ))?;
+
+        Ok(self.pool.protected_withdraw(
+            amount,
+            WithdrawType::TemporaryUse,
+            WithdrawStrategy::Rounded(RoundingMode::ToNearestMidpointToEven),
+        ))
+    }
+
+    /// Contribute to the pool, the added liquidity is used to fill the redemption queue first
+    pub fn contribute_proxy(&mut self, assets: Bucket) -> Result<Bucket, String> {
         let amount = assets.amount();
 
-        let (pool_available_amount, pool_borrowed_amount) = self.pool.get_pooled_amount();
+        let (pool_available_amount, pool_borrowed_amount) = self.get_pooled_amount();
 
         // Check if the pool deposit limit is reached
         self.pool_config
@@ -114,21 +311,43 @@ impl LendingPoolState {
                 pool_available_amount + pool_borrowed_amount + amount,
             ))?;
 
-        Runtime::emit_event(LendingPoolUpdatedEvent {
-            pool_res_address: self.pool_res_address,
-            event_type: LendingPoolUpdatedEventType::DepositState,
-        });
+        let unit_ratio = self.pool.get_pool_unit_ratio();
+
+        let pool_units = self.pool.contribute(assets);
 
-        Ok(self.pool.contribute(assets))
+        self.sync_supply_index(unit_ratio);
+
+        self.fill_redemption_queue()?;
+
+        self._emit_pool_updated_event(LendingPoolUpdatedEventType::DepositState);
+
+        Ok(pool_units)
     }
 
-    pub fn redeem_proxy(&self, assets: Bucket) -> Bucket {
-        Runtime::emit_event(LendingPoolUpdatedEvent {
-            pool_res_address: self.pool_res_address,
-            event_type: LendingPoolUpdatedEventType::DepositState,
-        });
+    pub fn redeem_proxy(&mut self, pool_units: Bucket) -> Bucket {
+        let unit_ratio = self.pool.get_pool_unit_ratio();
+
+        let assets = self.pool.redeem(pool_units);
+
+        self.sync_supply_index(unit_ratio);
 
-        self.pool.redeem(assets)
+        self._emit_pool_updated_event(LendingPoolUpdatedEventType::DepositState);
+
+        assets
+    }
+
+    /// Redeem pool units taken out of the CDP collaterals by liquidations and deleverage orders.
+    /// The pool minimum and redemption fee do not apply
+    pub fn protected_redeem_proxy(&mut self, pool_units: Bucket) -> Bucket {
+        let unit_ratio = self.pool.get_pool_unit_ratio();
+
+        let assets = self.pool.protected_redeem(pool_units);
+
+        self.sync_supply_index(unit_ratio);
+
+        self._emit_pool_updated_event(LendingPoolUpdatedEventType::DepositState);
+
+        assets
     }
 
     pub fn add_pool_units_as_collateral(&mut self, pool_units: Bucket) -> Result<(), String> {
@@ -142,10 +361,7 @@ impl LendingPoolState {
 
         self.collaterals.put(pool_units);
 
-        Runtime::emit_event(LendingPoolUpdatedEvent {
-            pool_res_address: self.pool_res_address,
-            event_type: LendingPoolUpdatedEventType::CollateralState,
-        });
+        self._emit_pool_updated_event(LendingPoolUpdatedEventType::CollateralState);
 
         Ok(())
     }
@@ -162,10 +378,7 @@ impl LendingPoolState {
             return Err("Not enough pool units to remove from collateral".into());
         }
 
-        Runtime::emit_event(LendingPoolUpdatedEvent {
-            pool_res_address: self.pool_res_address,
-            event_type: LendingPoolUpdatedEventType::CollateralState,
-        });
+        self._emit_pool_updated_event(LendingPoolUpdatedEventType::CollateralState);
 
         Ok(self.collaterals.take_advanced(
             pool_unit_amount,
@@ -180,21 +393,7 @@ impl LendingPoolState {
             return Err("Amount must be positive".into());
         }
 
-        let (pool_available_amount, pool_borrowed_amount) = self.pool.get_pooled_amount();
-
-        // Check if the borrow limit is reached
-        self.pool_config
-            .check_limit(CheckPoolConfigLimitInput::BorrowLimit(
-                pool_borrowed_amount + amount,
-            ))?;
-
-        // Check if utilization rate is not exceeded
-
-        self.pool_config
-            .check_limit(CheckPoolConfigLimitInput::UtilizationLimit(
-                (pool_borrowed_amount + amount)
-                    / ((pool_available_amount + pool_borrowed_amount) + amount),
-            ))?;
+        self._check_borrow_limits(amount)?;
 
         let loan_unit = self._update_loan_unit(amount)?;
 
@@ -207,10 +406,7 @@ impl LendingPoolState {
             loan_unit,
         );
 
-        Runtime::emit_event(LendingPoolUpdatedEvent {
-            pool_res_address: self.pool_res_address,
-            event_type: LendingPoolUpdatedEventType::LoanState,
-        });
+        self._emit_pool_updated_event(LendingPoolUpdatedEventType::LoanState);
 
         Ok(result)
     }
@@ -227,114 +423,558 @@ impl LendingPoolState {
         self.pool
             .protected_deposit(payment, DepositType::FromTemporaryUse);
 
-        Runtime::emit_event(LendingPoolUpdatedEvent {
-            pool_res_address: self.pool_res_address,
-            event_type: LendingPoolUpdatedEventType::LoanState,
-        });
+        self.fill_redemption_queue()?;
+
+        self._emit_pool_updated_event(LendingPoolUpdatedEventType::LoanState);
 
         // returned unit should be negative or 0
         // Send back positive loan_unit to evoid confusion at higher level in the stack
         Ok(-loan_unit)
     }
 
+    /// Get the rate locked by a fixed rate loan of the given amount:
+    /// the variable rate at the utilization after the loan is taken plus the configured premium
+    pub fn get_fixed_rate(
+        &self,
+        loan_type: FixedRateLoanType,
+        amount: Decimal,
+    ) -> Result<Decimal, String> {
+        let rate_premium = match loan_type {
+            FixedRateLoanType::FixedTerm => {
+                self.pool_config
+                    .fixed_term_loan
+                    .as_ref()
+                    .ok_or("Fixed-term loans are not enabled for this pool")?
+                    .rate_premium
+            }
+            FixedRateLoanType::Stable => {
+                self.pool_config
+                    .stable_rate_loan
+                    .as_ref()
+                    .ok_or("Stable rate loans are not enabled for this pool")?
+                    .rate_premium
+            }
+        };
+
+        let (pool_available_amount, pool_borrowed_amount) = self.get_pooled_amount();
+
+        let pool_total_liquidity = pool_available_amount + pool_borrowed_amount;
+
+        let pool_utilization = if pool_total_liquidity == 0.into() {
+            Decimal::ONE
+        } else {
+            ((pool_borrowed_amount + amount) / pool_total_liquidity).min(Decimal::ONE)
+        };
+
+        Ok(self.interest_strategy.get_interest_rate(pool_utilization)? + rate_premium)
+    }
+
+    /// Get the amount weighted average rate of all loan kinds for the given variable rate.
+    /// This is the rate the supplier yield is based on
+    pub fn get_blended_interest_rate(&self, variable_rate: Decimal) -> Decimal {
+        let total_amount =
+            self.total_loan + self.fixed_term_debt.principal + self.stable_rate_debt.principal;
+
+        if total_amount == Decimal::ZERO {
+            return variable_rate;
+        }
+
+        (self.total_loan * variable_rate
+            + self.fixed_term_debt.rate_weighted_principal
+            + self.stable_rate_debt.rate_weighted_principal)
+            / total_amount
+    }
+
+    /// Check if stable rate loans can be rebalanced to the current rate:
+    /// supply rate below the configured threshold or utilization above the configured threshold
+    pub fn check_stable_rate_rebalance(&self) -> Result<(), String> {
+        let stable_rate_loan_config = self
+            .pool_config
+            .stable_rate_loan
+            .as_ref()
+            .ok_or("Stable rate loans are not enabled for this pool")?;
+
+        let pool_rates = self.get_pool_rates(self.get_utilization())?;
+
+        if pool_rates.supply_rate >= stable_rate_loan_config.rebalance_supply_rate_threshold
+            && pool_rates.utilization <= stable_rate_loan_config.rebalance_utilization_threshold
+        {
+            return Err("Stable rate rebalance conditions are not met".into());
+        }
+
+        Ok(())
+    }
+
+    /// Handle request to take a fixed rate loan locked at the provided rate
+    pub fn withdraw_for_fixed_rate_borrow(
+        &mut self,
+        loan_type: FixedRateLoanType,
+        amount: Decimal,
+        rate: Decimal,
+    ) -> Result<Bucket, String> {
+        if amount <= 0.into() {
+            return Err("Amount must be positive".into());
+        }
+
+        self._check_borrow_limits(amount)?;
+
+        self._accrue_fixed_rate_interest()?;
+
+        self._get_fixed_rate_debt(loan_type)
+            .increase(amount, rate, Self::_now())?;
+
+        let result = self.pool.protected_withdraw(
+            amount,
+            WithdrawType::TemporaryUse,
+            WithdrawStrategy::Rounded(RoundingMode::ToNearestMidpointToEven),
+        );
+
+        self._emit_pool_updated_event(LendingPoolUpdatedEventType::LoanState);
+
+        Ok(result)
+    }
+
+    /// Handle repayment of a fixed rate loan, `repayment` is the repaid part of the loan
+    pub fn deposit_for_fixed_rate_repay(
+        &mut self,
+        loan_type: FixedRateLoanType,
+        payment: Bucket,
+        repayment: FixedRateLoanChange,
+    ) -> Result<(), String> {
+        if payment.resource_address() != self.pool_res_address {
+            return Err("Payment resource address mismatch".into());
+        }
+
+        self._accrue_fixed_rate_interest()?;
+
+        self._get_fixed_rate_debt(loan_type)
+            .remove(&repayment, Self::_now())?;
+
+        self.pool
+            .protected_deposit(payment, DepositType::FromTemporaryUse);
+
+        self.fill_redemption_queue()?;
+
+        self._emit_pool_updated_event(LendingPoolUpdatedEventType::LoanState);
+
+        Ok(())
+    }
+
+    /// Queue pool units the pool can not redeem right away and return the request index.
+    /// The pool minimum applies to the whole request, units redeemable now must use `redeem`
+    pub fn push_redemption_request(&mut self, pool_units: Bucket) -> Result<u64, String> {
+        let units = pool_units.amount();
+
+        let (_, min_redemption_units) = self.pool.get_min_amounts();
+
+        if units < min_redemption_units {
+            return Err("Redemption amount is below the minimum".into());
+        }
+
+        let (pool_available_amount, _) = self.get_pooled_amount();
+
+        let redeemable_units =
+            PreciseDecimal::from(pool_available_amount) * self.pool.get_pool_unit_ratio();
+
+        if self.redemption_queue.is_empty() && PreciseDecimal::from(units) <= redeemable_units {
+            return Err("Enough liquidity to redeem right away".into());
+        }
+
+        let request_index = self.redemption_queue.push(pool_units)?;
+
+        self.fill_redemption_queue()?;
+
+        Ok(request_index)
+    }
+
+    /// Redeem the queued pool units first-in, first-out with the available liquidity.
+    /// Partial fills pay the redemption fee, the minimum was checked on the whole request
+    pub fn fill_redemption_queue(&mut self) -> Result<(), String> {
+        while !self.redemption_queue.is_empty() {
+            // Liquidity moved to the yield strategy is recalled by the pool on redemption
+            let (pool_available_amount, _) = self.get_pooled_amount();
+
+            let pool_unit_ratio = self.pool.get_pool_unit_ratio();
+
+            let max_units = (PreciseDecimal::from(pool_available_amount) * pool_unit_ratio)
+                .checked_truncate(RoundingMode::ToZero)
+                .ok_or("Error calculating redeemable pool units")?;
+
+            let head = self.redemption_queue.head;
+
+            let mut request = self
+                .redemption_queue
+                .requests
+                .get_mut(&head)
+                .ok_or("Redemption request not found")?;
+
+            let units = request.remaining_units.min(max_units);
+
+            let is_dust = (PreciseDecimal::from(units) / pool_unit_ratio)
+                .checked_truncate(RoundingMode::ToZero)
+                .ok_or("Error calculating redeemable amount")?
+                == Decimal::ZERO;
+
+            // Wait for more liquidity, leftover units worth nothing are redeemed for nothing
+            if is_dust && units < request.remaining_units {
+                break;
+            }
+
+            let assets = self
+                .pool
+                .queued_redeem(self.redemption_queue.units.take(units));
+
+            self.sync_supply_index(pool_unit_ratio);
+
+            request.remaining_units -= units;
+            request.claimable_amount += assets.amount();
+
+            self.redemption_queue.assets.put(assets);
+
+            let filled = request.remaining_units == Decimal::ZERO;
+
+            drop(request);
+
+            if !filled {
+                break;
+            }
+
+            self.redemption_queue.head += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Move a closed fixed rate loan to the variable rate loans and return the created loan units.
+    /// The borrowed liquidity stays in use, only the loan accounting changes
+    pub fn convert_fixed_rate_to_variable(
+        &mut self,
+        loan_type: FixedRateLoanType,
+        loan: FixedRateLoanChange,
+    ) -> Result<Decimal, String> {
+        self._accrue_fixed_rate_interest()?;
+
+        self._get_fixed_rate_debt(loan_type)
+            .remove(&loan, Self::_now())?;
+
+        let loan_unit = self._update_loan_unit(loan.get_amount())?;
+
+        self._emit_pool_updated_event(LendingPoolUpdatedEventType::LoanState);
+
+        Ok(loan_unit)
+    }
+
+    /// Move a stable rate loan from its locked rate to a new rate,
+    /// given the rate weighted principal of the loan before and after the change
+    pub fn rebalance_stable_rate(
+        &mut self,
+        previous_rate_weighted_principal: Decimal,
+        rate_weighted_principal: Decimal,
+    ) -> Result<(), String> {
+        self._accrue_fixed_rate_interest()?;
+
+        self.stable_rate_debt.replace_rate_weighted_principal(
+            previous_rate_weighted_principal,
+            rate_weighted_principal,
+            Self::_now(),
+        )?;
+
+        self._emit_pool_updated_event(LendingPoolUpdatedEventType::LoanState);
+
+        Ok(())
+    }
+
     pub fn update_interest_and_price(
         &mut self,
         bypass_debounce: Option<(bool, bool)>,
     ) -> Result<(), String> {
-        let now: i64 = Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch;
+        let (bypass_price_debounce, bypass_interest_debounce) =
+            bypass_debounce.unwrap_or((false, false));
+
+        self.update_price(bypass_price_debounce)?;
 
+        self.update_interest(bypass_interest_debounce)
+    }
+
+    /// Same as `update_interest_and_price`, but keep the last known price when neither the primary
+    /// nor the fallback feed provides a fresh one. Only for operations that can not lower the CDP health
+    pub fn update_interest_and_last_known_price(
+        &mut self,
+        bypass_debounce: Option<(bool, bool)>,
+    ) -> Result<(), String> {
         let (bypass_price_debounce, bypass_interest_debounce) =
             bypass_debounce.unwrap_or((false, false));
 
-        /* UPDATING PRICE */
+        match self.update_price(bypass_price_debounce) {
+            Ok(()) => {}
+            // Only a missing or too old price is replaced, a misconfigured price source still fails
+            Err(PriceError::Unavailable(_)) => {
+                self._emit_price_feed_used_event(PriceFeedSource::LastKnown)
+            }
+            Err(error) => return Err(error.into()),
+        }
+
+        self.update_interest(bypass_interest_debounce)
+    }
+
+    pub fn update_price(&mut self, bypass_price_debounce: bool) -> Result<(), PriceError> {
+        let now: i64 = Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch;
 
         // Debounce price update to configured period (in minutes)
         if ((now - self.price_updated_at) / SECOND_PER_MINUTE)
             >= self.pool_config.price_update_period
             || bypass_price_debounce
         {
-            let price_feed_result = get_price(self.price_feed_comp, self.pool_res_address)?;
-
-            // Handle price update too old
-            if ((now - price_feed_result.timestamp) / SECOND_PER_MINUTE)
-                >= self.pool_config.price_expiration_period
-            {
-                return Err("Price info is too old".to_string());
-            }
-
+            // Use the fallback feed when the primary feed price is missing or too old
+            let ((spot_price_info, collateral_price, loan_price), source) =
+                match self._get_feed_prices(self.price_feed_comp, now) {
+                    Ok(prices) => (prices, PriceFeedSource::Primary),
+                    Err(PriceError::Unavailable(error)) => {
+                        let fallback_price_feed = self
+                            .fallback_price_feed_comp
+                            .ok_or(PriceError::Unavailable(error))?;
+
+                        (
+                            self._get_feed_prices(fallback_price_feed, now)?,
+                            PriceFeedSource::Fallback,
+                        )
+                    }
+                    Err(error) => return Err(error),
+                };
+
+            self.collateral_price = collateral_price;
+            self.loan_price = loan_price;
             self.price_updated_at = now;
-            self.price = price_feed_result.price;
+            self.price = spot_price_info.price;
+            self.price_confidence = spot_price_info.confidence.unwrap_or(Decimal::ZERO);
 
-            Runtime::emit_event(LendingPoolUpdatedEvent {
-                pool_res_address: self.pool_res_address,
-                event_type: LendingPoolUpdatedEventType::Price,
-            });
+            self._emit_pool_updated_event(LendingPoolUpdatedEventType::Price);
+            self._emit_price_feed_used_event(source);
         }
 
+        Ok(())
+    }
+
+    pub fn update_interest(&mut self, bypass_interest_debounce: bool) -> Result<(), String> {
+        let now: i64 = Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch;
+
         /* UPDATING INTEREST RATE */
 
         // Debounce interest update to configured period (in minutes)
         let period_in_minute = (now - self.interest_updated_at) / SECOND_PER_MINUTE;
         if period_in_minute >= self.pool_config.interest_update_period || bypass_interest_debounce {
-            let (pool_available_amount, pool_borrowed_amount) = self.pool.get_pooled_amount();
+            self.interest_updated_at = now;
 
-            let pool_total_liquidity = pool_available_amount + pool_borrowed_amount;
+            let pool_utilization = self.get_utilization();
 
-            let pool_utilization = if pool_total_liquidity == 0.into() {
-                Decimal::ZERO
-            } else {
-                pool_borrowed_amount / pool_total_liquidity
-            };
+            self.interest_strategy
+                .update_rate_at_target(pool_utilization, period_in_minute)?;
 
-            self.interest_updated_at = now;
+            self.target_interest_rate =
+                self.interest_strategy.get_interest_rate(pool_utilization)?;
 
-            self.interest_rate = self.interest_strategy.get_interest_rate(pool_utilization)?;
+            // Incentivize repayments while lenders are waiting for liquidity
+            if !self.redemption_queue.is_empty() {
+                self.target_interest_rate += self.redemption_queue.rate_premium;
+            }
+
+            self.interest_rate = match self.pool_config.max_interest_rate_change {
+                Some(max_interest_rate_change) => {
+                    // Allowed change scales with the number of elapsed update periods
+                    let max_change = max_interest_rate_change * period_in_minute
+                        / self.pool_config.interest_update_period;
+
+                    self.target_interest_rate.clamp(
+                        self.interest_rate - max_change,
+                        self.interest_rate + max_change,
+                    )
+                }
+                None => self.target_interest_rate,
+            };
 
             // Calculate interest rate down to a minute (1 YEAR = 525600 minutes)
             let minute_interest_rate = PreciseDecimal::ONE + (self.interest_rate / MINUTE_PER_YEAR);
 
-            let new_total_loan_amount =
-                self.total_loan * minute_interest_rate.checked_powi(period_in_minute).unwrap();
+            let interest_factor = minute_interest_rate.checked_powi(period_in_minute).unwrap();
+
+            let new_total_loan_amount = self.total_loan * interest_factor;
 
             let accrued_interest_amount = new_total_loan_amount - self.total_loan;
 
+            self.borrow_index = self.borrow_index * interest_factor;
+
             self.total_loan = new_total_loan_amount
                 .checked_truncate(RoundingMode::ToNearestMidpointToEven)
                 .unwrap();
 
-            // Virtually increase pooled liquidity with accrued interest amount
-            self.pool.increase_external_liquidity(
-                accrued_interest_amount
-                    .checked_truncate(RoundingMode::ToNearestMidpointToEven)
-                    .unwrap(),
-            );
-
-            //Calculate protocol fees on accrued interest amount
-            let protocol_fee_amount =
-                accrued_interest_amount * self.pool_config.protocol_interest_fee_rate;
-
-            // Permanent withdraw collected fee from pool to the reserve vault
-            self.reserve.put(
-                self.pool.protected_withdraw(
-                    protocol_fee_amount
-                        .checked_truncate(RoundingMode::ToNearestMidpointToEven)
-                        .unwrap(),
-                    WithdrawType::LiquidityWithdrawal,
-                    WithdrawStrategy::Rounded(RoundingMode::ToNearestMidpointToEven),
-                ),
-            );
-
-            Runtime::emit_event(LendingPoolUpdatedEvent {
-                pool_res_address: self.pool_res_address,
-                event_type: LendingPoolUpdatedEventType::Interest,
-            });
+            // Fixed rate loans accrue at their own locked rates
+            let accrued_interest_amount = accrued_interest_amount
+                + self.fixed_term_debt.accrue_interest(now)?
+                + self.stable_rate_debt.accrue_interest(now)?;
+
+            self.blended_interest_rate = self.get_blended_interest_rate(self.interest_rate);
+
+            self._distribute_interest(accrued_interest_amount);
+
+            self._emit_pool_updated_event(LendingPoolUpdatedEventType::Interest);
         }
 
         Ok(())
     }
 
+    /// Move the supply index with the pool unit value since the pool had the given unit ratio.
+    /// Called after every operation changing the ratio: interest, fees kept by the pool and yield strategy results
+    pub fn sync_supply_index(&mut self, old_unit_ratio: PreciseDecimal) {
+        let unit_ratio = self.pool.get_pool_unit_ratio();
+
+        if unit_ratio == PreciseDecimal::ZERO {
+            return;
+        }
+
+        // The unit ratio is in units per asset, the unit value is its inverse
+        self.supply_index = self.supply_index * old_unit_ratio / unit_ratio;
+    }
+
     ///* PRIVATE UTILITY METHODS *///
 
+    /// Get the spot price info, collateral and loan prices from a price feed, fails if any of them is too old
+    fn _get_feed_prices(
+        &self,
+        price_feed: Global<AnyComponent>,
+        now: i64,
+    ) -> Result<(PriceInfo, Decimal, Decimal), PriceError> {
+        let price_feed_result = self.price_source.get_price(
+            price_feed,
+            self.pool_res_address,
+            self.base_currency,
+            None,
+        )?;
+
+        // Handle price update too old
+        if ((now - price_feed_result.timestamp) / SECOND_PER_MINUTE)
+            >= self.pool_config.price_expiration_period
+        {
+            return Err(PriceError::Unavailable("Price info is too old".into()));
+        }
+
+        let spot_price = price_feed_result.price;
+
+        let (collateral_price, loan_price) = match &self.pool_config.twap {
+            Some(twap_config) => {
+                let twap_result = self.price_source.get_price(
+                    price_feed,
+                    self.pool_res_address,
+                    self.base_currency,
+                    Some(twap_config.window),
+                )?;
+
+                if ((now - twap_result.timestamp) / SECOND_PER_MINUTE)
+                    >= self.pool_config.price_expiration_period
+                {
+                    return Err(PriceError::Unavailable("TWAP info is too old".into()));
+                }
+
+                twap_config.get_collateral_and_loan_prices(spot_price, twap_result.price)
+            }
+            None => (spot_price, spot_price),
+        };
+
+        Ok((price_feed_result, collateral_price, loan_price))
+    }
+
+    fn _emit_pool_updated_event(&self, event_type: LendingPoolUpdatedEventType) {
+        Runtime::emit_event(LendingPoolUpdatedEvent {
+            pool_res_address: self.pool_res_address,
+            event_type,
+            borrow_index: self.borrow_index,
+            supply_index: self.supply_index,
+        });
+    }
+
+    fn _emit_price_feed_used_event(&self, source: PriceFeedSource) {
+        Runtime::emit_event(PriceFeedUsedEvent {
+            pool_res_address: self.pool_res_address,
+            source,
+            price: self.price,
+            price_updated_at: self.price_updated_at,
+        });
+    }
+
+    fn _now() -> i64 {
+        Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch
+    }
+
+    fn _check_borrow_limits(&self, amount: Decimal) -> Result<(), String> {
+        let (pool_available_amount, pool_borrowed_amount) = self.get_pooled_amount();
+
+        // Check if the borrow limit is reached
+        self.pool_config
+            .check_limit(CheckPoolConfigLimitInput::BorrowLimit(
+                pool_borrowed_amount + amount,
+            ))?;
+
+        // Check if utilization rate is not exceeded
+
+        self.pool_config
+            .check_limit(CheckPoolConfigLimitInput::UtilizationLimit(
+                (pool_borrowed_amount + amount)
+                    / ((pool_available_amount + pool_borrowed_amount) + amount),
+            ))
+    }
+
+    fn _get_fixed_rate_debt(&mut self, loan_type: FixedRateLoanType) -> &mut FixedRateLoan {
+        match loan_type {
+            FixedRateLoanType::FixedTerm => &mut self.fixed_term_debt,
+            FixedRateLoanType::Stable => &mut self.stable_rate_debt,
+        }
+    }
+
+    // Accrue the fixed rate loans interest outside of the debounced interest update,
+    // before the aggregate amounts and rates are changed
+    fn _accrue_fixed_rate_interest(&mut self) -> Result<(), String> {
+        let now = Self::_now();
+
+        let accrued_interest_amount = self.fixed_term_debt.accrue_interest(now)?
+            + self.stable_rate_debt.accrue_interest(now)?;
+
+        self._distribute_interest(accrued_interest_amount.into());
+
+        Ok(())
+    }
+
+    // Add accrued interest to the pool liquidity and send the protocol share to the reserve
+    fn _distribute_interest(&mut self, accrued_interest_amount: PreciseDecimal) {
+        if accrued_interest_amount == PreciseDecimal::ZERO {
+            return;
+        }
+
+        let unit_ratio = self.pool.get_pool_unit_ratio();
+
+        // Virtually increase pooled liquidity with accrued interest amount
+        self.pool.increase_external_liquidity(
+            accrued_interest_amount
+                .checked_truncate(RoundingMode::ToNearestMidpointToEven)
+                .unwrap(),
+        );
+
+        //Calculate protocol fees on accrued interest amount
+        let protocol_fee_amount =
+            accrued_interest_amount * self.pool_config.protocol_interest_fee_rate;
+
+        // Permanent withdraw collected fee from pool to the reserve vault
+        self.reserve.put(
+            self.pool.protected_withdraw(
+                protocol_fee_amount
+                    .checked_truncate(RoundingMode::ToNearestMidpointToEven)
+                    .unwrap(),
+                WithdrawType::LiquidityWithdrawal,
+                WithdrawStrategy::Rounded(RoundingMode::ToNearestMidpointToEven),
+            ),
+        );
+
+        self.sync_supply_index(unit_ratio);
+    }
+
     fn _update_loan_unit(&mut self, amount: Decimal) -> Result<Decimal, String> {
         let unit_ratio = self.get_loan_unit_ratio()?;
 
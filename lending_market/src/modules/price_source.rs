@@ -0,0 +1,170 @@
+use crate::modules::utils::*;
+use scrypto::prelude::*;
+
+/// Error getting the price of a pool resource
+#[derive(Debug, PartialEq)]
+pub enum PriceError {
+    /// A price feed has no fresh price, the last known price may be used instead
+    Unavailable(String),
+
+    /// The price source returned an unusable price, e.g. because it is misconfigured
+    Invalid(String),
+}
+impl From<PriceError> for String {
+    fn from(error: PriceError) -> String {
+        match error {
+            PriceError::Unavailable(message) | PriceError::Invalid(message) => message,
+        }
+    }
+}
+
+/// Where a pool gets the price of its resource, in the market base currency
+#[derive(ScryptoSbor, ManifestSbor, Clone, Debug, PartialEq)]
+pub enum PriceSource {
+    /// Price of the pool resource from the pool price feed
+    Feed,
+
+    /// Inverse of the feed price of the given resource, for feeds quoting the base currency in another resource
+    Inverted(ResourceAddress),
+
+    /// Price of the pool resource in an intermediate resource from a dedicated feed,
+    /// multiplied by the price of the intermediate resource from the pool price feed.
+    /// The dedicated feed only needs to implement `get_price`, its spot price is used even when a TWAP is configured
+    CrossRate {
+        pair_feed: ComponentAddress,
+        intermediate_res_address: ResourceAddress,
+    },
+
+    /// Price of a pool unit, derived from the price of the underlying resource
+    /// and the unit ratio of its pool.
+    /// The ratio is read spot: only use pools whose ratio can not be moved by anyone within a transaction,
+    /// e.g. a `SingleResourcePool` where liquidity is only added without minting units through protected methods.
+    /// A pool accepting donations would let anyone inflate the unit price used to value collaterals
+    PoolUnit {
+        pool: ComponentAddress,
+        underlying_res_address: ResourceAddress,
+    },
+
+    /// Redemption value in XRD of a liquid stake unit from its validator, multiplied by the XRD price
+    Lsu(ComponentAddress),
+}
+impl PriceSource {
+    /// Get the pool resource price.
+    /// When a TWAP window is given, the pool price feed prices are replaced by their TWAP
+    pub fn get_price(
+        &self,
+        price_feed: Global<AnyComponent>,
+        pool_res_address: ResourceAddress,
+        base_currency: ResourceAddress,
+        twap_window: Option<i64>,
+    ) -> Result<PriceInfo, PriceError> {
+        let feed_price = |price_feed: Global<AnyComponent>, res_address: ResourceAddress| {
+            match twap_window {
+                Some(window) => get_twap(price_feed, res_address, base_currency, window),
+                None => get_price(price_feed, res_address, base_currency),
+            }
+            .map_err(PriceError::Unavailable)
+        };
+
+        match self {
+            PriceSource::Feed => feed_price(price_feed, pool_res_address),
+
+            PriceSource::Inverted(res_address) => {
+                let price_info = feed_price(price_feed, *res_address)?;
+
+                if price_info.price <= Decimal::ZERO {
+                    return Err(PriceError::Invalid(
+                        "Inverted price must be positive".into(),
+                    ));
+                }
+
+                let price = Decimal::ONE / price_info.price;
+
+                Ok(PriceInfo {
+                    timestamp: price_info.timestamp,
+                    price,
+                    confidence: scale_confidence(price, price_info.get_relative_confidence()),
+                })
+            }
+
+            PriceSource::CrossRate {
+                pair_feed,
+                intermediate_res_address,
+            } => {
+                // The pair feed quotes the pool resource in the intermediate resource
+                let pair_price_info = get_price(
+                    (*pair_feed).into(),
+                    pool_res_address,
+                    *intermediate_res_address,
+                )
+                .map_err(PriceError::Unavailable)?;
+                let intermediate_price_info = feed_price(price_feed, *intermediate_res_address)?;
+
+                let price = pair_price_info.price * intermediate_price_info.price;
+
+                Ok(PriceInfo {
+                    timestamp: pair_price_info
+                        .timestamp
+                        .min(intermediate_price_info.timestamp),
+                    price,
+                    confidence: scale_confidence(
+                        price,
+                        pair_price_info.get_relative_confidence()
+                            + intermediate_price_info.get_relative_confidence(),
+                    ),
+                })
+            }
+
+            PriceSource::PoolUnit {
+                pool,
+                underlying_res_address,
+            } => {
+                let underlying_price_info = feed_price(price_feed, *underlying_res_address)?;
+
+                let unit_ratio = Global::<AnyComponent>::from(*pool)
+                    .call_raw::<PreciseDecimal>("get_pool_unit_ratio", scrypto_args!());
+
+                if unit_ratio <= PreciseDecimal::ZERO {
+                    return Err(PriceError::Invalid(
+                        "Pool unit ratio must be positive".into(),
+                    ));
+                }
+
+                let price = (PreciseDecimal::from(underlying_price_info.price) / unit_ratio)
+                    .checked_truncate(RoundingMode::ToZero)
+                    .ok_or(PriceError::Invalid(
+                        "Error calculating pool unit price".into(),
+                    ))?;
+
+                Ok(PriceInfo {
+                    timestamp: underlying_price_info.timestamp,
+                    price,
+                    confidence: scale_confidence(
+                        price,
+                        underlying_price_info.get_relative_confidence(),
+                    ),
+                })
+            }
+
+            PriceSource::Lsu(validator) => {
+                let redemption_value = Global::<AnyComponent>::from(*validator)
+                    .call_raw::<Decimal>("get_redemption_value", scrypto_args!(Decimal::ONE));
+
+                let xrd_price_info = feed_price(price_feed, XRD)?;
+
+                let price = redemption_value * xrd_price_info.price;
+
+                Ok(PriceInfo {
+                    timestamp: xrd_price_info.timestamp,
+                    price,
+                    confidence: scale_confidence(price, xrd_price_info.get_relative_confidence()),
+                })
+            }
+        }
+    }
+}
+
+// Apply a relative confidence to a derived price, none when the source prices have no confidence
+fn scale_confidence(price: Decimal, relative_confidence: Decimal) -> Option<Decimal> {
+    (relative_confidence > Decimal::ZERO).then(|| price * relative_confidence)
+}
@@ -0,0 +1,98 @@
+use scrypto::prelude::*;
+
+#[derive(ScryptoSbor, Clone, Debug, PartialEq)]
+pub struct RedemptionRequest {
+    /// Pool units waiting for liquidity to be redeemed
+    pub remaining_units: Decimal,
+
+    /// Redeemed assets not claimed yet
+    pub claimable_amount: Decimal,
+}
+
+/// First-in, first-out queue of pool unit redemptions waiting for liquidity
+#[derive(ScryptoSbor)]
+pub struct RedemptionQueue {
+    ///
+    pub requests: KeyValueStore<u64, RedemptionRequest>,
+
+    /// Index of the oldest request not fully filled
+    pub head: u64,
+
+    /// Index given to the next request
+    pub tail: u64,
+
+    /// Pool units locked by the requests not fully filled
+    pub units: Vault,
+
+    /// Redeemed assets waiting to be claimed
+    pub assets: Vault,
+
+    /// Added to the interest rate while the queue is not empty
+    pub rate_premium: Decimal,
+}
+impl RedemptionQueue {
+    pub fn new(pool_unit_res_address: ResourceAddress, pool_res_address: ResourceAddress) -> Self {
+        Self {
+            requests: KeyValueStore::new(),
+            head: 0,
+            tail: 0,
+            units: Vault::new(pool_unit_res_address),
+            assets: Vault::new(pool_res_address),
+            rate_premium: Decimal::ZERO,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head == self.tail
+    }
+
+    /// Lock pool units at the end of the queue and return the request index
+    pub fn push(&mut self, pool_units: Bucket) -> Result<u64, String> {
+        if pool_units.resource_address() != self.units.resource_address() {
+            return Err("Pool unit resource address mismatch".into());
+        }
+
+        if pool_units.amount() <= Decimal::ZERO {
+            return Err("Pool unit amount must be positive".into());
+        }
+
+        let index = self.tail;
+
+        self.requests.insert(
+            index,
+            RedemptionRequest {
+                remaining_units: pool_units.amount(),
+                claimable_amount: Decimal::ZERO,
+            },
+        );
+
+        self.units.put(pool_units);
+
+        self.tail += 1;
+
+        Ok(index)
+    }
+
+    /// Withdraw the redeemed assets of a request.
+    /// Also return whether the request is fully filled, in which case it is removed
+    pub fn claim(&mut self, index: u64) -> Result<(Bucket, bool), String> {
+        let mut request = self
+            .requests
+            .get_mut(&index)
+            .ok_or("Redemption request not found")?;
+
+        let assets = self.assets.take(request.claimable_amount);
+
+        request.claimable_amount = Decimal::ZERO;
+
+        let filled = request.remaining_units == Decimal::ZERO;
+
+        drop(request);
+
+        if filled {
+            self.requests.remove(&index);
+        }
+
+        Ok((assets, filled))
+    }
+}
@@ -22,26 +22,107 @@ pub enum DepositType {
     LiquiditySupply,
 }
 
+#[derive(ScryptoSbor, ManifestSbor, Clone, Copy, PartialEq, Debug)]
+pub enum PoolFeeDestination {
+    Pool,
+    FeeVault,
+}
+
+#[derive(ScryptoSbor, ManifestSbor, Clone, Copy, PartialEq, Debug)]
+pub struct PoolFeeConfig {
+    pub contribution_fee_rate: Decimal,
+    pub redemption_fee_rate: Decimal,
+    pub fee_destination: PoolFeeDestination,
+}
+
+#[derive(ScryptoSbor, Clone, Copy, PartialEq, Debug)]
+pub enum YieldStrategyAction {
+    Allocate(Decimal),
+    Recall(Decimal),
+    Sync,
+}
+
 #[derive(ScryptoSbor, Clone)]
 pub struct PriceInfo {
     pub timestamp: i64,
     pub price: Decimal,
+    /// Price uncertainty in both directions, when provided by the price feed
+    pub confidence: Option<Decimal>,
+}
+
+impl PriceInfo {
+    /// Confidence as a share of the price, zero when not provided
+    pub fn get_relative_confidence(&self) -> Decimal {
+        match self.confidence {
+            Some(confidence) if self.price > Decimal::ZERO => confidence / self.price,
+            _ => Decimal::ZERO,
+        }
+    }
+}
+
+/// Price info returned by price feeds without a confidence interval
+#[derive(ScryptoSbor)]
+struct PriceInfoWithoutConfidence {
+    timestamp: i64,
+    price: Decimal,
+}
+
+/// Decode a price feed output, feeds may return price infos with or without a confidence interval
+pub fn decode_price_info(output: &ScryptoValue) -> Result<Option<PriceInfo>, String> {
+    let bytes = scrypto_encode(output).map_err(|_| "Invalid price info".to_string())?;
+
+    if let Ok(price_info) = scrypto_decode::<Option<PriceInfo>>(&bytes) {
+        return Ok(price_info);
+    }
+
+    scrypto_decode::<Option<PriceInfoWithoutConfidence>>(&bytes)
+        .map(|price_info| {
+            price_info.map(|price_info| PriceInfo {
+                timestamp: price_info.timestamp,
+                price: price_info.price,
+                confidence: None,
+            })
+        })
+        .map_err(|_| "Invalid price info".to_string())
 }
 
 pub fn get_price(
     price_feed: Global<AnyComponent>,
     res_address: ResourceAddress,
+    base_currency: ResourceAddress,
 ) -> Result<PriceInfo, String> {
-    // Bypass price feed for XRD, return 1 as XRD is the base currency
-    if res_address == XRD {
+    // Bypass price feed for the base currency
+    if res_address == base_currency {
         return Ok(PriceInfo {
             timestamp: Clock::current_time(TimePrecision::Minute).seconds_since_unix_epoch,
             price: dec!(1),
+            confidence: None,
         });
     }
 
-    match price_feed.call_raw::<Option<PriceInfo>>("get_price", scrypto_args!(res_address)) {
+    let output = price_feed.call_raw::<ScryptoValue>("get_price", scrypto_args!(res_address));
+
+    match decode_price_info(&output)? {
         Some(price_info) => Ok(price_info),
         None => Err("Price not found".to_string()),
     }
 }
+
+pub fn get_twap(
+    price_feed: Global<AnyComponent>,
+    res_address: ResourceAddress,
+    base_currency: ResourceAddress,
+    window_minutes: i64,
+) -> Result<PriceInfo, String> {
+    if res_address == base_currency {
+        return get_price(price_feed, res_address, base_currency);
+    }
+
+    let output =
+        price_feed.call_raw::<ScryptoValue>("get_twap", scrypto_args!(res_address, window_minutes));
+
+    match decode_price_info(&output)? {
+        Some(price_info) => Ok(price_info),
+        None => Err("TWAP not found".to_string()),
+    }
+}
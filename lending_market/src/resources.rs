@@ -8,6 +8,7 @@ pub struct AdminBadgeData {}
 pub struct BatchFlashloanItem {
     pub loan_amount: Decimal,
     pub fee_amount: Decimal,
+    pub fee_discount_rate: Decimal,
     pub paid_back: bool,
 }
 
@@ -22,6 +23,14 @@ pub enum TransientResDataType {
     LiquidationTerm(LiquidationTerm),
 }
 
+/// Ticket representing pool units queued for redemption
+#[derive(ScryptoSbor, NonFungibleData)]
+pub struct RedemptionTicketData {
+    pub pool_res_address: ResourceAddress,
+    pub request_index: u64,
+    pub units: Decimal,
+}
+
 #[derive(ScryptoSbor, NonFungibleData)]
 pub struct TransientResData {
     pub data: TransientResDataType,
@@ -120,3 +129,27 @@ pub fn create_transient_res_manager(
         })
         .create_with_no_initial_supply()
 }
+
+pub fn create_redemption_ticket_res_manager(
+    owner_rule: AccessRule,
+    component_rule: AccessRule,
+) -> ResourceManager {
+    ResourceBuilder::new_integer_non_fungible::<RedemptionTicketData>(OwnerRole::None)
+        .metadata(metadata!(
+            roles {
+                metadata_setter => owner_rule.clone();
+                metadata_setter_updater => owner_rule.clone();
+                metadata_locker => owner_rule.clone();
+                metadata_locker_updater => owner_rule;
+            }
+        ))
+        .mint_roles(mint_roles! {
+            minter => component_rule.clone();
+            minter_updater => rule!(deny_all);
+        })
+        .burn_roles(burn_roles! {
+            burner => component_rule;
+            burner_updater => rule!(deny_all);
+        })
+        .create_with_no_initial_supply()
+}
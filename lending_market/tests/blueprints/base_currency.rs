@@ -0,0 +1,129 @@
+use crate::helpers::{
+    init::TestHelper, market::MarketTestHelper, methods::*, price_feed::PriceFeedTestHelper,
+};
+use lending_market::modules::price_source::*;
+use radix_engine_interface::prelude::*;
+use std::path::Path;
+use transaction::builder::ManifestBuilder;
+
+// Replace the helper market with a market quoting prices in USDC
+fn setup_usd_base_market(helper: &mut TestHelper) {
+    let usd = helper.faucet.usdc_resource_address;
+
+    helper.market = MarketTestHelper::new_with_base_currency(
+        &mut helper.test_runner,
+        helper.owner_account_address,
+        helper.owner_public_key,
+        &helper.price_feed,
+        &helper.faucet,
+        Some(usd),
+    );
+}
+
+#[test]
+fn test_xrd_priced_through_feed() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+
+    // The XRD price from the feed is ignored with the default XRD base
+    admin_update_price(&mut helper, 1u64, XRD, dec!("0.04")).expect_commit_success();
+
+    assert_eq!(market_update_pool_price(&mut helper, XRD).price, dec!(1));
+    assert_eq!(market_update_pool_price(&mut helper, usd).price, dec!(25));
+
+    setup_usd_base_market(&mut helper);
+
+    // XRD is priced through the feed and the base currency is worth 1
+    assert_eq!(
+        market_update_pool_price(&mut helper, XRD).price,
+        dec!("0.04")
+    );
+    assert_eq!(market_update_pool_price(&mut helper, usd).price, dec!(1));
+}
+
+#[test]
+fn test_lsu_price_in_base_currency() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+
+    setup_usd_base_market(&mut helper);
+
+    admin_update_price(&mut helper, 1u64, XRD, dec!("0.04")).expect_commit_success();
+
+    let validator = helper
+        .test_runner
+        .new_staked_validator_with_pub_key(helper.owner_public_key, helper.owner_account_address);
+
+    // The price source does not depend on the pool resource, it is checked through the USDC pool
+    market_update_price_source(&mut helper, usd, PriceSource::Lsu(validator))
+        .expect_commit_success();
+
+    // A stake unit is redeemed for 1 XRD, worth 0.04 USDC
+    assert_eq!(
+        market_update_pool_price(&mut helper, usd).price,
+        dec!("0.04")
+    );
+}
+
+#[test]
+fn test_cross_rate_through_intermediate_resource() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+
+    setup_usd_base_market(&mut helper);
+
+    admin_update_price(&mut helper, 1u64, XRD, dec!("0.04")).expect_commit_success();
+
+    // The pair feed quotes USDC in XRD, the intermediate resource differs from the USDC base currency
+    let pair_feed = PriceFeedTestHelper::new(
+        &mut helper.test_runner,
+        helper.owner_account_address,
+        helper.owner_public_key,
+    );
+
+    price_feed_admin_update_price(&mut helper, &pair_feed, usd, dec!(25)).expect_commit_success();
+
+    market_update_price_source(
+        &mut helper,
+        usd,
+        PriceSource::CrossRate {
+            pair_feed: pair_feed.price_feed_component_address,
+            intermediate_res_address: XRD,
+        },
+    )
+    .expect_commit_success();
+
+    // 25 XRD per USDC at 0.04 USDC per XRD
+    assert_eq!(market_update_pool_price(&mut helper, usd).price, dec!(1));
+
+    price_feed_admin_update_price(&mut helper, &pair_feed, usd, dec!(30)).expect_commit_success();
+
+    assert_eq!(
+        market_update_pool_price(&mut helper, usd).price,
+        dec!("1.2")
+    );
+}
+
+#[test]
+fn test_non_fungible_base_currency() {
+    let mut helper = TestHelper::new();
+
+    let non_fungible = helper
+        .test_runner
+        .create_non_fungible_resource(helper.owner_account_address);
+
+    let market_package_address = helper.test_runner.compile_and_publish(Path::new("."));
+
+    let manifest_builder = ManifestBuilder::new().lock_fee_from_faucet().call_function(
+        market_package_address,
+        "LendingMarket",
+        "instantiate",
+        manifest_args!((10u8, Some(non_fungible))),
+    );
+
+    execute_owner_manifest(&mut helper, manifest_builder, "instantiate_market")
+        .expect_commit_failure();
+}
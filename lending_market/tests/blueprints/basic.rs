@@ -1,4 +1,5 @@
 use crate::helpers::{init::TestHelper, methods::*};
+use lending_market::modules::utils::*;
 use radix_engine_interface::{blueprints::consensus_manager::TimePrecision, prelude::*};
 use scrypto_unit::*;
 use std::path::Path;
@@ -167,6 +168,75 @@ fn test_deposit_withdraw_borrow_repay() {
     .expect_commit_success();
 }
 
+#[test]
+fn test_remove_collateral_redemption_fee() {
+    let mut helper = TestHelper::new();
+
+    let (user_key, _, user_account) = helper.test_runner.new_allocated_account();
+
+    market_create_cdp(
+        &mut helper,
+        user_key,
+        user_account,
+        vec![(XRD, dec!(1_000))],
+    )
+    .expect_commit_success();
+
+    market_update_pool_fee_config(
+        &mut helper,
+        XRD,
+        PoolFeeConfig {
+            contribution_fee_rate: dec!(0),
+            redemption_fee_rate: dec!("0.1"),
+            fee_destination: PoolFeeDestination::FeeVault,
+        },
+    )
+    .expect_commit_success();
+
+    market_update_pool_min_amounts(&mut helper, XRD, dec!(0), dec!(200)).expect_commit_success();
+
+    // Withdrawn collateral is redeemed like a direct redemption
+    market_remove_collateral(
+        &mut helper,
+        user_key,
+        user_account,
+        1u64,
+        XRD,
+        dec!(100),
+        false,
+    )
+    .expect_commit_failure();
+
+    let xrd_balance = helper.test_runner.get_component_balance(user_account, XRD);
+
+    market_remove_collateral(
+        &mut helper,
+        user_key,
+        user_account,
+        1u64,
+        XRD,
+        dec!(200),
+        false,
+    )
+    .expect_commit_success();
+
+    // The pool virtual amounts keep the unit ratio slightly off 1
+    let redeemed_amount = helper.test_runner.get_component_balance(user_account, XRD) - xrd_balance;
+    assert!(redeemed_amount > dec!("179.999") && redeemed_amount <= dec!(180));
+
+    // Pool units kept as deposit units are not redeemed
+    market_remove_collateral(
+        &mut helper,
+        user_key,
+        user_account,
+        1u64,
+        XRD,
+        dec!(100),
+        true,
+    )
+    .expect_commit_success();
+}
+
 #[test]
 fn test_create_pool_package_address() {
     let mut test_runner = TestRunnerBuilder::new().build();
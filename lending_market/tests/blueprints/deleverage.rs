@@ -0,0 +1,141 @@
+use crate::helpers::{init::TestHelper, methods::*};
+use radix_engine_interface::prelude::*;
+
+// LP provides 800 USDC, borrower locks 15000 XRD and borrows 300 USDC (LTV ~0.71)
+fn setup_borrower(helper: &mut TestHelper) -> (Secp256k1PublicKey, ComponentAddress) {
+    let usd = helper.faucet.usdc_resource_address;
+
+    let (lp_user_key, _, lp_user_account) = helper.test_runner.new_allocated_account();
+    helper.test_runner.load_account_from_faucet(lp_user_account);
+    helper.test_runner.load_account_from_faucet(lp_user_account);
+    get_resource(helper, lp_user_key, lp_user_account, dec!(25_000)) //
+        .expect_commit_success();
+
+    market_contribute(helper, lp_user_key, lp_user_account, usd, dec!(800)).expect_commit_success();
+
+    let (borrower_key, _, borrower_account) = helper.test_runner.new_allocated_account();
+    helper
+        .test_runner
+        .load_account_from_faucet(borrower_account);
+
+    market_create_cdp(
+        helper,
+        borrower_key,
+        borrower_account,
+        vec![(XRD, dec!(15_000))],
+    )
+    .expect_commit_success();
+
+    market_borrow(helper, borrower_key, borrower_account, 1u64, usd, dec!(300))
+        .expect_commit_success();
+
+    (borrower_key, borrower_account)
+}
+
+#[test]
+fn test_execute_deleverage_order() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+
+    let (borrower_key, borrower_account) = setup_borrower(&mut helper);
+
+    market_create_deleverage_order(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        dec!("0.5"),
+        usd,
+        dec!(100),
+        XRD,
+        dec!("0.05"),
+        dec!("0.01"),
+    )
+    .expect_commit_success();
+
+    let (keeper_key, _, keeper_account) = helper.test_runner.new_allocated_account();
+
+    // The faucet swaps at the feed price, the order sells 100 * 25 * 1.05 XRD for 105 USDC
+    market_execute_deleverage_order(&mut helper, keeper_key, keeper_account, 1u64)
+        .expect_commit_success();
+
+    assert_eq!(
+        helper
+            .test_runner
+            .get_component_balance(keeper_account, usd),
+        dec!("1.05")
+    );
+
+    // Executed orders are removed
+    market_execute_deleverage_order(&mut helper, keeper_key, keeper_account, 1u64)
+        .expect_commit_failure();
+
+    // The loan is repaid, borrowing the repaid amount again is possible
+    market_borrow(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(100),
+    )
+    .expect_commit_success();
+}
+
+#[test]
+fn test_deleverage_order_below_trigger() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+
+    let (borrower_key, borrower_account) = setup_borrower(&mut helper);
+
+    market_create_deleverage_order(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        dec!("0.9"),
+        usd,
+        dec!(100),
+        XRD,
+        dec!("0.05"),
+        dec!("0.01"),
+    )
+    .expect_commit_success();
+
+    let (keeper_key, _, keeper_account) = helper.test_runner.new_allocated_account();
+
+    market_execute_deleverage_order(&mut helper, keeper_key, keeper_account, 1u64)
+        .expect_commit_failure();
+}
+
+#[test]
+fn test_deleverage_order_can_not_increase_ltv() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+
+    let (borrower_key, borrower_account) = setup_borrower(&mut helper);
+
+    // The whole swap proceeds would go to the keeper without repaying the loan
+    market_create_deleverage_order(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        dec!("0.5"),
+        usd,
+        dec!(100),
+        XRD,
+        dec!("0.05"),
+        dec!("1"),
+    )
+    .expect_commit_success();
+
+    let (keeper_key, _, keeper_account) = helper.test_runner.new_allocated_account();
+
+    market_execute_deleverage_order(&mut helper, keeper_key, keeper_account, 1u64)
+        .expect_commit_failure();
+}
@@ -0,0 +1,309 @@
+use crate::helpers::{init::TestHelper, methods::*};
+use lending_market::modules::pool_config::*;
+use radix_engine_interface::prelude::*;
+
+const T0: i64 = 1_700_000_000_000;
+const MINUTE: i64 = 60 * 1000;
+
+// 30 days in minutes
+const DURATION: i64 = 60 * 24 * 30;
+
+// Fixed-term loans enabled on the USDC pool, LP provides 800 USDC and borrower locks 15000 XRD
+fn setup_borrower(helper: &mut TestHelper) -> (Secp256k1PublicKey, ComponentAddress) {
+    let usd = helper.faucet.usdc_resource_address;
+
+    advance_time(helper, 1, T0);
+
+    market_update_pool_config(
+        helper,
+        usd,
+        UpdatePoolConfigInput::FixedTermLoan(Some(FixedTermLoanConfig {
+            rate_premium: dec!("0.02"),
+            max_duration: 60 * 24 * 365,
+        })),
+    )
+    .expect_commit_success();
+
+    let (lp_user_key, _, lp_user_account) = helper.test_runner.new_allocated_account();
+    helper.test_runner.load_account_from_faucet(lp_user_account);
+    helper.test_runner.load_account_from_faucet(lp_user_account);
+    get_resource(helper, lp_user_key, lp_user_account, dec!(25_000)) //
+        .expect_commit_success();
+
+    market_contribute(helper, lp_user_key, lp_user_account, usd, dec!(800)).expect_commit_success();
+
+    let (borrower_key, _, borrower_account) = helper.test_runner.new_allocated_account();
+    helper
+        .test_runner
+        .load_account_from_faucet(borrower_account);
+
+    market_create_cdp(
+        helper,
+        borrower_key,
+        borrower_account,
+        vec![(XRD, dec!(15_000))],
+    )
+    .expect_commit_success();
+
+    (borrower_key, borrower_account)
+}
+
+#[test]
+fn test_borrow_fixed_term() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+
+    let (borrower_key, borrower_account) = setup_borrower(&mut helper);
+
+    // Duration must be in (0, max_duration]
+    market_borrow_fixed_term(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(100),
+        0,
+    )
+    .expect_commit_failure();
+
+    market_borrow_fixed_term(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(100),
+        60 * 24 * 365 + 1,
+    )
+    .expect_commit_failure();
+
+    market_borrow_fixed_term(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(100),
+        DURATION,
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        helper
+            .test_runner
+            .get_component_balance(borrower_account, usd),
+        dec!(100)
+    );
+
+    // An additional borrow can not end after the maturity of the existing loan
+    market_borrow_fixed_term(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(50),
+        DURATION + 1,
+    )
+    .expect_commit_failure();
+
+    advance_time(&mut helper, 2, T0 + 10 * MINUTE);
+
+    market_borrow_fixed_term(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(50),
+        DURATION - 9,
+    )
+    .expect_commit_failure();
+
+    // A shorter additional borrow is locked to the maturity of the existing loan
+    market_borrow_fixed_term(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(50),
+        DURATION - 100,
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        helper
+            .test_runner
+            .get_component_balance(borrower_account, usd),
+        dec!(150)
+    );
+
+    // The CDP health is checked
+    market_borrow_fixed_term(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(300),
+        DURATION - 10,
+    )
+    .expect_commit_failure();
+
+    // The loan matures at the first borrow maturity, not at the shorter one
+    let (keeper_key, _, keeper_account) = helper.test_runner.new_allocated_account();
+
+    advance_time(&mut helper, 3, T0 + (DURATION - 1) * MINUTE);
+
+    market_convert_fixed_term_loans(&mut helper, keeper_key, keeper_account, 1u64)
+        .expect_commit_failure();
+
+    advance_time(&mut helper, 4, T0 + DURATION * MINUTE);
+
+    market_convert_fixed_term_loans(&mut helper, keeper_key, keeper_account, 1u64)
+        .expect_commit_success();
+}
+
+#[test]
+fn test_repay_fixed_term() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+
+    let (borrower_key, borrower_account) = setup_borrower(&mut helper);
+
+    market_borrow_fixed_term(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(100),
+        DURATION,
+    )
+    .expect_commit_success();
+
+    // Get some USDC to pay the interest
+    get_resource(&mut helper, borrower_key, borrower_account, dec!(250)).expect_commit_success();
+
+    advance_time(&mut helper, 2, T0 + (DURATION / 2) * MINUTE);
+
+    // Early repayment pays the interest accrued so far, interest first
+    market_repay_fixed_term(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(100),
+    )
+    .expect_commit_success();
+
+    let balance_before_repay = helper
+        .test_runner
+        .get_component_balance(borrower_account, usd);
+
+    market_repay_fixed_term(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(5),
+    )
+    .expect_commit_success();
+
+    // Less than 1 USDC of interest accrues in 15 days, the unused payment is returned
+    let repaid_amount = balance_before_repay
+        - helper
+            .test_runner
+            .get_component_balance(borrower_account, usd);
+
+    assert!(repaid_amount > dec!(0) && repaid_amount < dec!(1));
+
+    // The loan is closed
+    market_repay_fixed_term(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(1),
+    )
+    .expect_commit_failure();
+}
+
+#[test]
+fn test_fixed_term_loan_conversion() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+
+    let (borrower_key, borrower_account) = setup_borrower(&mut helper);
+
+    market_borrow_fixed_term(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(100),
+        DURATION,
+    )
+    .expect_commit_success();
+
+    get_resource(&mut helper, borrower_key, borrower_account, dec!(250)).expect_commit_success();
+
+    let (keeper_key, _, keeper_account) = helper.test_runner.new_allocated_account();
+
+    // Not matured yet
+    advance_time(&mut helper, 2, T0 + (DURATION - 1) * MINUTE);
+
+    market_convert_fixed_term_loans(&mut helper, keeper_key, keeper_account, 1u64)
+        .expect_commit_failure();
+
+    // Anyone can convert a matured loan
+    advance_time(&mut helper, 3, T0 + DURATION * MINUTE);
+
+    market_convert_fixed_term_loans(&mut helper, keeper_key, keeper_account, 1u64)
+        .expect_commit_success();
+
+    market_convert_fixed_term_loans(&mut helper, keeper_key, keeper_account, 1u64)
+        .expect_commit_failure();
+
+    // The loan is now a variable rate loan
+    market_repay_fixed_term(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(1),
+    )
+    .expect_commit_failure();
+
+    let balance_before_repay = helper
+        .test_runner
+        .get_component_balance(borrower_account, usd);
+
+    market_repay(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(105),
+    )
+    .expect_commit_success();
+
+    // The whole loan with the interest accrued until maturity is repaid
+    let repaid_amount = balance_before_repay
+        - helper
+            .test_runner
+            .get_component_balance(borrower_account, usd);
+
+    assert!(repaid_amount > dec!(100) && repaid_amount < dec!(102));
+}
@@ -1,4 +1,6 @@
 use crate::helpers::{init::TestHelper, methods::*};
+use lending_market::modules::pool_config::{FlashloanLimit, UpdatePoolConfigInput};
+use lending_market::modules::pool_state::{FlashloanRepaidEvent, FlashloanTakenEvent};
 use radix_engine_interface::prelude::*;
 use transaction::builder::ManifestBuilder;
 
@@ -45,7 +47,7 @@ pub fn test_invalid_flash_loan() {
         .call_method(
             helper.market.market_component_address,
             "take_batch_flashloan",
-            manifest_args!(loan_amounts),
+            manifest_args!(loan_amounts, None::<ManifestProof>),
         )
         .call_method(lp_user_account, "withdraw", manifest_args!(XRD, dec!(1100)))
         .call_method(lp_user_account, "withdraw", manifest_args!(usd, dec!(110)))
@@ -126,7 +128,7 @@ pub fn test_valid_flash_loan() {
         .call_method(
             helper.market.market_component_address,
             "take_batch_flashloan",
-            manifest_args!(loan_amounts),
+            manifest_args!(loan_amounts, None::<ManifestProof>),
         )
         .call_method(lp_user_account, "withdraw", manifest_args!(XRD, dec!(1100)))
         .call_method(lp_user_account, "withdraw", manifest_args!(usd, dec!(110)))
@@ -169,3 +171,342 @@ pub fn test_valid_flash_loan() {
         )
         .expect_commit_success();
 }
+
+// Take an XRD flashloan and repay it with the loan plus `fee_amount` from the account
+fn xrd_flashloan_manifest(
+    helper: &TestHelper,
+    account: ComponentAddress,
+    loan_amount: Decimal,
+    fee_amount: Decimal,
+    badge: Option<ResourceAddress>,
+) -> ManifestBuilder {
+    let mut loan_amounts: IndexMap<ResourceAddress, Decimal> = IndexMap::new();
+    loan_amounts.insert(XRD, loan_amount);
+
+    let mut manifest_builder = ManifestBuilder::new().lock_fee_from_faucet();
+
+    if let Some(badge) = badge {
+        manifest_builder = manifest_builder
+            .create_proof_from_account_of_amount(account, badge, dec!(1))
+            .pop_from_auth_zone("badge_proof");
+    }
+
+    manifest_builder
+        .with_name_lookup(|builder, lookup| {
+            let badge_proof = badge.map(|_| lookup.proof("badge_proof"));
+
+            builder.call_method(
+                helper.market.market_component_address,
+                "take_batch_flashloan",
+                manifest_args!(loan_amounts.clone(), badge_proof),
+            )
+        })
+        .call_method(account, "withdraw", manifest_args!(XRD, fee_amount))
+        .take_all_from_worktop(XRD, "xrd_bucket")
+        .take_from_worktop(
+            helper.market.batch_flashloan_resource_address,
+            Decimal::from(1),
+            "flash_loan_term_bucket",
+        )
+        .with_name_lookup(|builder, lookup| {
+            let flash_loan_term_bucket = lookup.bucket("flash_loan_term_bucket");
+            let xrd_bucket = lookup.bucket("xrd_bucket");
+
+            builder
+                .call_method(
+                    helper.market.market_component_address,
+                    "repay_batch_flashloan",
+                    manifest_args!(vec![xrd_bucket], flash_loan_term_bucket),
+                )
+                .deposit_batch(account)
+        })
+}
+
+#[test]
+pub fn test_flashloan_fee_tiers() {
+    let mut helper = TestHelper::new();
+
+    let (lp_user_key, _, lp_user_account) = helper.test_runner.new_allocated_account();
+    helper.test_runner.load_account_from_faucet(lp_user_account);
+
+    market_contribute(&mut helper, lp_user_key, lp_user_account, XRD, dec!(5_000))
+        .expect_commit_success();
+
+    // XRD pool flashloan fee rate is 0.001, 1 XRD for a 1000 XRD loan
+    let (user_key, _, user_account) = helper.test_runner.new_allocated_account();
+
+    let badge = helper
+        .test_runner
+        .create_fungible_resource(dec!(1), 0, user_account);
+
+    let manifest_builder = market_admin_manifest_builder(&helper).call_method(
+        helper.market.market_component_address,
+        "update_flashloan_fee_tier",
+        manifest_args!(badge, Some(dec!("0.5"))),
+    );
+
+    execute_owner_manifest(&mut helper, manifest_builder, "update_flashloan_fee_tier")
+        .expect_commit_success();
+
+    let execute = |helper: &mut TestHelper, manifest_builder: ManifestBuilder| {
+        helper.test_runner.execute_manifest(
+            manifest_builder.build(),
+            vec![NonFungibleGlobalId::from_public_key(&user_key)],
+        )
+    };
+
+    // Without a badge proof the default fee is due
+    let manifest_builder =
+        xrd_flashloan_manifest(&helper, user_account, dec!(1000), dec!("0.5"), None);
+    execute(&mut helper, manifest_builder).expect_commit_failure();
+
+    let manifest_builder = xrd_flashloan_manifest(&helper, user_account, dec!(1000), dec!(1), None);
+    execute(&mut helper, manifest_builder).expect_commit_success();
+
+    // The badge tier halves the fee
+    let manifest_builder =
+        xrd_flashloan_manifest(&helper, user_account, dec!(1000), dec!("0.5"), Some(badge));
+    execute(&mut helper, manifest_builder).expect_commit_success();
+
+    // Badges without a tier are rejected
+    let unknown_badge = helper
+        .test_runner
+        .create_fungible_resource(dec!(1), 0, user_account);
+
+    let manifest_builder = xrd_flashloan_manifest(
+        &helper,
+        user_account,
+        dec!(1000),
+        dec!(1),
+        Some(unknown_badge),
+    );
+    execute(&mut helper, manifest_builder).expect_commit_failure();
+}
+
+fn get_flashloan_stats(
+    helper: &mut TestHelper,
+    res_address: ResourceAddress,
+) -> (Decimal, Decimal, Decimal) {
+    let manifest_builder = ManifestBuilder::new().lock_fee_from_faucet().call_method(
+        helper.market.market_component_address,
+        "get_flashloan_stats",
+        manifest_args!(res_address),
+    );
+
+    execute_owner_manifest(helper, manifest_builder, "get_flashloan_stats")
+        .expect_commit_success()
+        .output::<(Decimal, Decimal, Decimal)>(1)
+}
+
+#[test]
+pub fn test_flashloan_events_and_stats() {
+    let mut helper = TestHelper::new();
+
+    let (lp_user_key, _, lp_user_account) = helper.test_runner.new_allocated_account();
+    helper.test_runner.load_account_from_faucet(lp_user_account);
+
+    market_contribute(&mut helper, lp_user_key, lp_user_account, XRD, dec!(5_000))
+        .expect_commit_success();
+
+    assert_eq!(
+        get_flashloan_stats(&mut helper, XRD),
+        (dec!(0), dec!(0), dec!(0))
+    );
+
+    // XRD pool flashloan fee rate is 0.001 with a 0.15 protocol share:
+    // 1 XRD fee for a 1000 XRD loan, 0.15 XRD to the reserve and 0.85 XRD to the lenders
+    let (user_key, _, user_account) = helper.test_runner.new_allocated_account();
+
+    let manifest_builder = xrd_flashloan_manifest(&helper, user_account, dec!(1000), dec!(1), None);
+
+    let receipt = helper.test_runner.execute_manifest(
+        manifest_builder.build(),
+        vec![NonFungibleGlobalId::from_public_key(&user_key)],
+    );
+
+    let application_events = &receipt.expect_commit_success().application_events;
+
+    let event_data = |event_name: &str| {
+        let matching_events: Vec<_> = application_events
+            .iter()
+            .filter(|(event_type_identifier, _)| {
+                helper.test_runner.event_name(event_type_identifier) == event_name
+            })
+            .map(|(_, event_data)| event_data.clone())
+            .collect();
+
+        assert_eq!(matching_events.len(), 1);
+
+        matching_events[0].clone()
+    };
+
+    let taken_event =
+        scrypto_decode::<FlashloanTakenEvent>(&event_data("FlashloanTakenEvent")).unwrap();
+
+    assert_eq!(taken_event.pool_res_address, XRD);
+    assert_eq!(taken_event.amount, dec!(1000));
+    assert_eq!(taken_event.fee_amount, dec!(1));
+    assert_eq!(taken_event.protocol_fee_amount, dec!("0.15"));
+    assert_eq!(taken_event.lp_fee_amount, dec!("0.85"));
+
+    let repaid_event =
+        scrypto_decode::<FlashloanRepaidEvent>(&event_data("FlashloanRepaidEvent")).unwrap();
+
+    assert_eq!(repaid_event.pool_res_address, XRD);
+    assert_eq!(repaid_event.amount, dec!(1000));
+    assert_eq!(repaid_event.fee_amount, dec!(1));
+    assert_eq!(repaid_event.protocol_fee_amount, dec!("0.15"));
+    assert_eq!(repaid_event.lp_fee_amount, dec!("0.85"));
+
+    assert_eq!(
+        get_flashloan_stats(&mut helper, XRD),
+        (dec!(1000), dec!("0.85"), dec!("0.15"))
+    );
+
+    // The counters add up over flashloans
+    let manifest_builder = xrd_flashloan_manifest(&helper, user_account, dec!(1000), dec!(1), None);
+
+    helper
+        .test_runner
+        .execute_manifest(
+            manifest_builder.build(),
+            vec![NonFungibleGlobalId::from_public_key(&user_key)],
+        )
+        .expect_commit_success();
+
+    assert_eq!(
+        get_flashloan_stats(&mut helper, XRD),
+        (dec!(2000), dec!("1.7"), dec!("0.3"))
+    );
+}
+
+#[test]
+pub fn test_flashloan_fee_strategy() {
+    let mut helper = TestHelper::new();
+
+    let (lp_user_key, _, lp_user_account) = helper.test_runner.new_allocated_account();
+    helper.test_runner.load_account_from_faucet(lp_user_account);
+
+    market_contribute(&mut helper, lp_user_key, lp_user_account, XRD, dec!(5_000))
+        .expect_commit_success();
+
+    // Fees go to the reserve so the pool liquidity stays at 5000 XRD
+    market_update_pool_config(
+        &mut helper,
+        XRD,
+        UpdatePoolConfigInput::ProtocolFlashloanFeeRate(dec!(1)),
+    )
+    .expect_commit_success();
+
+    // Fee rate of 0.001 plus 0.01 per utilization point up to 40%, then 0.05 per point
+    let manifest_builder = market_admin_manifest_builder(&helper).call_method(
+        helper.market.market_component_address,
+        "update_flashloan_fee_strategy",
+        manifest_args!(Some((
+            dec!("0.001"),
+            vec![(dec!(0), dec!("0.01")), (dec!("0.4"), dec!("0.05"))]
+        ))),
+    );
+
+    execute_owner_manifest(
+        &mut helper,
+        manifest_builder,
+        "update_flashloan_fee_strategy",
+    )
+    .expect_commit_success();
+
+    let (user_key, _, user_account) = helper.test_runner.new_allocated_account();
+
+    let execute = |helper: &mut TestHelper, manifest_builder: ManifestBuilder| {
+        helper.test_runner.execute_manifest(
+            manifest_builder.build(),
+            vec![NonFungibleGlobalId::from_public_key(&user_key)],
+        )
+    };
+
+    // A 1000 XRD loan takes the pool to 20% utilization: 0.001 + 0.2 * 0.01 = 0.003, 3 XRD fee
+    let manifest_builder =
+        xrd_flashloan_manifest(&helper, user_account, dec!(1000), dec!("2.9"), None);
+    execute(&mut helper, manifest_builder).expect_commit_failure();
+
+    let manifest_builder = xrd_flashloan_manifest(&helper, user_account, dec!(1000), dec!(3), None);
+    execute(&mut helper, manifest_builder).expect_commit_success();
+
+    // A 3000 XRD loan takes the pool to 60% utilization, past the kink:
+    // 0.005 + 0.2 * 0.05 = 0.015, 45 XRD fee
+    let manifest_builder =
+        xrd_flashloan_manifest(&helper, user_account, dec!(3000), dec!("44.9"), None);
+    execute(&mut helper, manifest_builder).expect_commit_failure();
+
+    let manifest_builder =
+        xrd_flashloan_manifest(&helper, user_account, dec!(3000), dec!(45), None);
+    execute(&mut helper, manifest_builder).expect_commit_success();
+
+    // Without a fee strategy the flat fee rate applies again
+    let manifest_builder = market_admin_manifest_builder(&helper).call_method(
+        helper.market.market_component_address,
+        "update_flashloan_fee_strategy",
+        manifest_args!(None::<(Decimal, Vec<(Decimal, Decimal)>)>),
+    );
+
+    execute_owner_manifest(
+        &mut helper,
+        manifest_builder,
+        "update_flashloan_fee_strategy",
+    )
+    .expect_commit_success();
+
+    let manifest_builder = xrd_flashloan_manifest(&helper, user_account, dec!(3000), dec!(3), None);
+    execute(&mut helper, manifest_builder).expect_commit_success();
+}
+
+#[test]
+pub fn test_flashloan_limit() {
+    let mut helper = TestHelper::new();
+
+    let (lp_user_key, _, lp_user_account) = helper.test_runner.new_allocated_account();
+    helper.test_runner.load_account_from_faucet(lp_user_account);
+
+    market_contribute(&mut helper, lp_user_key, lp_user_account, XRD, dec!(5_000))
+        .expect_commit_success();
+
+    let (user_key, _, user_account) = helper.test_runner.new_allocated_account();
+
+    let execute = |helper: &mut TestHelper, manifest_builder: ManifestBuilder| {
+        helper.test_runner.execute_manifest(
+            manifest_builder.build(),
+            vec![NonFungibleGlobalId::from_public_key(&user_key)],
+        )
+    };
+
+    // Half of the 5000 XRD available liquidity
+    market_update_pool_config(
+        &mut helper,
+        XRD,
+        UpdatePoolConfigInput::FlashloanLimit(Some(FlashloanLimit::LiquidityRatio(dec!("0.5")))),
+    )
+    .expect_commit_success();
+
+    let manifest_builder =
+        xrd_flashloan_manifest(&helper, user_account, dec!(2600), dec!("2.6"), None);
+    execute(&mut helper, manifest_builder).expect_commit_failure();
+
+    let manifest_builder =
+        xrd_flashloan_manifest(&helper, user_account, dec!(2500), dec!("2.5"), None);
+    execute(&mut helper, manifest_builder).expect_commit_success();
+
+    // Fixed amount limit
+    market_update_pool_config(
+        &mut helper,
+        XRD,
+        UpdatePoolConfigInput::FlashloanLimit(Some(FlashloanLimit::Amount(dec!(1000)))),
+    )
+    .expect_commit_success();
+
+    let manifest_builder =
+        xrd_flashloan_manifest(&helper, user_account, dec!(1001), dec!("1.001"), None);
+    execute(&mut helper, manifest_builder).expect_commit_failure();
+
+    let manifest_builder = xrd_flashloan_manifest(&helper, user_account, dec!(1000), dec!(1), None);
+    execute(&mut helper, manifest_builder).expect_commit_success();
+}
@@ -1,8 +1,11 @@
 use crate::helpers::{
-    faucet::FaucetTestHelper, market::MarketTestHelper, price_feed::PriceFeedTestHelper,
+    faucet::FaucetTestHelper, init::TestHelper, market::MarketTestHelper, methods::*,
+    price_feed::PriceFeedTestHelper,
 };
+use radix_engine_interface::prelude::*;
 use scrypto_unit::*;
 use std::path::Path;
+use transaction::builder::ManifestBuilder;
 
 #[test]
 fn test_instantiate_price_feed() {
@@ -59,3 +62,38 @@ fn test_create_pool_package_address() {
         test_runner.compile_and_publish(Path::new("../single_resource_pool"));
     println!("{:?}\n", _pool_package_address);
 }
+
+#[test]
+fn test_pool_unit_metadata_owner() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+    let usd_pool_unit = helper.market.pools.get(&usd).unwrap().1;
+
+    // The market admin owns the pool unit resource
+    let manifest_builder = market_admin_manifest_builder(&helper).set_metadata(
+        usd_pool_unit,
+        "description",
+        MetadataValue::String("USDC lending pool unit".to_string()),
+    );
+
+    execute_owner_manifest(&mut helper, manifest_builder, "set_pool_unit_metadata")
+        .expect_commit_success();
+
+    assert_eq!(
+        helper
+            .test_runner
+            .get_metadata(usd_pool_unit.into(), "description"),
+        Some(MetadataValue::String("USDC lending pool unit".to_string()))
+    );
+
+    // Without the admin badges the metadata can not be changed
+    let manifest_builder = ManifestBuilder::new().lock_fee_from_faucet().set_metadata(
+        usd_pool_unit,
+        "description",
+        MetadataValue::String("Unauthorized".to_string()),
+    );
+
+    execute_owner_manifest(&mut helper, manifest_builder, "set_pool_unit_metadata")
+        .expect_commit_failure();
+}
@@ -0,0 +1,114 @@
+use crate::helpers::{init::TestHelper, methods::*};
+use lending_market::modules::pool_config::UpdatePoolConfigInput;
+use radix_engine_interface::prelude::*;
+use transaction::builder::ManifestBuilder;
+
+// Clock times in milliseconds, the USDC pool updates its interest every 5 minutes
+const T0: i64 = 1_700_000_040_000;
+const INTEREST_UPDATE_PERIOD: i64 = 5 * 60 * 1000;
+
+fn get_interest_rates(helper: &mut TestHelper, res_address: ResourceAddress) -> (Decimal, Decimal) {
+    let manifest_builder = ManifestBuilder::new().lock_fee_from_faucet().call_method(
+        helper.market.market_component_address,
+        "get_interest_rates",
+        manifest_args!(res_address),
+    );
+
+    execute_owner_manifest(helper, manifest_builder, "get_interest_rates")
+        .expect_commit_success()
+        .output::<(Decimal, Decimal)>(1)
+}
+
+#[test]
+fn test_max_interest_rate_change() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+    let max_interest_rate_change = dec!("0.05");
+
+    advance_time(&mut helper, 1, T0);
+
+    market_update_pool_config(
+        &mut helper,
+        usd,
+        UpdatePoolConfigInput::MaxInterestRateChange(Some(max_interest_rate_change)),
+    )
+    .expect_commit_success();
+
+    // LP provides 800 USDC, borrower locks 30000 XRD and borrows 600 USDC at once
+    let (lp_user_key, _, lp_user_account) = helper.test_runner.new_allocated_account();
+    helper.test_runner.load_account_from_faucet(lp_user_account);
+    helper.test_runner.load_account_from_faucet(lp_user_account);
+    get_resource(&mut helper, lp_user_key, lp_user_account, dec!(25_000)) //
+        .expect_commit_success();
+
+    market_contribute(&mut helper, lp_user_key, lp_user_account, usd, dec!(800))
+        .expect_commit_success();
+
+    let (borrower_key, _, borrower_account) = helper.test_runner.new_allocated_account();
+    for _ in 0..3 {
+        helper
+            .test_runner
+            .load_account_from_faucet(borrower_account);
+    }
+
+    market_create_cdp(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        vec![(XRD, dec!(30_000))],
+    )
+    .expect_commit_success();
+
+    market_borrow(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(600),
+    )
+    .expect_commit_success();
+
+    let (_, applied_rate) = get_interest_rates(&mut helper, usd);
+
+    // Within the same minute the target rate follows the utilization jump but the applied rate can not move
+    market_update_pool_state(&mut helper, usd);
+
+    let (target_rate, new_applied_rate) = get_interest_rates(&mut helper, usd);
+
+    assert_eq!(new_applied_rate, applied_rate);
+    assert!(target_rate > applied_rate + max_interest_rate_change * 3);
+
+    // The applied rate then trails the target rate by one step per update period
+    let mut applied_rate = new_applied_rate;
+
+    for round in 2..5 {
+        advance_time(
+            &mut helper,
+            round,
+            T0 + (round as i64 - 1) * INTEREST_UPDATE_PERIOD,
+        );
+
+        market_update_pool_state(&mut helper, usd);
+
+        let (target_rate, new_applied_rate) = get_interest_rates(&mut helper, usd);
+
+        assert!(target_rate > new_applied_rate);
+        assert_eq!(new_applied_rate, applied_rate + max_interest_rate_change);
+
+        applied_rate = new_applied_rate;
+    }
+
+    // Over two update periods the applied rate can move by two steps
+    advance_time(&mut helper, 5, T0 + 5 * INTEREST_UPDATE_PERIOD);
+
+    market_update_pool_state(&mut helper, usd);
+
+    let (target_rate, new_applied_rate) = get_interest_rates(&mut helper, usd);
+
+    assert_eq!(
+        new_applied_rate,
+        target_rate.min(applied_rate + max_interest_rate_change * 2)
+    );
+}
@@ -1,4 +1,14 @@
+pub mod base_currency;
 pub mod basic;
+pub mod deleverage;
+pub mod fixed_term;
 pub mod flashloan;
 pub mod instantiate;
+pub mod interest_rate;
 pub mod liquidation;
+pub mod pool_indexes;
+pub mod price_confidence;
+pub mod price_fallback;
+pub mod price_source;
+pub mod redemption_queue;
+pub mod stable_rate;
@@ -0,0 +1,137 @@
+use crate::helpers::{init::TestHelper, methods::*};
+use radix_engine_interface::prelude::*;
+use transaction::builder::ManifestBuilder;
+
+// Clock times in milliseconds
+const T0: i64 = 1_700_000_040_000;
+const T0_PLUS_30_DAYS: i64 = T0 + 30 * 24 * 60 * 60 * 1000;
+
+fn get_pool_indexes(
+    helper: &mut TestHelper,
+    res_address: ResourceAddress,
+) -> (PreciseDecimal, PreciseDecimal) {
+    let manifest_builder = ManifestBuilder::new().lock_fee_from_faucet().call_method(
+        helper.market.market_component_address,
+        "get_pool_indexes",
+        manifest_args!(res_address),
+    );
+
+    execute_owner_manifest(helper, manifest_builder, "get_pool_indexes")
+        .expect_commit_success()
+        .output::<(PreciseDecimal, PreciseDecimal)>(1)
+}
+
+fn get_pool_unit_ratio(helper: &mut TestHelper, res_address: ResourceAddress) -> PreciseDecimal {
+    let pool = helper.market.pools.get(&res_address).unwrap().0;
+
+    let manifest_builder = ManifestBuilder::new().lock_fee_from_faucet().call_method(
+        pool,
+        "get_pool_unit_ratio",
+        manifest_args!(),
+    );
+
+    execute_owner_manifest(helper, manifest_builder, "get_pool_unit_ratio")
+        .expect_commit_success()
+        .output::<PreciseDecimal>(1)
+}
+
+#[test]
+fn test_pool_indexes() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+
+    advance_time(&mut helper, 1, T0);
+
+    // LP provides 800 USDC, borrower locks 15000 XRD and borrows 400 USDC
+    let (lp_user_key, _, lp_user_account) = helper.test_runner.new_allocated_account();
+    helper.test_runner.load_account_from_faucet(lp_user_account);
+    helper.test_runner.load_account_from_faucet(lp_user_account);
+    get_resource(&mut helper, lp_user_key, lp_user_account, dec!(25_000)) //
+        .expect_commit_success();
+
+    market_contribute(&mut helper, lp_user_key, lp_user_account, usd, dec!(800))
+        .expect_commit_success();
+
+    let (borrower_key, _, borrower_account) = helper.test_runner.new_allocated_account();
+    helper
+        .test_runner
+        .load_account_from_faucet(borrower_account);
+    helper
+        .test_runner
+        .load_account_from_faucet(borrower_account);
+
+    market_create_cdp(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        vec![(XRD, dec!(15_000))],
+    )
+    .expect_commit_success();
+
+    market_borrow(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(400),
+    )
+    .expect_commit_success();
+
+    // USDC to pay the interest
+    get_resource(&mut helper, borrower_key, borrower_account, dec!(2_500)) //
+        .expect_commit_success();
+
+    let (borrow_index, supply_index) = get_pool_indexes(&mut helper, usd);
+    let unit_ratio = get_pool_unit_ratio(&mut helper, usd);
+
+    // Interest accrues over 30 days
+    advance_time(&mut helper, 2, T0_PLUS_30_DAYS);
+
+    market_update_pool_state(&mut helper, usd);
+
+    let (new_borrow_index, new_supply_index) = get_pool_indexes(&mut helper, usd);
+    let new_unit_ratio = get_pool_unit_ratio(&mut helper, usd);
+
+    assert!(new_borrow_index > borrow_index);
+    assert!(new_supply_index > supply_index);
+
+    // Lenders earn less than borrowers pay on the whole liquidity
+    assert!(new_supply_index / supply_index < new_borrow_index / borrow_index);
+
+    // The supply index follows the pool unit value, the inverse of the unit ratio
+    let supply_growth = new_supply_index / supply_index;
+    let unit_value_growth = unit_ratio / new_unit_ratio;
+
+    assert!((supply_growth - unit_value_growth).checked_abs().unwrap() < pdec!("0.000000000001"));
+
+    // The borrow index follows the loan growth, the whole loan is repaid with its interest
+    let usd_balance = helper
+        .test_runner
+        .get_component_balance(borrower_account, usd);
+
+    market_repay(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        usd_balance,
+    )
+    .expect_commit_success();
+
+    let repaid_amount = usd_balance
+        - helper
+            .test_runner
+            .get_component_balance(borrower_account, usd);
+
+    let expected_repaid_amount = PreciseDecimal::from(dec!(400)) * new_borrow_index / borrow_index;
+
+    assert!(
+        (PreciseDecimal::from(repaid_amount) - expected_repaid_amount)
+            .checked_abs()
+            .unwrap()
+            < pdec!("0.000001")
+    );
+}
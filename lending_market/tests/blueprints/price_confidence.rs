@@ -0,0 +1,190 @@
+use crate::helpers::{init::TestHelper, methods::*};
+use radix_engine_interface::prelude::*;
+use scrypto_test::prelude::*;
+
+// Call a method of the market price feed with its admin badge
+fn price_feed_admin_call(
+    helper: &mut TestHelper,
+    method_name: &str,
+    args: ManifestArgs,
+) -> TransactionReceiptV1 {
+    let manifest_builder = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .create_proof_from_account_of_non_fungible(
+            helper.owner_account_address,
+            NonFungibleGlobalId::new(
+                helper.price_feed.price_feed_admin_badge,
+                NonFungibleLocalId::integer(1),
+            ),
+        )
+        .call_method(
+            helper.price_feed.price_feed_component_address,
+            method_name,
+            args,
+        )
+        .deposit_batch(helper.owner_account_address);
+
+    execute_owner_manifest(helper, manifest_builder, "price_feed_admin_call")
+}
+
+// Submit a price to the market price feed with the given updater badge
+fn price_feed_update_price(
+    helper: &mut TestHelper,
+    badge_id: u64,
+    resource_address: ResourceAddress,
+    price: Decimal,
+) -> TransactionReceiptV1 {
+    let badge_global_id = NonFungibleGlobalId::new(
+        helper.price_feed.price_feed_updater_badge,
+        NonFungibleLocalId::integer(badge_id),
+    );
+
+    let price_feed_component_address = helper.price_feed.price_feed_component_address;
+
+    let manifest_builder = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .create_proof_from_account_of_non_fungible(
+            helper.owner_account_address,
+            badge_global_id.clone(),
+        )
+        .create_proof_from_account_of_non_fungible(helper.owner_account_address, badge_global_id)
+        .pop_from_auth_zone("badge_proof")
+        .with_name_lookup(|builder, lookup| {
+            builder.call_method(
+                price_feed_component_address,
+                "update_price",
+                manifest_args!(lookup.proof("badge_proof"), resource_address, price),
+            )
+        });
+
+    execute_owner_manifest(helper, manifest_builder, "price_feed_update_price")
+}
+
+// Two updaters quote USDC around the midpoint, the confidence is half the spread
+fn set_usd_price_spread(helper: &mut TestHelper, low: Decimal, high: Decimal) {
+    let usd = helper.faucet.usdc_resource_address;
+
+    price_feed_update_price(helper, 1, usd, low).expect_commit_success();
+    price_feed_update_price(helper, 2, usd, high).expect_commit_success();
+
+    let event = market_update_pool_price(helper, usd);
+    assert_eq!(event.price, (low + high) / 2);
+}
+
+#[test]
+fn test_cdp_health_with_price_confidence() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+
+    // LP provides 800 USDC, borrower locks 15000 XRD and borrows 400 USDC at 25 XRD per USDC.
+    // The discounted collateral value is 15000 * 0.7 = 10500 XRD
+    let (lp_user_key, _, lp_user_account) = helper.test_runner.new_allocated_account();
+    helper.test_runner.load_account_from_faucet(lp_user_account);
+    helper.test_runner.load_account_from_faucet(lp_user_account);
+    get_resource(&mut helper, lp_user_key, lp_user_account, dec!(25_000)) //
+        .expect_commit_success();
+
+    market_contribute(&mut helper, lp_user_key, lp_user_account, usd, dec!(800))
+        .expect_commit_success();
+
+    let (borrower_key, _, borrower_account) = helper.test_runner.new_allocated_account();
+    helper
+        .test_runner
+        .load_account_from_faucet(borrower_account);
+
+    market_create_cdp(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        vec![(XRD, dec!(15_000))],
+    )
+    .expect_commit_success();
+
+    let cdp_id: u64 = 1;
+
+    market_borrow(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        cdp_id,
+        usd,
+        dec!(400),
+    )
+    .expect_commit_success();
+
+    // Liquidator gets USDC before the admin price is replaced by the updater prices
+    let (liquidator_user_key, _, liquidator_user_account) =
+        helper.test_runner.new_allocated_account();
+
+    let xrd_balance = helper
+        .test_runner
+        .get_component_balance(liquidator_user_account, XRD);
+
+    swap(
+        &mut helper,
+        liquidator_user_account,
+        liquidator_user_key,
+        xrd_balance,
+        XRD,
+        usd,
+    )
+    .expect_commit_success();
+
+    let usd_balance = helper
+        .test_runner
+        .get_component_balance(liquidator_user_account, usd);
+
+    price_feed_admin_call(
+        &mut helper,
+        "mint_updater_badge",
+        manifest_args!(true, None::<IndexSet<ResourceAddress>>),
+    )
+    .expect_commit_success();
+    price_feed_admin_call(
+        &mut helper,
+        "mint_updater_badge",
+        manifest_args!(true, None::<IndexSet<ResourceAddress>>),
+    )
+    .expect_commit_success();
+    price_feed_admin_call(&mut helper, "remove_admin_price", manifest_args!(usd))
+        .expect_commit_success();
+
+    // USDC at 26 +/- 1 XRD. At the midpoint a 401 USDC loan is worth 10426 XRD,
+    // but the borrow is checked at the upper bound: 401 * 27 = 10827 XRD
+    set_usd_price_spread(&mut helper, dec!(25), dec!(27));
+
+    market_borrow(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        cdp_id,
+        usd,
+        dec!(1),
+    )
+    .expect_commit_failure();
+
+    // The liquidation uses the midpoint: 400 * 26 = 10400 XRD
+    market_fast_liquidation(
+        &mut helper,
+        liquidator_user_key,
+        liquidator_user_account,
+        cdp_id,
+        vec![(usd, usd_balance)],
+        vec![XRD],
+    )
+    .expect_commit_failure();
+
+    // USDC at 28 +/- 1 XRD, the midpoint loan value is 400 * 28 = 11200 XRD
+    set_usd_price_spread(&mut helper, dec!(27), dec!(29));
+
+    market_fast_liquidation(
+        &mut helper,
+        liquidator_user_key,
+        liquidator_user_account,
+        cdp_id,
+        vec![(usd, usd_balance)],
+        vec![XRD],
+    )
+    .expect_commit_success();
+}
@@ -0,0 +1,190 @@
+use crate::helpers::{init::TestHelper, methods::*, price_feed::PriceFeedTestHelper};
+use lending_market::modules::pool_state::PriceFeedSource;
+use radix_engine_interface::prelude::*;
+
+// Clock times in milliseconds, prices expire after 240 minutes
+const T0: i64 = 1_700_000_040_000;
+const T0_PLUS_5_HOURS: i64 = T0 + 5 * 60 * 60 * 1000;
+
+#[test]
+fn test_fallback_price_feed() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+
+    let fallback_price_feed = PriceFeedTestHelper::new(
+        &mut helper.test_runner,
+        helper.owner_account_address,
+        helper.owner_public_key,
+    );
+
+    advance_time(&mut helper, 1, T0);
+
+    // The fallback feed must price the pool resource
+    let manifest_builder = market_admin_manifest_builder(&helper).call_method(
+        helper.market.market_component_address,
+        "update_fallback_price_feed",
+        manifest_args!(usd, Some(fallback_price_feed.price_feed_component_address)),
+    );
+
+    execute_owner_manifest(&mut helper, manifest_builder, "update_fallback_price_feed")
+        .expect_commit_failure();
+
+    price_feed_admin_update_price(&mut helper, &fallback_price_feed, usd, dec!(26))
+        .expect_commit_success();
+
+    let manifest_builder = market_admin_manifest_builder(&helper).call_method(
+        helper.market.market_component_address,
+        "update_fallback_price_feed",
+        manifest_args!(usd, Some(fallback_price_feed.price_feed_component_address)),
+    );
+
+    execute_owner_manifest(&mut helper, manifest_builder, "update_fallback_price_feed")
+        .expect_commit_success();
+
+    let event = market_update_pool_price(&mut helper, usd);
+    assert_eq!(event.source, PriceFeedSource::Primary);
+    assert_eq!(event.price, dec!(25));
+
+    // The primary price is too old, the fresh fallback price is used
+    helper
+        .test_runner
+        .advance_to_round_at_timestamp(Round::of(2), T0_PLUS_5_HOURS);
+
+    price_feed_admin_update_price(&mut helper, &fallback_price_feed, usd, dec!(26))
+        .expect_commit_success();
+
+    let event = market_update_pool_price(&mut helper, usd);
+    assert_eq!(event.source, PriceFeedSource::Fallback);
+    assert_eq!(event.price, dec!(26));
+}
+
+#[test]
+fn test_operations_with_stale_price() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+
+    advance_time(&mut helper, 1, T0);
+
+    // LP provides 800 USDC, borrower locks 15000 XRD and borrows 419 USDC
+    let (lp_user_key, _, lp_user_account) = helper.test_runner.new_allocated_account();
+    helper.test_runner.load_account_from_faucet(lp_user_account);
+    helper.test_runner.load_account_from_faucet(lp_user_account);
+    get_resource(&mut helper, lp_user_key, lp_user_account, dec!(25_000)) //
+        .expect_commit_success();
+
+    market_contribute(&mut helper, lp_user_key, lp_user_account, usd, dec!(800))
+        .expect_commit_success();
+
+    let (borrower_key, _, borrower_account) = helper.test_runner.new_allocated_account();
+    helper
+        .test_runner
+        .load_account_from_faucet(borrower_account);
+
+    market_create_cdp(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        vec![(XRD, dec!(15_000))],
+    )
+    .expect_commit_success();
+
+    let cdp_id: u64 = 1;
+
+    market_borrow(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        cdp_id,
+        usd,
+        dec!(419),
+    )
+    .expect_commit_success();
+
+    // At 27 XRD per USDC the CDP can be liquidated, but the price expires before the pool sees it
+    admin_update_price(&mut helper, 1u64, usd, dec!(27)).expect_commit_success();
+
+    helper
+        .test_runner
+        .advance_to_round_at_timestamp(Round::of(2), T0_PLUS_5_HOURS);
+
+    let (liquidator_user_key, _, liquidator_user_account) =
+        helper.test_runner.new_allocated_account();
+
+    let xrd_balance = helper
+        .test_runner
+        .get_component_balance(liquidator_user_account, XRD);
+
+    swap(
+        &mut helper,
+        liquidator_user_account,
+        liquidator_user_key,
+        xrd_balance,
+        XRD,
+        usd,
+    )
+    .expect_commit_success();
+
+    let usd_balance = helper
+        .test_runner
+        .get_component_balance(liquidator_user_account, usd);
+
+    // Operations that can lower the CDP health need a fresh price
+    market_fast_liquidation(
+        &mut helper,
+        liquidator_user_key,
+        liquidator_user_account,
+        cdp_id,
+        vec![(usd, usd_balance)],
+        vec![XRD],
+    )
+    .expect_commit_failure();
+
+    market_borrow(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        cdp_id,
+        usd,
+        dec!(1),
+    )
+    .expect_commit_failure();
+
+    // Repay and add collateral proceed on the last known price
+    let receipt = market_repay(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        cdp_id,
+        usd,
+        dec!(10),
+    );
+
+    let event = get_price_feed_used_events(&helper, &receipt).pop().unwrap();
+    assert_eq!(event.source, PriceFeedSource::LastKnown);
+    assert_eq!(event.price, dec!(25));
+
+    market_add_collateral(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        cdp_id,
+        usd,
+        dec!(10),
+    )
+    .expect_commit_success();
+
+    // The liquidation goes through once the price is fresh again
+    admin_update_price(&mut helper, 1u64, usd, dec!(27)).expect_commit_success();
+
+    market_fast_liquidation(
+        &mut helper,
+        liquidator_user_key,
+        liquidator_user_account,
+        cdp_id,
+        vec![(usd, usd_balance)],
+        vec![XRD],
+    )
+    .expect_commit_success();
+}
@@ -0,0 +1,178 @@
+use crate::helpers::{init::TestHelper, methods::*, price_feed::PriceFeedTestHelper};
+use lending_market::modules::{
+    pool_config::*, pool_state::PriceFeedSource, price_source::*, utils::*,
+};
+use radix_engine_interface::prelude::*;
+use scrypto_test::prelude::*;
+use std::path::Path;
+
+// Update the USDC pool state and return the price it took from its price source
+fn get_usd_pool_price(helper: &mut TestHelper) -> Decimal {
+    let usd = helper.faucet.usdc_resource_address;
+
+    let event = market_update_pool_price(helper, usd);
+
+    assert_eq!(event.source, PriceFeedSource::Primary);
+
+    event.price
+}
+
+#[test]
+fn test_inverted_price_source() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+
+    let quote =
+        helper
+            .test_runner
+            .create_fungible_resource(dec!(1), 18, helper.owner_account_address);
+
+    // No price for the quoted resource
+    market_update_price_source(&mut helper, usd, PriceSource::Inverted(quote))
+        .expect_commit_failure();
+
+    admin_update_price(&mut helper, 1u64, quote, dec!("0.04")).expect_commit_success();
+
+    market_update_price_source(&mut helper, usd, PriceSource::Inverted(quote))
+        .expect_commit_success();
+
+    assert_eq!(get_usd_pool_price(&mut helper), dec!(25));
+}
+
+#[test]
+fn test_cross_rate_price_source() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+
+    let intermediate =
+        helper
+            .test_runner
+            .create_fungible_resource(dec!(1), 18, helper.owner_account_address);
+
+    let pair_feed = PriceFeedTestHelper::new(
+        &mut helper.test_runner,
+        helper.owner_account_address,
+        helper.owner_public_key,
+    );
+
+    // The pair feed quotes USDC in the intermediate resource, which the market feed prices in XRD
+    price_feed_admin_update_price(&mut helper, &pair_feed, usd, dec!(50)).expect_commit_success();
+    admin_update_price(&mut helper, 1u64, intermediate, dec!("0.5")).expect_commit_success();
+
+    market_update_price_source(
+        &mut helper,
+        usd,
+        PriceSource::CrossRate {
+            pair_feed: pair_feed.price_feed_component_address,
+            intermediate_res_address: intermediate,
+        },
+    )
+    .expect_commit_success();
+
+    assert_eq!(get_usd_pool_price(&mut helper), dec!(25));
+
+    // The pair feed spot price is used with a TWAP config
+    market_update_pool_config(
+        &mut helper,
+        usd,
+        UpdatePoolConfigInput::Twap(Some(TwapConfig {
+            window: 30,
+            conservative: false,
+        })),
+    )
+    .expect_commit_success();
+
+    price_feed_admin_update_price(&mut helper, &pair_feed, usd, dec!(60)).expect_commit_success();
+
+    assert_eq!(get_usd_pool_price(&mut helper), dec!(30));
+}
+
+#[test]
+fn test_pool_unit_price_source() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+
+    // Standalone XRD pool administrated by the helper owner
+    let pool_package_address = helper
+        .test_runner
+        .compile_and_publish(Path::new("../single_resource_pool"));
+
+    let admin_rule = rule!(require(NonFungibleGlobalId::from_public_key(
+        &helper.owner_public_key
+    )));
+
+    let manifest_builder = ManifestBuilder::new().lock_fee_from_faucet().call_function(
+        pool_package_address,
+        "SingleResourcePool",
+        "instantiate",
+        manifest_args!(
+            XRD,
+            OwnerRole::None,
+            admin_rule,
+            rule!(allow_all),
+            rule!(allow_all),
+            PoolFeeConfig {
+                contribution_fee_rate: dec!(0),
+                redemption_fee_rate: dec!(0),
+                fee_destination: PoolFeeDestination::Pool,
+            },
+            None::<ComponentAddress>
+        ),
+    );
+
+    let pool = execute_owner_manifest(&mut helper, manifest_builder, "instantiate_pool")
+        .expect_commit_success()
+        .new_component_addresses()[0];
+
+    // 100 units for 200 XRD, a unit is worth 2 XRD
+    let manifest_builder = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .withdraw_from_account(helper.owner_account_address, XRD, dec!(100))
+        .take_all_from_worktop(XRD, "xrd")
+        .with_name_lookup(|builder, lookup| {
+            builder.call_method(pool, "contribute", manifest_args!(lookup.bucket("xrd")))
+        })
+        .call_method(
+            pool,
+            "increase_external_liquidity",
+            manifest_args!(dec!(100)),
+        )
+        .deposit_batch(helper.owner_account_address);
+
+    execute_owner_manifest(&mut helper, manifest_builder, "pool_contribute")
+        .expect_commit_success();
+
+    market_update_price_source(
+        &mut helper,
+        usd,
+        PriceSource::PoolUnit {
+            pool,
+            underlying_res_address: XRD,
+        },
+    )
+    .expect_commit_success();
+
+    // The pool virtual amounts keep the unit price slightly below 2
+    let price = get_usd_pool_price(&mut helper);
+    assert!(price > dec!("1.999999") && price <= dec!(2));
+}
+
+#[test]
+fn test_lsu_price_source() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+
+    let validator = helper
+        .test_runner
+        .new_staked_validator_with_pub_key(helper.owner_public_key, helper.owner_account_address);
+
+    market_update_price_source(&mut helper, usd, PriceSource::Lsu(validator))
+        .expect_commit_success();
+
+    // Nothing was earned yet, a stake unit is redeemed for 1 XRD
+    assert_eq!(get_usd_pool_price(&mut helper), dec!(1));
+}
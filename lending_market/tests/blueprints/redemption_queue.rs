@@ -0,0 +1,181 @@
+use crate::helpers::{init::TestHelper, methods::*};
+use lending_market::modules::utils::*;
+use radix_engine_interface::prelude::*;
+
+#[test]
+fn test_redemption_queue() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+    let usd_pool_unit = helper.market.pools.get(&usd).unwrap().1;
+
+    // LP provides 800 USDC, borrower locks 20000 XRD and borrows 500 USDC
+    let (lp_user_key, _, lp_user_account) = helper.test_runner.new_allocated_account();
+    helper.test_runner.load_account_from_faucet(lp_user_account);
+    helper.test_runner.load_account_from_faucet(lp_user_account);
+    get_resource(&mut helper, lp_user_key, lp_user_account, dec!(25_000)) //
+        .expect_commit_success();
+
+    market_contribute(&mut helper, lp_user_key, lp_user_account, usd, dec!(800))
+        .expect_commit_success();
+
+    let (borrower_key, _, borrower_account) = helper.test_runner.new_allocated_account();
+    helper
+        .test_runner
+        .load_account_from_faucet(borrower_account);
+
+    market_create_cdp(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        vec![(XRD, dec!(20_000))],
+    )
+    .expect_commit_success();
+
+    market_borrow(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(500),
+    )
+    .expect_commit_success();
+
+    // Units the pool can redeem right away are not queued
+    market_request_redeem(
+        &mut helper,
+        lp_user_key,
+        lp_user_account,
+        usd_pool_unit,
+        dec!(200),
+    )
+    .expect_commit_failure();
+
+    // The minimum redemption units apply to the whole request
+    market_update_pool_min_amounts(&mut helper, usd, dec!(0), dec!(1_000)).expect_commit_success();
+
+    market_request_redeem(
+        &mut helper,
+        lp_user_key,
+        lp_user_account,
+        usd_pool_unit,
+        dec!(600),
+    )
+    .expect_commit_failure();
+
+    market_update_pool_min_amounts(&mut helper, usd, dec!(0), dec!(500)).expect_commit_success();
+
+    let lp_usd_balance = helper
+        .test_runner
+        .get_component_balance(lp_user_account, usd);
+
+    // Queue 600 pool units, the 300 USDC available are redeemed right away
+    market_request_redeem(
+        &mut helper,
+        lp_user_key,
+        lp_user_account,
+        usd_pool_unit,
+        dec!(600),
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        helper.test_runner.get_component_balance(
+            lp_user_account,
+            helper.market.redemption_ticket_resource_address
+        ),
+        dec!(1)
+    );
+
+    market_claim_redemption(&mut helper, lp_user_key, lp_user_account, 1u64)
+        .expect_commit_success();
+
+    assert_eq!(
+        helper
+            .test_runner
+            .get_component_balance(lp_user_account, usd),
+        lp_usd_balance + dec!(300)
+    );
+
+    // Nothing more to claim until new liquidity comes in
+    market_claim_redemption(&mut helper, lp_user_key, lp_user_account, 1u64)
+        .expect_commit_success();
+
+    assert_eq!(
+        helper
+            .test_runner
+            .get_component_balance(lp_user_account, usd),
+        lp_usd_balance + dec!(300)
+    );
+
+    // Queued units pay the redemption fee
+    market_update_pool_fee_config(
+        &mut helper,
+        usd,
+        PoolFeeConfig {
+            contribution_fee_rate: dec!(0),
+            redemption_fee_rate: dec!("0.1"),
+            fee_destination: PoolFeeDestination::FeeVault,
+        },
+    )
+    .expect_commit_success();
+
+    // Contributions fill the queue
+    let (other_lp_user_key, _, other_lp_user_account) = helper.test_runner.new_allocated_account();
+    get_resource(
+        &mut helper,
+        other_lp_user_key,
+        other_lp_user_account,
+        dec!(2_500),
+    )
+    .expect_commit_success();
+
+    market_contribute(
+        &mut helper,
+        other_lp_user_key,
+        other_lp_user_account,
+        usd,
+        dec!(100),
+    )
+    .expect_commit_success();
+
+    market_claim_redemption(&mut helper, lp_user_key, lp_user_account, 1u64)
+        .expect_commit_success();
+
+    assert_eq!(
+        helper
+            .test_runner
+            .get_component_balance(lp_user_account, usd),
+        lp_usd_balance + dec!(390)
+    );
+
+    // Repayments fill the rest of the queue, the ticket is burned once fully claimed
+    market_repay(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(250),
+    )
+    .expect_commit_success();
+
+    market_claim_redemption(&mut helper, lp_user_key, lp_user_account, 1u64)
+        .expect_commit_success();
+
+    assert_eq!(
+        helper
+            .test_runner
+            .get_component_balance(lp_user_account, usd),
+        lp_usd_balance + dec!(570)
+    );
+
+    assert_eq!(
+        helper.test_runner.get_component_balance(
+            lp_user_account,
+            helper.market.redemption_ticket_resource_address
+        ),
+        dec!(0)
+    );
+}
@@ -0,0 +1,206 @@
+use crate::helpers::{init::TestHelper, methods::*};
+use lending_market::modules::pool_config::*;
+use radix_engine_interface::prelude::*;
+
+const T0: i64 = 1_700_000_000_000;
+const DAY: i64 = 24 * 60 * 60 * 1000;
+
+fn stable_rate_loan_config(rebalance_supply_rate_threshold: Decimal) -> UpdatePoolConfigInput {
+    UpdatePoolConfigInput::StableRateLoan(Some(StableRateLoanConfig {
+        rate_premium: dec!("0.01"),
+        rebalance_supply_rate_threshold,
+        rebalance_utilization_threshold: dec!("0.5"),
+    }))
+}
+
+// Stable rate loans enabled on the USDC pool, LP provides 800 USDC and two borrowers lock 15000 XRD each
+fn setup_borrowers(
+    helper: &mut TestHelper,
+) -> (
+    (Secp256k1PublicKey, ComponentAddress),
+    (Secp256k1PublicKey, ComponentAddress),
+) {
+    let usd = helper.faucet.usdc_resource_address;
+
+    advance_time(helper, 1, T0);
+
+    market_update_pool_config(helper, usd, stable_rate_loan_config(dec!(0)))
+        .expect_commit_success();
+
+    let (lp_user_key, _, lp_user_account) = helper.test_runner.new_allocated_account();
+    helper.test_runner.load_account_from_faucet(lp_user_account);
+    helper.test_runner.load_account_from_faucet(lp_user_account);
+    get_resource(helper, lp_user_key, lp_user_account, dec!(25_000)) //
+        .expect_commit_success();
+
+    market_contribute(helper, lp_user_key, lp_user_account, usd, dec!(800)).expect_commit_success();
+
+    let mut borrowers = Vec::new();
+
+    for _ in 0..2 {
+        let (borrower_key, _, borrower_account) = helper.test_runner.new_allocated_account();
+        helper
+            .test_runner
+            .load_account_from_faucet(borrower_account);
+
+        market_create_cdp(
+            helper,
+            borrower_key,
+            borrower_account,
+            vec![(XRD, dec!(15_000))],
+        )
+        .expect_commit_success();
+
+        borrowers.push((borrower_key, borrower_account));
+    }
+
+    (borrowers[0], borrowers[1])
+}
+
+#[test]
+fn test_borrow_repay_stable() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+
+    let ((borrower_key, borrower_account), _) = setup_borrowers(&mut helper);
+
+    market_borrow_stable(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(100),
+    )
+    .expect_commit_success();
+
+    // Additional borrows are added to the existing loan
+    market_borrow_stable(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(50),
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        helper
+            .test_runner
+            .get_component_balance(borrower_account, usd),
+        dec!(150)
+    );
+
+    // The CDP health is checked
+    market_borrow_stable(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(300),
+    )
+    .expect_commit_failure();
+
+    // Get some USDC to pay the interest
+    get_resource(&mut helper, borrower_key, borrower_account, dec!(250)).expect_commit_success();
+
+    advance_time(&mut helper, 2, T0 + 30 * DAY);
+
+    let balance_before_repay = helper
+        .test_runner
+        .get_component_balance(borrower_account, usd);
+
+    market_repay_stable(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(155),
+    )
+    .expect_commit_success();
+
+    // The loan with the accrued interest is repaid, the unused payment is returned
+    let repaid_amount = balance_before_repay
+        - helper
+            .test_runner
+            .get_component_balance(borrower_account, usd);
+
+    assert!(repaid_amount > dec!(150) && repaid_amount < dec!(152));
+
+    // The loan is closed
+    market_repay_stable(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(1),
+    )
+    .expect_commit_failure();
+}
+
+#[test]
+fn test_rebalance_stable_rate() {
+    let mut helper = TestHelper::new();
+
+    let usd = helper.faucet.usdc_resource_address;
+
+    let ((borrower_key, borrower_account), (other_borrower_key, other_borrower_account)) =
+        setup_borrowers(&mut helper);
+
+    let (keeper_key, _, keeper_account) = helper.test_runner.new_allocated_account();
+
+    market_borrow_stable(
+        &mut helper,
+        borrower_key,
+        borrower_account,
+        1u64,
+        usd,
+        dec!(100),
+    )
+    .expect_commit_success();
+
+    // Utilization is 0.125, below the rebalance threshold
+    market_rebalance_stable_rate(&mut helper, keeper_key, keeper_account, 1u64, usd)
+        .expect_commit_failure();
+
+    // Utilization goes above the rebalance threshold, the stable rate can be moved up by anyone
+    market_borrow(
+        &mut helper,
+        other_borrower_key,
+        other_borrower_account,
+        2u64,
+        usd,
+        dec!(350),
+    )
+    .expect_commit_success();
+
+    market_rebalance_stable_rate(&mut helper, keeper_key, keeper_account, 1u64, usd)
+        .expect_commit_success();
+
+    // Already at the current stable rate
+    market_rebalance_stable_rate(&mut helper, keeper_key, keeper_account, 1u64, usd)
+        .expect_commit_failure();
+
+    // Utilization goes back down and the rebalance conditions are always met:
+    // the current stable rate is lower and the loan is not rebalanced down
+    market_repay(
+        &mut helper,
+        other_borrower_key,
+        other_borrower_account,
+        2u64,
+        usd,
+        dec!(350),
+    )
+    .expect_commit_success();
+
+    market_update_pool_config(&mut helper, usd, stable_rate_loan_config(dec!(1)))
+        .expect_commit_success();
+
+    market_rebalance_stable_rate(&mut helper, keeper_key, keeper_account, 1u64, usd)
+        .expect_commit_failure();
+}
@@ -1,3 +1,5 @@
+use lending_market::modules::pool_config::*;
+use lending_market::modules::price_source::*;
 use radix_engine::vm::NoExtension;
 use radix_engine_interface::prelude::*;
 use scrypto::*;
@@ -16,6 +18,7 @@ pub struct MarketTestHelper {
     pub market_admin_badge: ResourceAddress,
     pub market_reserve_collector_badge: ResourceAddress,
     pub liquidation_term_resource_address: ResourceAddress,
+    pub redemption_ticket_resource_address: ResourceAddress,
     pub pools: IndexMap<ResourceAddress, (ComponentAddress, ResourceAddress)>,
 }
 
@@ -26,6 +29,25 @@ impl MarketTestHelper {
         owner_public_key: Secp256k1PublicKey,
         price_feed: &PriceFeedTestHelper,
         faucet: &FaucetTestHelper,
+    ) -> MarketTestHelper {
+        MarketTestHelper::new_with_base_currency(
+            test_runner,
+            owner_account_address,
+            owner_public_key,
+            price_feed,
+            faucet,
+            None,
+        )
+    }
+
+    // Market with XRD and USDC pools quoting prices in the given base currency, XRD when not set
+    pub fn new_with_base_currency(
+        test_runner: &mut TestRunner<NoExtension, InMemorySubstateDatabase>,
+        owner_account_address: ComponentAddress,
+        owner_public_key: Secp256k1PublicKey,
+        price_feed: &PriceFeedTestHelper,
+        faucet: &FaucetTestHelper,
+        base_currency: Option<ResourceAddress>,
     ) -> MarketTestHelper {
         let _pool_package_address =
             test_runner.compile_and_publish(Path::new("../single_resource_pool"));
@@ -58,7 +80,7 @@ impl MarketTestHelper {
                 market_package_address,
                 "LendingMarket",
                 "instantiate",
-                manifest_args!((10u8,)),
+                manifest_args!((10u8, base_currency)),
             )
             .deposit_batch(owner_account_address);
 
@@ -79,6 +101,7 @@ impl MarketTestHelper {
         let cdp_resource_address = resource_addresses_created[2];
         let batch_flashloan_resource_address = resource_addresses_created[3];
         let liquidation_term_resource_address = resource_addresses_created[3];
+        let redemption_ticket_resource_address = resource_addresses_created[4];
 
         // // Pools
 
@@ -115,9 +138,14 @@ impl MarketTestHelper {
                         None::<Decimal>,
                         None::<Decimal>,
                         None::<Decimal>,
+                        None::<FlashloanLimit>,
                         5i64,
+                        None::<Decimal>,
                         15i64,
-                        240i64
+                        240i64,
+                        None::<FixedTermLoanConfig>,
+                        None::<StableRateLoanConfig>,
+                        None::<TwapConfig>
                     ),
                     (
                         dec!(0.05),
@@ -129,7 +157,9 @@ impl MarketTestHelper {
                         IndexMap::<ResourceAddress, Decimal>::new(),
                         IndexMap::<u8, Decimal>::new(),
                         dec!("0.7")
-                    )
+                    ),
+                    None::<ComponentAddress>,
+                    None::<PriceSource>
                 ),
             )
             .deposit_batch(owner_account_address)
@@ -181,9 +211,14 @@ impl MarketTestHelper {
                         None::<Decimal>,
                         None::<Decimal>,
                         None::<Decimal>,
+                        None::<FlashloanLimit>,
                         5i64,
+                        None::<Decimal>,
                         15i64,
-                        240i64
+                        240i64,
+                        None::<FixedTermLoanConfig>,
+                        None::<StableRateLoanConfig>,
+                        None::<TwapConfig>
                     ),
                     (
                         dec!(0.05),
@@ -195,7 +230,9 @@ impl MarketTestHelper {
                         IndexMap::<ResourceAddress, Decimal>::new(),
                         IndexMap::<u8, Decimal>::new(),
                         dec!("0.0")
-                    )
+                    ),
+                    None::<ComponentAddress>,
+                    None::<PriceSource>
                 ),
             )
             .deposit_batch(owner_account_address);
@@ -223,6 +260,7 @@ impl MarketTestHelper {
             cdp_resource_address,
             market_reserve_collector_badge,
             liquidation_term_resource_address,
+            redemption_ticket_resource_address,
             pools,
         }
     }
@@ -1,4 +1,9 @@
 use super::init::{build_and_dumb_to_fs, TestHelper};
+use super::price_feed::PriceFeedTestHelper;
+use lending_market::modules::pool_config::*;
+use lending_market::modules::pool_state::PriceFeedUsedEvent;
+use lending_market::modules::price_source::PriceSource;
+use lending_market::modules::utils::PoolFeeConfig;
 use radix_engine_interface::prelude::*;
 use scrypto::*;
 use scrypto_test::prelude::*;
@@ -33,6 +38,31 @@ pub fn admin_update_price(
     )
 }
 
+// Set a price with the admin badge of the given price feed, held by the helper owner
+pub fn price_feed_admin_update_price(
+    helper: &mut TestHelper,
+    price_feed: &PriceFeedTestHelper,
+    resource_address: ResourceAddress,
+    price: Decimal,
+) -> TransactionReceiptV1 {
+    let manifest_builder = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .create_proof_from_account_of_non_fungible(
+            helper.owner_account_address,
+            NonFungibleGlobalId::new(
+                price_feed.price_feed_admin_badge,
+                NonFungibleLocalId::integer(1),
+            ),
+        )
+        .call_method(
+            price_feed.price_feed_component_address,
+            "admin_update_price",
+            manifest_args!(resource_address, price),
+        );
+
+    execute_owner_manifest(helper, manifest_builder, "price_feed_admin_update_price")
+}
+
 pub fn get_price(
     helper: &mut TestHelper,
     resource_address: ResourceAddress,
@@ -136,6 +166,51 @@ pub fn swap(
     receipt
 }
 
+// Advance the ledger time in milliseconds and keep the XRD and USDC prices fresh
+pub fn advance_time(helper: &mut TestHelper, round: u64, timestamp: i64) {
+    let usd = helper.faucet.usdc_resource_address;
+
+    helper
+        .test_runner
+        .advance_to_round_at_timestamp(Round::of(round), timestamp);
+
+    admin_update_price(helper, 1u64, XRD, dec!(1)).expect_commit_success();
+    admin_update_price(helper, 1u64, usd, dec!(25)).expect_commit_success();
+}
+
+// Manifest builder with the market admin proof in the auth zone
+pub fn market_admin_manifest_builder(helper: &TestHelper) -> ManifestBuilder {
+    ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .create_proof_from_account_of_non_fungibles(
+            helper.owner_account_address,
+            helper.market.market_admin_badge,
+            vec![
+                NonFungibleLocalId::integer(1),
+                NonFungibleLocalId::integer(2),
+                NonFungibleLocalId::integer(3),
+                NonFungibleLocalId::integer(4),
+            ],
+        )
+}
+
+pub fn execute_owner_manifest(
+    helper: &mut TestHelper,
+    manifest_builder: ManifestBuilder,
+    name: &str,
+) -> TransactionReceiptV1 {
+    let receipt = helper.test_runner.execute_manifest(
+        build_and_dumb_to_fs(manifest_builder, name.into()),
+        vec![NonFungibleGlobalId::from_public_key(
+            &helper.owner_public_key,
+        )],
+    );
+
+    println!("{:?}\n", receipt);
+
+    receipt
+}
+
 pub fn market_update_pool_state(
     helper: &mut TestHelper,
     res_address: ResourceAddress,
@@ -160,6 +235,16 @@ pub fn market_update_pool_state(
     receipt
 }
 
+// Update the pool state and return the price feed used event of the price update
+pub fn market_update_pool_price(
+    helper: &mut TestHelper,
+    res_address: ResourceAddress,
+) -> PriceFeedUsedEvent {
+    let receipt = market_update_pool_state(helper, res_address);
+
+    get_price_feed_used_events(helper, &receipt).pop().unwrap()
+}
+
 pub fn market_contribute(
     helper: &mut TestHelper,
     user_public_key: Secp256k1PublicKey,
@@ -216,6 +301,96 @@ pub fn market_redeem(
     )
 }
 
+pub fn market_request_redeem(
+    helper: &mut TestHelper,
+    user_public_key: Secp256k1PublicKey,
+    user_account_address: ComponentAddress,
+    pool_unit_res_address: ResourceAddress,
+    amount: Decimal,
+) -> TransactionReceipt {
+    let manifest_builder = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .withdraw_from_account(user_account_address, pool_unit_res_address, amount)
+        .take_all_from_worktop(pool_unit_res_address, "pool_unit_bucket")
+        .with_name_lookup(|builder, lookup| {
+            let bucket = lookup.bucket("pool_unit_bucket");
+
+            builder.call_method(
+                helper.market.market_component_address,
+                "request_redeem",
+                manifest_args!(bucket),
+            )
+        })
+        .deposit_batch(user_account_address);
+
+    helper.test_runner.execute_manifest(
+        build_and_dumb_to_fs(manifest_builder, "request_redeem".into()),
+        vec![NonFungibleGlobalId::from_public_key(&user_public_key)],
+    )
+}
+
+pub fn market_claim_redemption(
+    helper: &mut TestHelper,
+    user_public_key: Secp256k1PublicKey,
+    user_account_address: ComponentAddress,
+    ticket_id: u64,
+) -> TransactionReceipt {
+    let ticket_res_address = helper.market.redemption_ticket_resource_address;
+
+    let manifest_builder = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .withdraw_non_fungibles_from_account(
+            user_account_address,
+            ticket_res_address,
+            &btreeset!(NonFungibleLocalId::Integer(ticket_id.into())),
+        )
+        .take_all_from_worktop(ticket_res_address, "ticket_bucket")
+        .with_name_lookup(|builder, lookup| {
+            let bucket = lookup.bucket("ticket_bucket");
+
+            builder.call_method(
+                helper.market.market_component_address,
+                "claim_redemption",
+                manifest_args!(bucket),
+            )
+        })
+        .deposit_batch(user_account_address);
+
+    helper.test_runner.execute_manifest(
+        build_and_dumb_to_fs(manifest_builder, "claim_redemption".into()),
+        vec![NonFungibleGlobalId::from_public_key(&user_public_key)],
+    )
+}
+
+pub fn market_update_pool_min_amounts(
+    helper: &mut TestHelper,
+    res_address: ResourceAddress,
+    min_contribution_amount: Decimal,
+    min_redemption_units: Decimal,
+) -> TransactionReceiptV1 {
+    let manifest_builder = market_admin_manifest_builder(helper).call_method(
+        helper.market.market_component_address,
+        "update_pool_min_amounts",
+        manifest_args!(res_address, min_contribution_amount, min_redemption_units),
+    );
+
+    execute_owner_manifest(helper, manifest_builder, "update_pool_min_amounts")
+}
+
+pub fn market_update_pool_fee_config(
+    helper: &mut TestHelper,
+    res_address: ResourceAddress,
+    fee_config: PoolFeeConfig,
+) -> TransactionReceiptV1 {
+    let manifest_builder = market_admin_manifest_builder(helper).call_method(
+        helper.market.market_component_address,
+        "update_pool_fee_config",
+        manifest_args!(res_address, fee_config),
+    );
+
+    execute_owner_manifest(helper, manifest_builder, "update_pool_fee_config")
+}
+
 pub fn market_create_cdp(
     helper: &mut TestHelper,
     user_public_key: Secp256k1PublicKey,
@@ -406,6 +581,240 @@ pub fn market_repay(
     )
 }
 
+pub fn market_update_pool_config(
+    helper: &mut TestHelper,
+    res_address: ResourceAddress,
+    value: UpdatePoolConfigInput,
+) -> TransactionReceiptV1 {
+    let manifest_builder = market_admin_manifest_builder(helper).call_method(
+        helper.market.market_component_address,
+        "update_pool_config",
+        manifest_args!(res_address, value),
+    );
+
+    execute_owner_manifest(helper, manifest_builder, "update_pool_config")
+}
+
+pub fn market_update_price_source(
+    helper: &mut TestHelper,
+    res_address: ResourceAddress,
+    price_source: PriceSource,
+) -> TransactionReceiptV1 {
+    let manifest_builder = market_admin_manifest_builder(helper).call_method(
+        helper.market.market_component_address,
+        "update_price_source",
+        manifest_args!(res_address, price_source),
+    );
+
+    execute_owner_manifest(helper, manifest_builder, "update_price_source")
+}
+
+// Price feed used events of a committed transaction, in emission order
+pub fn get_price_feed_used_events(
+    helper: &TestHelper,
+    receipt: &TransactionReceiptV1,
+) -> Vec<PriceFeedUsedEvent> {
+    receipt
+        .expect_commit_success()
+        .application_events
+        .iter()
+        .filter(|(event_type_identifier, _)| {
+            helper.test_runner.event_name(event_type_identifier) == "PriceFeedUsedEvent"
+        })
+        .map(|(_, event_data)| scrypto_decode::<PriceFeedUsedEvent>(event_data).unwrap())
+        .collect()
+}
+
+pub fn market_borrow_fixed_term(
+    helper: &mut TestHelper,
+    user_public_key: Secp256k1PublicKey,
+    user_account_address: ComponentAddress,
+    cdp_id: u64,
+    res_address: ResourceAddress,
+    amount: Decimal,
+    duration: i64,
+) -> TransactionReceipt {
+    let manifest_builder = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .create_proof_from_account_of_non_fungible(
+            user_account_address,
+            NonFungibleGlobalId::new(
+                helper.market.cdp_resource_address,
+                NonFungibleLocalId::Integer(cdp_id.into()),
+            ),
+        )
+        .pop_from_auth_zone("cdp_proof")
+        .with_name_lookup(|builder, lookup| {
+            let proof = lookup.proof("cdp_proof");
+
+            builder.call_method(
+                helper.market.market_component_address,
+                "borrow_fixed_term",
+                manifest_args!(proof, res_address, amount, duration),
+            )
+        })
+        .deposit_batch(user_account_address);
+
+    helper.test_runner.execute_manifest(
+        build_and_dumb_to_fs(manifest_builder, "borrow_fixed_term".into()),
+        vec![NonFungibleGlobalId::from_public_key(&user_public_key)],
+    )
+}
+
+pub fn market_repay_fixed_term(
+    helper: &mut TestHelper,
+    user_public_key: Secp256k1PublicKey,
+    user_account_address: ComponentAddress,
+    cdp_id: u64,
+    res_address: ResourceAddress,
+    amount: Decimal,
+) -> TransactionReceipt {
+    let manifest_builder = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .create_proof_from_account_of_non_fungible(
+            user_account_address,
+            NonFungibleGlobalId::new(
+                helper.market.cdp_resource_address,
+                NonFungibleLocalId::Integer(cdp_id.into()),
+            ),
+        )
+        .pop_from_auth_zone("cdp_proof")
+        .withdraw_from_account(user_account_address, res_address, amount)
+        .take_all_from_worktop(res_address, "res_bucket")
+        .with_name_lookup(|builder, lookup| {
+            let proof = lookup.proof("cdp_proof");
+            let bucket = lookup.bucket("res_bucket");
+
+            builder.call_method(
+                helper.market.market_component_address,
+                "repay_fixed_term",
+                manifest_args!(proof, bucket),
+            )
+        })
+        .deposit_batch(user_account_address);
+
+    helper.test_runner.execute_manifest(
+        build_and_dumb_to_fs(manifest_builder, "repay_fixed_term".into()),
+        vec![NonFungibleGlobalId::from_public_key(&user_public_key)],
+    )
+}
+
+pub fn market_convert_fixed_term_loans(
+    helper: &mut TestHelper,
+    user_public_key: Secp256k1PublicKey,
+    user_account_address: ComponentAddress,
+    cdp_id: u64,
+) -> TransactionReceipt {
+    let manifest_builder = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            helper.market.market_component_address,
+            "convert_fixed_term_loans",
+            manifest_args!(NonFungibleLocalId::Integer(cdp_id.into())),
+        )
+        .deposit_batch(user_account_address);
+
+    helper.test_runner.execute_manifest(
+        build_and_dumb_to_fs(manifest_builder, "convert_fixed_term_loans".into()),
+        vec![NonFungibleGlobalId::from_public_key(&user_public_key)],
+    )
+}
+
+pub fn market_borrow_stable(
+    helper: &mut TestHelper,
+    user_public_key: Secp256k1PublicKey,
+    user_account_address: ComponentAddress,
+    cdp_id: u64,
+    res_address: ResourceAddress,
+    amount: Decimal,
+) -> TransactionReceipt {
+    let manifest_builder = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .create_proof_from_account_of_non_fungible(
+            user_account_address,
+            NonFungibleGlobalId::new(
+                helper.market.cdp_resource_address,
+                NonFungibleLocalId::Integer(cdp_id.into()),
+            ),
+        )
+        .pop_from_auth_zone("cdp_proof")
+        .with_name_lookup(|builder, lookup| {
+            let proof = lookup.proof("cdp_proof");
+
+            builder.call_method(
+                helper.market.market_component_address,
+                "borrow_stable",
+                manifest_args!(proof, res_address, amount),
+            )
+        })
+        .deposit_batch(user_account_address);
+
+    helper.test_runner.execute_manifest(
+        build_and_dumb_to_fs(manifest_builder, "borrow_stable".into()),
+        vec![NonFungibleGlobalId::from_public_key(&user_public_key)],
+    )
+}
+
+pub fn market_repay_stable(
+    helper: &mut TestHelper,
+    user_public_key: Secp256k1PublicKey,
+    user_account_address: ComponentAddress,
+    cdp_id: u64,
+    res_address: ResourceAddress,
+    amount: Decimal,
+) -> TransactionReceipt {
+    let manifest_builder = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .create_proof_from_account_of_non_fungible(
+            user_account_address,
+            NonFungibleGlobalId::new(
+                helper.market.cdp_resource_address,
+                NonFungibleLocalId::Integer(cdp_id.into()),
+            ),
+        )
+        .pop_from_auth_zone("cdp_proof")
+        .withdraw_from_account(user_account_address, res_address, amount)
+        .take_all_from_worktop(res_address, "res_bucket")
+        .with_name_lookup(|builder, lookup| {
+            let proof = lookup.proof("cdp_proof");
+            let bucket = lookup.bucket("res_bucket");
+
+            builder.call_method(
+                helper.market.market_component_address,
+                "repay_stable",
+                manifest_args!(proof, bucket),
+            )
+        })
+        .deposit_batch(user_account_address);
+
+    helper.test_runner.execute_manifest(
+        build_and_dumb_to_fs(manifest_builder, "repay_stable".into()),
+        vec![NonFungibleGlobalId::from_public_key(&user_public_key)],
+    )
+}
+
+pub fn market_rebalance_stable_rate(
+    helper: &mut TestHelper,
+    user_public_key: Secp256k1PublicKey,
+    user_account_address: ComponentAddress,
+    cdp_id: u64,
+    res_address: ResourceAddress,
+) -> TransactionReceipt {
+    let manifest_builder = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            helper.market.market_component_address,
+            "rebalance_stable_rate",
+            manifest_args!(NonFungibleLocalId::Integer(cdp_id.into()), res_address),
+        )
+        .deposit_batch(user_account_address);
+
+    helper.test_runner.execute_manifest(
+        build_and_dumb_to_fs(manifest_builder, "rebalance_stable_rate".into()),
+        vec![NonFungibleGlobalId::from_public_key(&user_public_key)],
+    )
+}
+
 pub fn market_start_liquidation(
     helper: &mut TestHelper,
     user_public_key: Secp256k1PublicKey,
@@ -531,6 +940,77 @@ pub fn market_fast_liquidation(
     )
 }
 
+pub fn market_create_deleverage_order(
+    helper: &mut TestHelper,
+    user_public_key: Secp256k1PublicKey,
+    user_account_address: ComponentAddress,
+    cdp_id: u64,
+    trigger_ltv: Decimal,
+    loan_res_address: ResourceAddress,
+    loan_amount: Decimal,
+    collateral_res_address: ResourceAddress,
+    max_slippage_rate: Decimal,
+    keeper_fee_rate: Decimal,
+) -> TransactionReceipt {
+    let manifest_builder = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .create_proof_from_account_of_non_fungible(
+            user_account_address,
+            NonFungibleGlobalId::new(
+                helper.market.cdp_resource_address,
+                NonFungibleLocalId::Integer(cdp_id.into()),
+            ),
+        )
+        .pop_from_auth_zone("cdp_proof")
+        .with_name_lookup(|builder, lookup| {
+            let proof = lookup.proof("cdp_proof");
+
+            builder.call_method(
+                helper.market.market_component_address,
+                "create_deleverage_order",
+                manifest_args!(
+                    proof,
+                    (
+                        trigger_ltv,
+                        loan_res_address,
+                        loan_amount,
+                        collateral_res_address,
+                        helper.faucet.faucet_component_address,
+                        max_slippage_rate,
+                        keeper_fee_rate
+                    )
+                ),
+            )
+        })
+        .deposit_batch(user_account_address);
+
+    helper.test_runner.execute_manifest(
+        build_and_dumb_to_fs(manifest_builder, "create_deleverage_order".into()),
+        vec![NonFungibleGlobalId::from_public_key(&user_public_key)],
+    )
+}
+
+pub fn market_execute_deleverage_order(
+    helper: &mut TestHelper,
+    keeper_public_key: Secp256k1PublicKey,
+    keeper_account_address: ComponentAddress,
+    order_id: u64,
+) -> TransactionReceipt {
+    let manifest_builder = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            helper.market.market_component_address,
+            "execute_deleverage_order",
+            manifest_args!(order_id),
+        )
+        .deposit_batch(keeper_account_address);
+
+    helper.test_runner.execute_manifest(
+        build_and_dumb_to_fs(manifest_builder, "execute_deleverage_order".into()),
+        vec![NonFungibleGlobalId::from_public_key(&keeper_public_key)],
+    )
+}
+
 pub fn market_take_batch_flashloan(
     helper: &mut TestHelper,
     _user_public_key: Secp256k1PublicKey,
@@ -541,7 +1021,7 @@ pub fn market_take_batch_flashloan(
     manifest_builder.lock_fee_from_faucet().call_method(
         helper.market.market_component_address,
         "take_batch_flashloan",
-        manifest_args!(loan_amounts),
+        manifest_args!(loan_amounts, None::<ManifestProof>),
     );
 }
 
@@ -49,6 +49,8 @@ fn test_get_collateral_units() {
         collaterals,
         loans: IndexMap::new(),
         delegatee_loans: IndexMap::new(),
+        fixed_term_loans: IndexMap::new(),
+        stable_rate_loans: IndexMap::new(),
         minted_at: 0,
         updated_at: 0,
     };
@@ -59,6 +61,8 @@ fn test_get_collateral_units() {
         collateral_updated: false,
         loan_updated: false,
         delegatee_loan_updated: false,
+        fixed_term_loan_updated: false,
+        stable_rate_loan_updated: false,
     };
     assert_eq!(wrapped_cdp_data.get_collateral_units(res_address), dec!(10));
 }
@@ -77,6 +81,8 @@ fn test_get_loan_unit() {
         collaterals: IndexMap::new(),
         loans,
         delegatee_loans: IndexMap::new(),
+        fixed_term_loans: IndexMap::new(),
+        stable_rate_loans: IndexMap::new(),
         minted_at: 0,
         updated_at: 0,
     };
@@ -87,6 +93,8 @@ fn test_get_loan_unit() {
         collateral_updated: false,
         loan_updated: false,
         delegatee_loan_updated: false,
+        fixed_term_loan_updated: false,
+        stable_rate_loan_updated: false,
     };
     assert_eq!(wrapped_cdp_data.get_loan_unit(res_address), dec!(10));
 }
@@ -107,6 +115,8 @@ fn test_increase_delegatee_count() {
             collaterals: IndexMap::new(),
             loans: IndexMap::new(),
             delegatee_loans: IndexMap::new(),
+            fixed_term_loans: IndexMap::new(),
+            stable_rate_loans: IndexMap::new(),
             minted_at: 0,
             updated_at: 0,
         },
@@ -115,6 +125,8 @@ fn test_increase_delegatee_count() {
         collateral_updated: false,
         loan_updated: false,
         delegatee_loan_updated: false,
+        fixed_term_loan_updated: false,
+        stable_rate_loan_updated: false,
     };
     wrapped_cdp_data.increase_delegatee_count().unwrap();
 
@@ -142,6 +154,8 @@ fn test_decrease_delegatee_count() {
             collaterals: IndexMap::new(),
             loans: IndexMap::new(),
             delegatee_loans: IndexMap::new(),
+            fixed_term_loans: IndexMap::new(),
+            stable_rate_loans: IndexMap::new(),
             minted_at: 0,
             updated_at: 0,
         },
@@ -150,6 +164,8 @@ fn test_decrease_delegatee_count() {
         collateral_updated: false,
         loan_updated: false,
         delegatee_loan_updated: false,
+        fixed_term_loan_updated: false,
+        stable_rate_loan_updated: false,
     };
     wrapped_cdp_data.decrease_delegatee_count().unwrap();
     cdp_type = CDPType::Delegator(DelegateeInfo {
@@ -175,6 +191,8 @@ fn test_update_collateral() {
             collaterals: IndexMap::new(),
             loans: IndexMap::new(),
             delegatee_loans: IndexMap::new(),
+            fixed_term_loans: IndexMap::new(),
+            stable_rate_loans: IndexMap::new(),
             minted_at: 0,
             updated_at: 0,
         },
@@ -183,6 +201,8 @@ fn test_update_collateral() {
         collateral_updated: false,
         loan_updated: false,
         delegatee_loan_updated: false,
+        fixed_term_loan_updated: false,
+        stable_rate_loan_updated: false,
     };
     wrapped_cdp_data
         .update_collateral(res_address.clone(), dec!(10))
@@ -205,6 +225,8 @@ fn test_update_loan() {
             collaterals: IndexMap::new(),
             loans: IndexMap::new(),
             delegatee_loans: IndexMap::new(),
+            fixed_term_loans: IndexMap::new(),
+            stable_rate_loans: IndexMap::new(),
             minted_at: 0,
             updated_at: 0,
         },
@@ -213,6 +235,8 @@ fn test_update_loan() {
         collateral_updated: false,
         loan_updated: false,
         delegatee_loan_updated: false,
+        fixed_term_loan_updated: false,
+        stable_rate_loan_updated: false,
     };
     wrapped_cdp_data
         .update_loan(res_address.clone(), dec!(10))
@@ -234,6 +258,8 @@ fn test_update_delegatee_loan() {
             collaterals: IndexMap::new(),
             loans: IndexMap::new(),
             delegatee_loans: IndexMap::new(),
+            fixed_term_loans: IndexMap::new(),
+            stable_rate_loans: IndexMap::new(),
             minted_at: 0,
             updated_at: 0,
         },
@@ -242,6 +268,8 @@ fn test_update_delegatee_loan() {
         collateral_updated: false,
         loan_updated: false,
         delegatee_loan_updated: false,
+        fixed_term_loan_updated: false,
+        stable_rate_loan_updated: false,
     };
     wrapped_cdp_data
         .update_delegatee_loan(res_address.clone(), dec!(10))
@@ -0,0 +1,68 @@
+use lending_market::modules::deleverage_order::*;
+use scrypto::*;
+use scrypto_test::prelude::*;
+
+fn get_default_deleverage_order() -> DeleverageOrder {
+    DeleverageOrder {
+        trigger_ltv: dec!(0.9),
+        loan_res_address: XRD,
+        loan_amount: dec!(100),
+        collateral_res_address: ACCOUNT_OWNER_BADGE,
+        swap_component: FAUCET,
+        max_slippage_rate: dec!(0.02),
+        keeper_fee_rate: dec!(0.005),
+    }
+}
+
+#[test]
+fn test_check_valid_deleverage_order() {
+    let order = get_default_deleverage_order();
+    assert!(order.check().is_ok());
+}
+
+#[test]
+fn test_check_invalid_trigger_ltv() {
+    let order = DeleverageOrder {
+        trigger_ltv: dec!(0),
+        ..get_default_deleverage_order()
+    };
+
+    assert!(order.check().is_err());
+}
+
+#[test]
+fn test_check_invalid_loan_amount() {
+    let order = DeleverageOrder {
+        loan_amount: dec!(-100),
+        ..get_default_deleverage_order()
+    };
+
+    assert!(order.check().is_err());
+}
+
+#[test]
+fn test_check_identical_loan_and_collateral() {
+    let order = DeleverageOrder {
+        collateral_res_address: XRD,
+        ..get_default_deleverage_order()
+    };
+
+    assert!(order.check().is_err());
+}
+
+#[test]
+fn test_check_invalid_rates() {
+    let order = DeleverageOrder {
+        max_slippage_rate: dec!(1.1),
+        ..get_default_deleverage_order()
+    };
+
+    assert!(order.check().is_err());
+
+    let order = DeleverageOrder {
+        keeper_fee_rate: dec!(-0.01),
+        ..get_default_deleverage_order()
+    };
+
+    assert!(order.check().is_err());
+}
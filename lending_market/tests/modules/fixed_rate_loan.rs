@@ -0,0 +1,146 @@
+use lending_market::modules::fixed_rate_loan::*;
+use scrypto::*;
+use scrypto_test::prelude::*;
+
+const MINUTE: i64 = 60;
+const YEAR: i64 = 60 * 24 * 365 * MINUTE;
+
+#[test]
+fn test_fixed_rate_loan_increase() {
+    let mut loan = FixedRateLoan::new(0);
+
+    assert!(loan.increase(dec!(-1), dec!(0.1), 0).is_err());
+
+    assert!(loan.increase(dec!(100), dec!(0.1), 0).is_ok());
+    assert_eq!(loan.principal, dec!(100));
+    assert_eq!(loan.get_rate(), dec!(0.1));
+
+    // The rate is the principal weighted average rate
+    assert!(loan.increase(dec!(300), dec!(0.2), 0).is_ok());
+    assert_eq!(loan.principal, dec!(400));
+    assert_eq!(loan.rate_weighted_principal, dec!(70));
+    assert_eq!(loan.get_rate(), dec!(0.175));
+}
+
+#[test]
+fn test_fixed_rate_loan_accrue_interest() {
+    let mut loan = FixedRateLoan::new(0);
+
+    assert!(loan.increase(dec!(100), dec!(0.1), 0).is_ok());
+
+    // No interest within the same minute
+    assert_eq!(loan.get_amount(MINUTE - 1), Ok(dec!(100)));
+
+    // Simple interest on the principal
+    assert_eq!(loan.get_amount(YEAR), Ok(dec!(110)));
+
+    assert_eq!(loan.accrue_interest(YEAR / 2), Ok(dec!(5)));
+    assert_eq!(loan.accrue_interest(YEAR), Ok(dec!(5)));
+    assert_eq!(loan.principal, dec!(100));
+    assert_eq!(loan.get_interest(YEAR), Ok(dec!(10)));
+    assert_eq!(loan.updated_at, YEAR);
+}
+
+#[test]
+fn test_fixed_rate_loan_repay() {
+    let mut loan = FixedRateLoan::new(0);
+
+    assert!(loan.increase(dec!(100), dec!(0.1), 0).is_ok());
+
+    assert!(loan.repay(dec!(-1), YEAR).is_err());
+
+    // Interest is repaid first
+    let repaid_loan = loan.repay(dec!(4), YEAR).unwrap();
+    assert_eq!(repaid_loan.principal, dec!(0));
+    assert_eq!(repaid_loan.rate_weighted_principal, dec!(0));
+    assert_eq!(repaid_loan.get_amount(), dec!(4));
+    assert_eq!(loan.get_amount(YEAR), Ok(dec!(106)));
+
+    let repaid_loan = loan.repay(dec!(56), YEAR).unwrap();
+    assert_eq!(repaid_loan.principal, dec!(50));
+    assert_eq!(repaid_loan.rate_weighted_principal, dec!(5));
+    assert_eq!(repaid_loan.get_amount(), dec!(56));
+    assert_eq!(loan.principal, dec!(50));
+    assert_eq!(loan.get_interest(YEAR), Ok(dec!(0)));
+    assert_eq!(loan.get_rate(), dec!(0.1));
+
+    // A repayment above the loan amount closes the loan
+    let repaid_loan = loan.repay(dec!(51), YEAR).unwrap();
+    assert_eq!(repaid_loan.get_amount(), dec!(50));
+    assert_eq!(loan, FixedRateLoan::new(YEAR));
+}
+
+#[test]
+fn test_fixed_rate_loan_aggregate() {
+    let mut aggregate = FixedRateLoan::new(0);
+    let mut first_loan = FixedRateLoan::new(0);
+    let mut second_loan = FixedRateLoan::new(7 * MINUTE);
+
+    assert!(first_loan.increase(dec!(100), dec!(0.07), 0).is_ok());
+    assert!(aggregate.increase(dec!(100), dec!(0.07), 0).is_ok());
+
+    assert!(second_loan
+        .increase(dec!(33), dec!(0.13), 7 * MINUTE)
+        .is_ok());
+    assert!(aggregate.increase(dec!(33), dec!(0.13), 7 * MINUTE).is_ok());
+
+    // The aggregate accrues interest more often than the loans
+    for minute in 8..20 {
+        assert!(aggregate.accrue_interest(minute * MINUTE).is_ok());
+    }
+
+    let first_repayment = first_loan.repay(dec!(31), 20 * MINUTE).unwrap();
+    assert!(aggregate.remove(&first_repayment, 20 * MINUTE).is_ok());
+
+    let now = YEAR + 3 * MINUTE;
+
+    // The aggregate stays equal to the sum of the loans until both are closed
+    assert_eq!(
+        aggregate.get_amount(now),
+        Ok(first_loan.get_amount(now).unwrap() + second_loan.get_amount(now).unwrap())
+    );
+
+    let first_closed_loan = first_loan.close(now).unwrap();
+    let second_closed_loan = second_loan.close(now).unwrap();
+
+    assert!(aggregate.remove(&first_closed_loan, now).is_ok());
+    assert!(aggregate.remove(&second_closed_loan, now).is_ok());
+    assert_eq!(aggregate, FixedRateLoan::new(now));
+
+    // Removing more than the aggregate fails instead of being capped
+    assert!(aggregate.remove(&second_closed_loan, now).is_err());
+}
+
+#[test]
+fn test_fixed_rate_loan_update_rate() {
+    let mut aggregate = FixedRateLoan::new(0);
+    let mut loan = FixedRateLoan::new(0);
+
+    assert!(loan.increase(dec!(100), dec!(0.1), 0).is_ok());
+    assert!(aggregate.increase(dec!(100), dec!(0.1), 0).is_ok());
+
+    let previous_rate_weighted_principal = loan.update_rate(dec!(0.2), YEAR).unwrap();
+    assert_eq!(previous_rate_weighted_principal, dec!(10));
+    assert!(aggregate
+        .replace_rate_weighted_principal(
+            previous_rate_weighted_principal,
+            loan.rate_weighted_principal,
+            YEAR
+        )
+        .is_ok());
+
+    // Interest accrued before the update is kept
+    assert_eq!(loan.get_amount(2 * YEAR), Ok(dec!(130)));
+    assert_eq!(aggregate.get_amount(2 * YEAR), Ok(dec!(130)));
+}
+
+#[test]
+fn test_fixed_term_loan_maturity() {
+    let fixed_term_loan = FixedTermLoan {
+        loan: FixedRateLoan::new(0),
+        maturity: YEAR,
+    };
+
+    assert!(!fixed_term_loan.is_matured(YEAR - MINUTE));
+    assert!(fixed_term_loan.is_matured(YEAR));
+}
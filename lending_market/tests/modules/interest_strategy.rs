@@ -147,3 +147,165 @@ fn test_interest_strategy_get_rate_value() {
 
     assert!(interest_strategy.get_interest_rate(dec!(1.1)).is_err());
 }
+
+#[test]
+fn test_interest_strategy_get_pool_rates() {
+    let mut interest_strategy = InterestStrategy::new();
+
+    let breakpoints = vec![
+        ISInputBreakPoint {
+            usage: dec!(0),
+            slop: dec!(1),
+        },
+        ISInputBreakPoint {
+            usage: dec!(0.5),
+            slop: dec!(3),
+        },
+    ];
+
+    let result = interest_strategy.set_breakpoints(dec!(0.05), breakpoints);
+
+    assert!(result.is_ok());
+
+    assert_eq!(
+        interest_strategy.get_pool_rates(dec!(0.5), dec!(0.1)),
+        Ok(PoolRates {
+            utilization: dec!(0.5),
+            borrow_rate: dec!(0.55),
+            supply_rate: dec!(0.2475),
+        })
+    );
+
+    assert!(interest_strategy.get_rate_curve(1, dec!(0.1)).is_err());
+
+    assert!(interest_strategy
+        .get_rate_curve(MAX_RATE_CURVE_SAMPLES + 1, dec!(0.1))
+        .is_err());
+
+    assert_eq!(
+        interest_strategy
+            .get_rate_curve(MAX_RATE_CURVE_SAMPLES, dec!(0.1))
+            .unwrap()
+            .len(),
+        MAX_RATE_CURVE_SAMPLES as usize
+    );
+
+    let curve = interest_strategy.get_rate_curve(3, dec!(0.1)).unwrap();
+
+    assert_eq!(curve.len(), 3);
+    assert_eq!(curve[0].supply_rate, dec!(0));
+    assert_eq!(
+        curve[1],
+        interest_strategy
+            .get_pool_rates(dec!(0.5), dec!(0.1))
+            .unwrap()
+    );
+    assert_eq!(curve[2].utilization, dec!(1));
+    assert_eq!(curve[2].borrow_rate, dec!(2.05));
+}
+
+#[test]
+fn test_interest_strategy_exponential_model() {
+    let mut interest_strategy = InterestStrategy::new();
+
+    assert!(interest_strategy
+        .set_model(
+            InterestModelInput::Exponential(ExponentialModel {
+                base_rate: dec!(0.02),
+                multiplier: dec!(0.1),
+                growth_rate: dec!(-1),
+            }),
+            None,
+        )
+        .is_err());
+
+    let result = interest_strategy.set_model(
+        InterestModelInput::Exponential(ExponentialModel {
+            base_rate: dec!(0.02),
+            multiplier: dec!(0.1),
+            growth_rate: dec!(2),
+        }),
+        None,
+    );
+
+    assert!(result.is_ok());
+
+    assert_eq!(interest_strategy.get_interest_rate(dec!(0)), Ok(dec!(0.02)));
+
+    // 0.02 + 0.1 * (e^2 - 1)
+    let rate = interest_strategy.get_interest_rate(dec!(1)).unwrap();
+    assert!((rate - dec!(0.658905609893065)).checked_abs().unwrap() < dec!(0.000000001));
+
+    // Max rate clamp
+    let result = interest_strategy.set_model(
+        InterestModelInput::Exponential(ExponentialModel {
+            base_rate: dec!(0.02),
+            multiplier: dec!(0.1),
+            growth_rate: dec!(2),
+        }),
+        Some(dec!(0.5)),
+    );
+
+    assert!(result.is_ok());
+
+    assert_eq!(interest_strategy.get_interest_rate(dec!(1)), Ok(dec!(0.5)));
+}
+
+#[test]
+fn test_interest_strategy_adaptive_model() {
+    let mut interest_strategy = InterestStrategy::new();
+
+    let model = AdaptiveModel {
+        target_usage: dec!(0.8),
+        rate_at_target: dec!(0.04),
+        min_rate_at_target: dec!(0.01),
+        max_rate_at_target: dec!(1),
+        adjustment_speed: dec!(50),
+        curve_steepness: dec!(4),
+    };
+
+    assert!(interest_strategy
+        .set_model(
+            InterestModelInput::Adaptive(AdaptiveModel {
+                target_usage: dec!(1),
+                ..model.clone()
+            }),
+            None,
+        )
+        .is_err());
+
+    let result = interest_strategy.set_model(InterestModelInput::Adaptive(model), None);
+
+    assert!(result.is_ok());
+
+    assert_eq!(interest_strategy.get_interest_rate(dec!(0)), Ok(dec!(0.01)));
+    assert_eq!(
+        interest_strategy.get_interest_rate(dec!(0.8)),
+        Ok(dec!(0.04))
+    );
+    assert_eq!(interest_strategy.get_interest_rate(dec!(1)), Ok(dec!(0.16)));
+
+    // Rate at target does not move while the utilization stays on target
+    assert!(interest_strategy
+        .update_rate_at_target(dec!(0.8), 60 * 24)
+        .is_ok());
+    assert_eq!(
+        interest_strategy.get_interest_rate(dec!(0.8)),
+        Ok(dec!(0.04))
+    );
+
+    // Rate at target drifts up while the utilization stays above target
+    assert!(interest_strategy
+        .update_rate_at_target(dec!(1), 60 * 24)
+        .is_ok());
+    assert!(interest_strategy.get_interest_rate(dec!(0.8)).unwrap() > dec!(0.04));
+
+    // And is bounded by the minimum rate at target when staying below target for long
+    assert!(interest_strategy
+        .update_rate_at_target(dec!(0), 60 * 24 * 365)
+        .is_ok());
+    assert_eq!(
+        interest_strategy.get_interest_rate(dec!(0.8)),
+        Ok(dec!(0.01))
+    );
+}
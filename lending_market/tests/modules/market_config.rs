@@ -5,6 +5,7 @@ use scrypto_test::prelude::*;
 fn test_check_valid_max_cdp_position() {
     let market_config = MarketConfig {
         max_cdp_position: 10,
+        base_currency: None,
     };
 
     assert_eq!(market_config.check(), Ok(()));
@@ -14,6 +15,7 @@ fn test_check_valid_max_cdp_position() {
 fn test_check_invalid_max_cdp_position() {
     let market_config = MarketConfig {
         max_cdp_position: 0,
+        base_currency: None,
     };
 
     assert_eq!(
@@ -26,6 +28,7 @@ fn test_check_invalid_max_cdp_position() {
 fn test_update_max_cdp_position_valid() {
     let mut market_config = MarketConfig {
         max_cdp_position: 10,
+        base_currency: None,
     };
 
     assert_eq!(
@@ -40,6 +43,7 @@ fn test_update_max_cdp_position_valid() {
 fn test_update_max_cdp_position_invalid() {
     let mut market_config = MarketConfig {
         max_cdp_position: 10,
+        base_currency: None,
     };
 
     assert_eq!(
@@ -47,3 +51,13 @@ fn test_update_max_cdp_position_invalid() {
         Err("Max CDP position must be greater than 0".into())
     );
 }
+
+#[test]
+fn test_default_base_currency() {
+    let market_config = MarketConfig {
+        max_cdp_position: 10,
+        base_currency: None,
+    };
+
+    assert_eq!(market_config.get_base_currency(), XRD);
+}
@@ -1,6 +1,9 @@
 pub mod cdp_data;
+pub mod deleverage_order;
+pub mod fixed_rate_loan;
 pub mod interest_strategy;
 pub mod liquidation_threshold;
 pub mod market_config;
 pub mod operation_status;
 pub mod pool_config;
+pub mod utils;
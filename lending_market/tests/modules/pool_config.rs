@@ -14,9 +14,14 @@ fn get_default_pool_config() -> PoolConfig {
         deposit_limit: None,
         borrow_limit: None,
         utilization_limit: None,
+        flashloan_limit: None,
         price_update_period: 3600,
         interest_update_period: 3600,
+        max_interest_rate_change: None,
         price_expiration_period: 3601,
+        fixed_term_loan: None,
+        stable_rate_loan: None,
+        twap: None,
     }
 }
 
@@ -131,6 +136,45 @@ fn test_check_invalid_utilization_limit() {
     assert!(config.check().is_err());
 }
 
+#[test]
+fn test_check_invalid_flashloan_limit() {
+    let config = PoolConfig {
+        flashloan_limit: Some(FlashloanLimit::Amount(dec!(-100))),
+        ..get_default_pool_config()
+    };
+
+    assert!(config.check().is_err());
+
+    let config = PoolConfig {
+        flashloan_limit: Some(FlashloanLimit::LiquidityRatio(dec!(1.2))),
+        ..get_default_pool_config()
+    };
+
+    assert!(config.check().is_err());
+}
+
+#[test]
+fn test_check_flashloan_limit() {
+    let config = PoolConfig {
+        flashloan_limit: Some(FlashloanLimit::LiquidityRatio(dec!(0.5))),
+        ..get_default_pool_config()
+    };
+
+    assert!(config
+        .check_limit(CheckPoolConfigLimitInput::FlashloanLimit(
+            dec!(50),
+            dec!(100)
+        ))
+        .is_ok());
+
+    assert!(config
+        .check_limit(CheckPoolConfigLimitInput::FlashloanLimit(
+            dec!(51),
+            dec!(100)
+        ))
+        .is_err());
+}
+
 #[test]
 fn test_check_invalid_liquidation_bonus_rate() {
     let config = PoolConfig {
@@ -185,6 +229,23 @@ fn test_check_invalid_interest_update_period() {
     assert!(config.check().is_err());
 }
 
+#[test]
+fn test_check_invalid_max_interest_rate_change() {
+    let config = PoolConfig {
+        max_interest_rate_change: Some(dec!(0)),
+        ..get_default_pool_config()
+    };
+
+    assert!(config.check().is_err());
+
+    let config = PoolConfig {
+        max_interest_rate_change: Some(dec!(0.01)),
+        ..get_default_pool_config()
+    };
+
+    assert!(config.check().is_ok());
+}
+
 #[test]
 fn test_check_invalid_price_expiration_period() {
     let config = PoolConfig {
@@ -202,3 +263,123 @@ fn test_check_invalid_price_expiration_period() {
 
     assert!(config.check().is_err());
 }
+
+#[test]
+fn test_check_invalid_fixed_term_loan() {
+    let config = PoolConfig {
+        fixed_term_loan: Some(FixedTermLoanConfig {
+            rate_premium: dec!(-0.01),
+            max_duration: 60 * 24 * 365,
+        }),
+        ..get_default_pool_config()
+    };
+
+    assert!(config.check().is_err());
+
+    let config = PoolConfig {
+        fixed_term_loan: Some(FixedTermLoanConfig {
+            rate_premium: dec!(0.01),
+            max_duration: 0,
+        }),
+        ..get_default_pool_config()
+    };
+
+    assert!(config.check().is_err());
+
+    let config = PoolConfig {
+        fixed_term_loan: Some(FixedTermLoanConfig {
+            rate_premium: dec!(0.01),
+            max_duration: 60 * 24 * 365,
+        }),
+        ..get_default_pool_config()
+    };
+
+    assert!(config.check().is_ok());
+}
+
+#[test]
+fn test_check_invalid_stable_rate_loan() {
+    let valid_stable_rate_loan = StableRateLoanConfig {
+        rate_premium: dec!(0.02),
+        rebalance_supply_rate_threshold: dec!(0.01),
+        rebalance_utilization_threshold: dec!(0.95),
+    };
+
+    let config = PoolConfig {
+        stable_rate_loan: Some(StableRateLoanConfig {
+            rate_premium: dec!(-0.02),
+            ..valid_stable_rate_loan.clone()
+        }),
+        ..get_default_pool_config()
+    };
+
+    assert!(config.check().is_err());
+
+    let config = PoolConfig {
+        stable_rate_loan: Some(StableRateLoanConfig {
+            rebalance_utilization_threshold: dec!(1.1),
+            ..valid_stable_rate_loan.clone()
+        }),
+        ..get_default_pool_config()
+    };
+
+    assert!(config.check().is_err());
+
+    let config = PoolConfig {
+        stable_rate_loan: Some(valid_stable_rate_loan),
+        ..get_default_pool_config()
+    };
+
+    assert!(config.check().is_ok());
+}
+
+#[test]
+fn test_check_twap_config() {
+    let config = PoolConfig {
+        twap: Some(TwapConfig {
+            window: 0,
+            conservative: true,
+        }),
+        ..get_default_pool_config()
+    };
+
+    assert!(config.check().is_err());
+
+    let config = PoolConfig {
+        twap: Some(TwapConfig {
+            window: 30,
+            conservative: true,
+        }),
+        ..get_default_pool_config()
+    };
+
+    assert!(config.check().is_ok());
+}
+
+#[test]
+fn test_twap_collateral_and_loan_prices() {
+    let twap_config = TwapConfig {
+        window: 30,
+        conservative: true,
+    };
+
+    // Collaterals are valued at the lower price and loans at the higher price
+    assert_eq!(
+        twap_config.get_collateral_and_loan_prices(dec!(10), dec!(12)),
+        (dec!(10), dec!(12))
+    );
+    assert_eq!(
+        twap_config.get_collateral_and_loan_prices(dec!(12), dec!(10)),
+        (dec!(10), dec!(12))
+    );
+
+    let twap_config = TwapConfig {
+        window: 30,
+        conservative: false,
+    };
+
+    assert_eq!(
+        twap_config.get_collateral_and_loan_prices(dec!(10), dec!(12)),
+        (dec!(12), dec!(12))
+    );
+}
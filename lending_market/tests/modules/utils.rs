@@ -0,0 +1,57 @@
+use lending_market::modules::utils::*;
+use scrypto::*;
+use scrypto_test::prelude::*;
+
+// Price info of a feed that does not provide a confidence interval
+#[derive(ScryptoSbor)]
+struct FeedPriceInfo {
+    timestamp: i64,
+    price: Decimal,
+}
+
+fn to_feed_output<T: ScryptoEncode>(output: &T) -> ScryptoValue {
+    scrypto_decode(&scrypto_encode(output).unwrap()).unwrap()
+}
+
+#[test]
+fn test_decode_price_info_with_confidence() {
+    let output = to_feed_output(&Some(PriceInfo {
+        timestamp: 60,
+        price: dec!(25),
+        confidence: Some(dec!(1)),
+    }));
+
+    let price_info = decode_price_info(&output).unwrap().unwrap();
+
+    assert_eq!(price_info.timestamp, 60);
+    assert_eq!(price_info.price, dec!(25));
+    assert_eq!(price_info.confidence, Some(dec!(1)));
+    assert_eq!(price_info.get_relative_confidence(), dec!("0.04"));
+}
+
+#[test]
+fn test_decode_price_info_without_confidence() {
+    let output = to_feed_output(&Some(FeedPriceInfo {
+        timestamp: 60,
+        price: dec!(25),
+    }));
+
+    let price_info = decode_price_info(&output).unwrap().unwrap();
+
+    assert_eq!(price_info.timestamp, 60);
+    assert_eq!(price_info.price, dec!(25));
+    assert_eq!(price_info.confidence, None);
+    assert_eq!(price_info.get_relative_confidence(), dec!(0));
+}
+
+#[test]
+fn test_decode_price_info_not_found() {
+    assert!(decode_price_info(&to_feed_output(&None::<FeedPriceInfo>))
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_decode_invalid_price_info() {
+    assert!(decode_price_info(&to_feed_output(&Some(dec!(25)))).is_err());
+}
@@ -0,0 +1,68 @@
+use scrypto::prelude::*;
+
+/// Yield strategy used to test the SingleResourcePool yield strategy integration.
+/// Hold the deposited assets and let anyone add yield or remove funds to simulate gains and losses.
+/// Not meant to be deployed
+#[blueprint]
+pub mod mock_yield_strategy {
+
+    enable_method_auth! {
+        roles {
+            pool => updatable_by: [];
+        },
+        methods {
+            deposit => restrict_to :[pool];
+            withdraw => restrict_to :[pool];
+
+            balance => PUBLIC;
+            add_yield => PUBLIC;
+            remove_funds => PUBLIC;
+        }
+    }
+
+    pub struct MockYieldStrategy {
+        assets: Vault,
+    }
+
+    impl MockYieldStrategy {
+        pub fn instantiate(
+            res_address: ResourceAddress,
+            pool_rule: AccessRule,
+        ) -> Global<MockYieldStrategy> {
+            Self {
+                assets: Vault::new(res_address),
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .roles(roles!(
+                pool => pool_rule;
+            ))
+            .globalize()
+        }
+
+        pub fn deposit(&mut self, assets: Bucket) {
+            self.assets.put(assets);
+        }
+
+        pub fn withdraw(&mut self, amount: Decimal) -> Bucket {
+            self.assets.take_advanced(
+                amount.min(self.assets.amount()),
+                WithdrawStrategy::Rounded(RoundingMode::ToZero),
+            )
+        }
+
+        pub fn balance(&self) -> Decimal {
+            self.assets.amount()
+        }
+
+        // Simulate strategy gains
+        pub fn add_yield(&mut self, assets: Bucket) {
+            self.assets.put(assets);
+        }
+
+        // Simulate strategy losses
+        pub fn remove_funds(&mut self, amount: Decimal) -> Bucket {
+            self.assets.take(amount)
+        }
+    }
+}
@@ -22,6 +22,98 @@ pub enum DepositType {
     LiquiditySupply,
 }
 
+#[derive(ScryptoSbor, ManifestSbor, Clone, Copy, PartialEq, Debug)]
+pub enum PoolFeeDestination {
+    /// Fees stay in the pool and accrue to the remaining pool unit holders
+    Pool,
+    /// Fees are kept in a separate vault and withdrawn by the admin
+    FeeVault,
+}
+
+#[derive(ScryptoSbor, ManifestSbor, Clone, Copy, PartialEq, Debug)]
+pub struct PoolFeeConfig {
+    /// Fee rate taken from the contributed assets
+    pub contribution_fee_rate: Decimal,
+
+    /// Fee rate taken from the redeemed assets
+    pub redemption_fee_rate: Decimal,
+
+    pub fee_destination: PoolFeeDestination,
+}
+impl PoolFeeConfig {
+    pub fn check(&self) {
+        assert!(
+            self.contribution_fee_rate >= 0.into() && self.contribution_fee_rate < 1.into(),
+            "Contribution fee rate must be in the range [0, 1)"
+        );
+        assert!(
+            self.redemption_fee_rate >= 0.into() && self.redemption_fee_rate < 1.into(),
+            "Redemption fee rate must be in the range [0, 1)"
+        );
+    }
+}
+
+// Build the pool unit metadata from the pooled resource metadata, e.g. XRD -> wXRD
+fn pool_unit_metadata_init(
+    pool_res_address: ResourceAddress,
+    dapp_definition: Option<ComponentAddress>,
+) -> MetadataInit {
+    let res_manager = ResourceManager::from_address(pool_res_address);
+
+    let get_string = |key: &str| -> Option<String> {
+        res_manager
+            .get_metadata::<&str, String>(key)
+            .ok()
+            .flatten()
+            .filter(|value| !value.is_empty())
+    };
+
+    // Metadata missing on the pooled resource is left unset on the pool unit
+    let mut init = MetadataInit::new();
+
+    if let Some(symbol) = get_string("symbol") {
+        init.set_metadata("symbol", format!("w{}", symbol));
+        init.set_metadata(
+            "description",
+            format!(
+                "Pool unit representing a share of the {} lending pool",
+                symbol
+            ),
+        );
+    }
+
+    if let Some(name) = get_string("name") {
+        init.set_metadata("name", format!("Weft {} Pool Unit", name));
+    }
+
+    if let Some(icon_url) = res_manager
+        .get_metadata::<&str, UncheckedUrl>("icon_url")
+        .ok()
+        .flatten()
+    {
+        init.set_metadata("icon_url", icon_url);
+    }
+
+    if let Some(dapp_definition) = dapp_definition {
+        init.set_metadata(
+            "dapp_definitions",
+            vec![GlobalAddress::from(dapp_definition)],
+        );
+    }
+
+    init
+}
+
+#[derive(ScryptoSbor, ManifestSbor, Clone, Copy, PartialEq, Debug)]
+pub enum YieldStrategyAction {
+    /// Move idle liquidity to the yield strategy
+    Allocate(Decimal),
+    /// Move liquidity back from the yield strategy
+    Recall(Decimal),
+    /// Update the tracked strategy liquidity with the strategy balance to account gains and losses
+    Sync,
+}
+
 pub fn assert_fungible_res_address(address: ResourceAddress, message: Option<String>) {
     assert!(
         ResourceManager::from_address(address)
@@ -55,16 +147,29 @@ pub mod single_resource_pool {
 
             protected_deposit => restrict_to :[admin];
             protected_withdraw => restrict_to :[admin];
+            protected_redeem => restrict_to :[admin];
+            queued_redeem => restrict_to :[admin];
 
             decrease_external_liquidity => restrict_to :[admin];
             increase_external_liquidity => restrict_to :[admin];
 
+            update_min_amounts => restrict_to :[admin];
+            update_fee_config => restrict_to :[admin];
+            withdraw_collected_fees => restrict_to :[admin];
+
+            update_yield_strategy => restrict_to :[admin];
+            manage_yield_strategy => restrict_to :[admin];
+
             contribute => restrict_to :[can_contribute];
             redeem  => restrict_to :[can_redeem];
 
             get_pool_unit_ratio => PUBLIC;
             get_pool_unit_supply => PUBLIC;
             get_pooled_amount => PUBLIC;
+            get_min_amounts => PUBLIC;
+            get_fee_config => PUBLIC;
+            get_collected_fees => PUBLIC;
+            get_yield_strategy => PUBLIC;
 
         }
     }
@@ -81,6 +186,28 @@ pub mod single_resource_pool {
 
         /// Ratio between the pool unit and the pooled token
         unit_to_asset_ratio: PreciseDecimal,
+
+        /// Minimum amount of pooled token accepted by a contribution
+        min_contribution_amount: Decimal,
+
+        /// Minimum amount of pool units accepted by a redemption
+        min_redemption_units: Decimal,
+
+        /// Contribution and redemption fees
+        fee_config: PoolFeeConfig,
+
+        /// Fees collected when the fee destination is the fee vault
+        fees: Vault,
+
+        /// Admin approved component implementing `deposit`, `withdraw` and `balance`,
+        /// used to earn yield on idle liquidity
+        yield_strategy: Option<Global<AnyComponent>>,
+
+        /// Amount moved to the yield strategy, tracked like the external liquidity
+        strategy_liquidity_amount: Decimal,
+
+        /// Maximum share of the total liquidity that can be moved to the yield strategy
+        max_strategy_share: Decimal,
     }
 
     impl SingleResourcePool {
@@ -88,11 +215,23 @@ pub mod single_resource_pool {
             pool_res_address: ResourceAddress,
             owner_role: OwnerRole,
             component_rule: AccessRule,
+            fee_config: PoolFeeConfig,
+            dapp_definition: Option<ComponentAddress>,
         ) -> (Owned<SingleResourcePool>, ResourceAddress) {
             /* CHECK INPUTS */
             assert_fungible_res_address(pool_res_address, None);
+            fee_config.check();
 
             let pool_unit_res_manager = ResourceBuilder::new_fungible(owner_role)
+                .metadata(ModuleConfig {
+                    init: pool_unit_metadata_init(pool_res_address, dapp_definition),
+                    roles: metadata_roles! {
+                        metadata_setter => OWNER;
+                        metadata_setter_updater => OWNER;
+                        metadata_locker => OWNER;
+                        metadata_locker_updater => rule!(deny_all);
+                    },
+                })
                 .mint_roles(mint_roles! {
                     minter => component_rule.clone();
                     minter_updater => rule!(deny_all);
@@ -108,6 +247,13 @@ pub mod single_resource_pool {
                 pool_unit_res_manager,
                 external_liquidity_amount: 0.into(),
                 unit_to_asset_ratio: 1.into(),
+                min_contribution_amount: 0.into(),
+                min_redemption_units: 0.into(),
+                fee_config,
+                fees: Vault::new(pool_res_address),
+                yield_strategy: None,
+                strategy_liquidity_amount: 0.into(),
+                max_strategy_share: 0.into(),
             }
             .instantiate();
 
@@ -120,6 +266,8 @@ pub mod single_resource_pool {
             admin_rule: AccessRule,
             contribute_rule: AccessRule,
             redeem_rule: AccessRule,
+            fee_config: PoolFeeConfig,
+            dapp_definition: Option<ComponentAddress>,
         ) -> (Global<SingleResourcePool>, ResourceAddress) {
             let (address_reservation, component_address) =
                 Runtime::allocate_component_address(SingleResourcePool::blueprint_id());
@@ -131,6 +279,8 @@ pub mod single_resource_pool {
                     pool_res_address,
                     owner_role.clone(),
                     component_rule,
+                    fee_config,
+                    dapp_definition,
                 );
 
             let pool_component = owned_pool_component
@@ -154,27 +304,167 @@ pub mod single_resource_pool {
             self.pool_unit_res_manager.total_supply().unwrap_or(dec!(0))
         }
 
-        pub fn get_pooled_amount(&self) -> (Decimal, Decimal) {
-            (self.liquidity.amount(), self.external_liquidity_amount)
+        // Liquidity available right away, external liquidity and liquidity moved to the yield strategy.
+        // Strategy liquidity is recalled when needed but may not be withdrawable right away
+        pub fn get_pooled_amount(&self) -> (Decimal, Decimal, Decimal) {
+            (
+                self.liquidity.amount(),
+                self.external_liquidity_amount,
+                self.strategy_liquidity_amount,
+            )
+        }
+
+        pub fn get_min_amounts(&self) -> (Decimal, Decimal) {
+            (self.min_contribution_amount, self.min_redemption_units)
+        }
+
+        pub fn update_min_amounts(
+            &mut self,
+            min_contribution_amount: Decimal,
+            min_redemption_units: Decimal,
+        ) {
+            /* INPUT CHECK */
+            assert!(
+                min_contribution_amount >= 0.into(),
+                "Minimum contribution amount must not be negative!"
+            );
+            assert!(
+                min_redemption_units >= 0.into(),
+                "Minimum redemption units must not be negative!"
+            );
+
+            self.min_contribution_amount = min_contribution_amount;
+            self.min_redemption_units = min_redemption_units;
+        }
+
+        pub fn get_fee_config(&self) -> PoolFeeConfig {
+            self.fee_config
+        }
+
+        pub fn update_fee_config(&mut self, fee_config: PoolFeeConfig) {
+            fee_config.check();
+
+            self.fee_config = fee_config;
+        }
+
+        pub fn get_collected_fees(&self) -> Decimal {
+            self.fees.amount()
+        }
+
+        pub fn withdraw_collected_fees(&mut self) -> Bucket {
+            self.fees.take_all()
+        }
+
+        pub fn get_yield_strategy(&self) -> (Option<ComponentAddress>, Decimal, Decimal) {
+            (
+                self.yield_strategy.map(|strategy| strategy.address()),
+                self.strategy_liquidity_amount,
+                self.max_strategy_share,
+            )
+        }
+
+        // Replace the yield strategy. Liquidity in the previous strategy is recalled first
+        pub fn update_yield_strategy(
+            &mut self,
+            yield_strategy: Option<ComponentAddress>,
+            max_strategy_share: Decimal,
+        ) {
+            /* INPUT CHECK */
+            assert!(
+                max_strategy_share >= 0.into() && max_strategy_share <= 1.into(),
+                "Max strategy share must be in the range [0, 1]"
+            );
+
+            if let Some(strategy) = self.yield_strategy {
+                let assets = strategy.call_raw::<Bucket>(
+                    "withdraw",
+                    scrypto_args!(strategy.call_raw::<Decimal>("balance", scrypto_args!())),
+                );
+
+                self.strategy_liquidity_amount = 0.into();
+                self.liquidity.put(assets);
+            }
+
+            self.yield_strategy = yield_strategy.map(|address| address.into());
+            self.max_strategy_share = max_strategy_share;
+
+            self.unit_to_asset_ratio = self._get_unit_to_asset_ratio();
+        }
+
+        pub fn manage_yield_strategy(&mut self, action: YieldStrategyAction) {
+            let strategy = self.yield_strategy.expect("No yield strategy set");
+
+            match action {
+                YieldStrategyAction::Allocate(amount) => {
+                    assert!(amount > 0.into(), "Allocated amount must be positive!");
+
+                    let total_liquidity_amount = self.liquidity.amount()
+                        + self.external_liquidity_amount
+                        + self.strategy_liquidity_amount;
+
+                    assert!(
+                        self.strategy_liquidity_amount + amount
+                            <= total_liquidity_amount * self.max_strategy_share,
+                        "Allocation exceeds the max strategy share"
+                    );
+
+                    let assets = self
+                        .liquidity
+                        .take_advanced(amount, WithdrawStrategy::Rounded(RoundingMode::ToZero));
+
+                    self.strategy_liquidity_amount += assets.amount();
+
+                    strategy.call_raw::<()>("deposit", scrypto_args!(assets));
+                }
+                YieldStrategyAction::Recall(amount) => {
+                    self._recall_from_strategy(amount);
+                }
+                YieldStrategyAction::Sync => {
+                    self.strategy_liquidity_amount =
+                        strategy.call_raw::<Decimal>("balance", scrypto_args!());
+
+                    self.unit_to_asset_ratio = self._get_unit_to_asset_ratio();
+                }
+            }
         }
 
         // Handle request to increase liquidity.
         // Add liquidity to the pool and get pool units back
-        pub fn contribute(&mut self, assets: Bucket) -> Bucket {
+        pub fn contribute(&mut self, mut assets: Bucket) -> Bucket {
             /* CHECK INPUT */
             assert!(
                 assets.resource_address() == self.liquidity.resource_address(),
                 "Pool resource address mismatch"
             );
+            assert!(
+                assets.amount() >= self.min_contribution_amount,
+                "Contribution amount is below the minimum"
+            );
+
+            let fee_amount = assets.amount() * self.fee_config.contribution_fee_rate;
+
+            let unit_amount = ((assets.amount() - fee_amount) * self.unit_to_asset_ratio) //
+                .checked_truncate(RoundingMode::ToZero)
+                .expect("Error while calculating unit amount to mint");
 
-            let unit_amount =
-                (assets.amount() * self.unit_to_asset_ratio) //
-                    .checked_truncate(RoundingMode::ToNearestMidpointToEven)
-                    .expect("Error while calculating unit amount to mint");
+            assert!(unit_amount > 0.into(), "Contribution amount is too small");
+
+            if self.fee_config.fee_destination == PoolFeeDestination::FeeVault {
+                self.fees.put(
+                    assets
+                        .take_advanced(fee_amount, WithdrawStrategy::Rounded(RoundingMode::ToZero)),
+                );
+            }
 
             self.liquidity.put(assets);
 
-            self.pool_unit_res_manager.mint(unit_amount)
+            let pool_units = self.pool_unit_res_manager.mint(unit_amount);
+
+            if fee_amount > 0.into() {
+                self.unit_to_asset_ratio = self._get_unit_to_asset_ratio();
+            }
+
+            pool_units
         }
 
         // Handle request to decrease liquidity.
@@ -185,22 +475,48 @@ pub mod single_resource_pool {
                 pool_units.resource_address() == self.pool_unit_res_manager.address(),
                 "Pool unit resource address mismatch"
             );
+            assert!(
+                pool_units.amount() >= self.min_redemption_units,
+                "Redemption amount is below the minimum"
+            );
+            assert!(
+                self._get_redemption_amount(&pool_units) > 0.into(),
+                "Redemption amount is too small"
+            );
 
-            let amount = (pool_units.amount() / self.unit_to_asset_ratio) //
-                .checked_truncate(RoundingMode::ToNearestMidpointToEven)
-                .expect("Error while calculating amount to withdraw");
+            let mut assets = self._redeem(pool_units);
 
-            self.pool_unit_res_manager.burn(pool_units);
+            self._take_redemption_fee(&mut assets);
 
+            assets
+        }
+
+        // Handle the partial fills of queued redemptions. The fee applies, the minimum is checked
+        // by the caller on the whole request, units worth nothing are burned for an empty bucket
+        pub fn queued_redeem(&mut self, pool_units: Bucket) -> Bucket {
+            /* INPUT CHECK */
             assert!(
-                amount <= self.liquidity.amount(),
-                "Not enough liquidity to withdraw this amount"
+                pool_units.resource_address() == self.pool_unit_res_manager.address(),
+                "Pool unit resource address mismatch"
             );
 
-            self.liquidity.take_advanced(
-                amount,
-                WithdrawStrategy::Rounded(RoundingMode::ToNearestMidpointToEven),
-            )
+            let mut assets = self._redeem(pool_units);
+
+            self._take_redemption_fee(&mut assets);
+
+            assets
+        }
+
+        // Handle protocol redemptions: liquidations and deleverage orders.
+        // No minimum or fee applies, units worth nothing are burned for an empty bucket
+        pub fn protected_redeem(&mut self, pool_units: Bucket) -> Bucket {
+            /* INPUT CHECK */
+            assert!(
+                pool_units.resource_address() == self.pool_unit_res_manager.address(),
+                "Pool unit resource address mismatch"
+            );
+
+            self._redeem(pool_units)
         }
 
         pub fn protected_withdraw(
@@ -212,6 +528,8 @@ pub mod single_resource_pool {
             /* INPUT CHECK */
             assert!(amount >= 0.into(), "Withdraw amount must not be negative!");
 
+            self._ensure_liquidity(amount);
+
             let assets = self.liquidity.take_advanced(amount, withdraw_strategy);
 
             if withdraw_type == WithdrawType::ForTemporaryUse {
@@ -270,16 +588,94 @@ pub mod single_resource_pool {
 
         /* PRIVATE UTILITY METHODS */
 
+        fn _get_redemption_amount(&self, pool_units: &Bucket) -> Decimal {
+            (pool_units.amount() / self.unit_to_asset_ratio) //
+                .checked_truncate(RoundingMode::ToZero)
+                .expect("Error while calculating amount to withdraw")
+        }
+
+        // Burn the pool units and withdraw the corresponding liquidity
+        fn _redeem(&mut self, pool_units: Bucket) -> Bucket {
+            let amount = self._get_redemption_amount(&pool_units);
+
+            self.pool_unit_res_manager.burn(pool_units);
+
+            self._ensure_liquidity(amount);
+
+            assert!(
+                amount <= self.liquidity.amount(),
+                "Not enough liquidity to withdraw this amount"
+            );
+
+            self.liquidity.take_advanced(
+                amount,
+                WithdrawStrategy::Rounded(RoundingMode::ToNearestMidpointToEven),
+            )
+        }
+
+        // Keep the redemption fee out of the redeemed assets
+        fn _take_redemption_fee(&mut self, assets: &mut Bucket) {
+            let fee_amount = assets.amount() * self.fee_config.redemption_fee_rate;
+
+            if fee_amount > 0.into() {
+                let fees = assets.take_advanced(
+                    fee_amount,
+                    WithdrawStrategy::Rounded(RoundingMode::AwayFromZero),
+                );
+
+                match self.fee_config.fee_destination {
+                    PoolFeeDestination::Pool => self.liquidity.put(fees),
+                    PoolFeeDestination::FeeVault => self.fees.put(fees),
+                }
+
+                self.unit_to_asset_ratio = self._get_unit_to_asset_ratio();
+            }
+        }
+
+        // Recall liquidity from the yield strategy if the vault does not hold the needed amount
+        fn _ensure_liquidity(&mut self, amount: Decimal) {
+            let missing_amount = amount - self.liquidity.amount();
+
+            if missing_amount > 0.into() && self.yield_strategy.is_some() {
+                self._recall_from_strategy(missing_amount.min(self.strategy_liquidity_amount));
+            }
+        }
+
+        fn _recall_from_strategy(&mut self, amount: Decimal) {
+            assert!(
+                amount >= 0.into() && amount <= self.strategy_liquidity_amount,
+                "Recalled amount must be in the range [0, strategy liquidity]"
+            );
+
+            let strategy = self.yield_strategy.expect("No yield strategy set");
+
+            let assets = strategy.call_raw::<Bucket>("withdraw", scrypto_args!(amount));
+
+            // A recalled amount lower than requested is a strategy loss
+            self.strategy_liquidity_amount -= amount;
+
+            let realized_loss = assets.amount() < amount;
+
+            self.liquidity.put(assets);
+
+            if realized_loss {
+                self.unit_to_asset_ratio = self._get_unit_to_asset_ratio();
+            }
+        }
+
+        // Virtual units and assets are added to the pool so that the ratio cannot be
+        // manipulated by supplying liquidity to an empty or nearly empty pool
         fn _get_unit_to_asset_ratio(&mut self) -> PreciseDecimal {
-            let total_liquidity_amount = self.liquidity.amount() + self.external_liquidity_amount;
+            let virtual_amount = PreciseDecimal::from(dec!("0.000001"));
+
+            let total_liquidity_amount = self.liquidity.amount()
+                + self.external_liquidity_amount
+                + self.strategy_liquidity_amount;
 
             let total_supply = self.pool_unit_res_manager.total_supply().unwrap_or(dec!(0));
 
-            if total_liquidity_amount != 0.into() {
-                PreciseDecimal::from(total_supply) / PreciseDecimal::from(total_liquidity_amount)
-            } else {
-                1.into()
-            }
+            (PreciseDecimal::from(total_supply) + virtual_amount)
+                / (PreciseDecimal::from(total_liquidity_amount) + virtual_amount)
         }
     }
 }
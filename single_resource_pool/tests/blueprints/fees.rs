@@ -0,0 +1,162 @@
+use crate::helpers::init::*;
+use radix_engine_interface::prelude::*;
+use single_asset_pool::*;
+
+fn get_collected_fees(helper: &mut PoolTestHelper) -> Decimal {
+    helper
+        .call("get_collected_fees", manifest_args!())
+        .expect_commit_success()
+        .output::<Decimal>(1)
+}
+
+#[test]
+fn test_pool_fees_to_pool() {
+    let mut helper = PoolTestHelper::new(PoolFeeConfig {
+        contribution_fee_rate: dec!("0.1"),
+        redemption_fee_rate: dec!("0.1"),
+        fee_destination: PoolFeeDestination::Pool,
+    });
+
+    let balance = helper.get_balance(XRD);
+
+    // The contribution fee stays in the pool, 100 XRD back 90 units
+    helper.contribute(dec!(100)).expect_commit_success();
+
+    assert_eq!(
+        helper.get_balance(helper.pool_unit_resource_address),
+        dec!(90)
+    );
+    assert!(helper.get_pool_unit_ratio() < pdec!("0.91"));
+    assert_eq!(get_collected_fees(&mut helper), dec!(0));
+
+    // The redemption fee stays in the pool and raises the value of the remaining units
+    helper.redeem(dec!(45)).expect_commit_success();
+
+    let redeemed_amount = helper.get_balance(XRD) - (balance - dec!(100));
+    assert!(redeemed_amount > dec!(44) && redeemed_amount < dec!(46));
+
+    let ratio = helper.get_pool_unit_ratio();
+    assert!(ratio < pdec!("0.85"));
+
+    // Protocol redemptions are not charged, the remaining units get the whole pool
+    let balance = helper.get_balance(XRD);
+
+    helper.protected_redeem(dec!(45)).expect_commit_success();
+
+    let redeemed_amount = helper.get_balance(XRD) - balance;
+    assert!(redeemed_amount > dec!(54) && redeemed_amount <= dec!(55));
+    assert_eq!(get_collected_fees(&mut helper), dec!(0));
+}
+
+#[test]
+fn test_pool_fees_to_fee_vault() {
+    let mut helper = PoolTestHelper::new(PoolFeeConfig {
+        contribution_fee_rate: dec!("0.1"),
+        redemption_fee_rate: dec!("0.1"),
+        fee_destination: PoolFeeDestination::FeeVault,
+    });
+
+    let balance = helper.get_balance(XRD);
+
+    // The contribution fee is kept aside, the ratio is unchanged
+    helper.contribute(dec!(100)).expect_commit_success();
+
+    assert_eq!(
+        helper.get_balance(helper.pool_unit_resource_address),
+        dec!(90)
+    );
+    assert_eq!(helper.get_pool_unit_ratio(), pdec!(1));
+    assert_eq!(get_collected_fees(&mut helper), dec!(10));
+
+    // 50 units are redeemed for 50 XRD, 5 XRD go to the fee vault
+    helper.redeem(dec!(50)).expect_commit_success();
+
+    assert_eq!(helper.get_balance(XRD), balance - dec!(100) + dec!(45));
+    assert_eq!(helper.get_pool_unit_ratio(), pdec!(1));
+    assert_eq!(get_collected_fees(&mut helper), dec!(15));
+
+    // Protocol redemptions are not charged
+    helper.protected_redeem(dec!(40)).expect_commit_success();
+
+    assert_eq!(helper.get_balance(XRD), balance - dec!(100) + dec!(85));
+    assert_eq!(get_collected_fees(&mut helper), dec!(15));
+
+    // The admin withdraws the collected fees
+    helper
+        .call("withdraw_collected_fees", manifest_args!())
+        .expect_commit_success();
+
+    assert_eq!(helper.get_balance(XRD), balance - dec!(100) + dec!(100));
+    assert_eq!(get_collected_fees(&mut helper), dec!(0));
+}
+
+#[test]
+fn test_pool_queued_redeem_fees() {
+    let mut helper = PoolTestHelper::new(PoolFeeConfig {
+        contribution_fee_rate: dec!(0),
+        redemption_fee_rate: dec!("0.1"),
+        fee_destination: PoolFeeDestination::FeeVault,
+    });
+
+    let balance = helper.get_balance(XRD);
+
+    helper.contribute(dec!(100)).expect_commit_success();
+
+    helper
+        .call("update_min_amounts", manifest_args!(dec!(0), dec!(50)))
+        .expect_commit_success();
+
+    helper.redeem(dec!(20)).expect_commit_failure();
+
+    // Queued partial fills are below the minimum but pay the redemption fee
+    helper.queued_redeem(dec!(20)).expect_commit_success();
+
+    assert_eq!(helper.get_balance(XRD), balance - dec!(100) + dec!(18));
+    assert_eq!(get_collected_fees(&mut helper), dec!(2));
+}
+
+#[test]
+fn test_pool_update_fee_config() {
+    let mut helper = PoolTestHelper::new(no_fee_config());
+
+    helper
+        .call(
+            "update_fee_config",
+            manifest_args!(PoolFeeConfig {
+                contribution_fee_rate: dec!(1),
+                redemption_fee_rate: dec!(0),
+                fee_destination: PoolFeeDestination::Pool,
+            }),
+        )
+        .expect_commit_failure();
+
+    helper
+        .call(
+            "update_fee_config",
+            manifest_args!(PoolFeeConfig {
+                contribution_fee_rate: dec!(0),
+                redemption_fee_rate: dec!(-1),
+                fee_destination: PoolFeeDestination::Pool,
+            }),
+        )
+        .expect_commit_failure();
+
+    helper
+        .call(
+            "update_fee_config",
+            manifest_args!(PoolFeeConfig {
+                contribution_fee_rate: dec!("0.5"),
+                redemption_fee_rate: dec!(0),
+                fee_destination: PoolFeeDestination::FeeVault,
+            }),
+        )
+        .expect_commit_success();
+
+    helper.contribute(dec!(100)).expect_commit_success();
+
+    assert_eq!(
+        helper.get_balance(helper.pool_unit_resource_address),
+        dec!(50)
+    );
+    assert_eq!(get_collected_fees(&mut helper), dec!(50));
+}
@@ -0,0 +1,43 @@
+use crate::helpers::init::*;
+use radix_engine_interface::prelude::*;
+
+#[test]
+fn test_pool_unit_metadata() {
+    let mut helper = PoolTestHelper::new(no_fee_config());
+
+    let pool_unit = GlobalAddress::from(helper.pool_unit_resource_address);
+
+    assert_eq!(
+        helper.test_runner.get_metadata(pool_unit, "symbol"),
+        Some(MetadataValue::String("wXRD".to_string()))
+    );
+    assert_eq!(
+        helper.test_runner.get_metadata(pool_unit, "name"),
+        Some(MetadataValue::String("Weft Radix Pool Unit".to_string()))
+    );
+
+    // The icon is copied as a Url
+    assert!(matches!(
+        helper.test_runner.get_metadata(pool_unit, "icon_url"),
+        Some(MetadataValue::Url(_))
+    ));
+}
+
+#[test]
+fn test_pool_unit_metadata_without_source_metadata() {
+    let mut helper = PoolTestHelper::new_with_resource(no_fee_config(), true);
+
+    let pool_unit = GlobalAddress::from(helper.pool_unit_resource_address);
+
+    // Missing source metadata is left unset instead of producing partial values
+    assert_eq!(helper.test_runner.get_metadata(pool_unit, "symbol"), None);
+    assert_eq!(helper.test_runner.get_metadata(pool_unit, "name"), None);
+    assert_eq!(helper.test_runner.get_metadata(pool_unit, "icon_url"), None);
+
+    helper.contribute(dec!(100)).expect_commit_success();
+
+    assert_eq!(
+        helper.get_balance(helper.pool_unit_resource_address),
+        dec!(100)
+    );
+}
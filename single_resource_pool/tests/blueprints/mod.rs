@@ -0,0 +1,4 @@
+pub mod fees;
+pub mod metadata;
+pub mod pool;
+pub mod yield_strategy;
@@ -0,0 +1,120 @@
+use crate::helpers::init::*;
+use radix_engine_interface::prelude::*;
+
+#[test]
+fn test_pool_unit_ratio() {
+    let mut helper = PoolTestHelper::new(no_fee_config());
+
+    assert_eq!(helper.get_pool_unit_ratio(), pdec!(1));
+
+    // The first contribution mints units 1:1
+    helper.contribute(dec!(100)).expect_commit_success();
+
+    assert_eq!(
+        helper.get_balance(helper.pool_unit_resource_address),
+        dec!(100)
+    );
+    assert_eq!(helper.get_pool_unit_ratio(), pdec!(1));
+
+    // Liquidity supplied without minting units is shared by the unit holders
+    helper
+        .call("increase_external_liquidity", manifest_args!(dec!(100)))
+        .expect_commit_success();
+
+    let ratio = helper.get_pool_unit_ratio();
+    assert!(ratio > pdec!("0.49") && ratio < pdec!("0.51"));
+
+    helper.contribute(dec!(100)).expect_commit_success();
+
+    let units = helper.get_balance(helper.pool_unit_resource_address);
+    assert!(units > dec!(149) && units < dec!(151));
+}
+
+#[test]
+fn test_pool_unit_ratio_with_empty_pool() {
+    let mut helper = PoolTestHelper::new(no_fee_config());
+
+    // Virtual units keep the ratio finite and positive when the pool has no units
+    helper
+        .call("increase_external_liquidity", manifest_args!(dec!(100)))
+        .expect_commit_success();
+
+    let ratio = helper.get_pool_unit_ratio();
+    assert!(ratio > pdec!(0) && ratio < pdec!("0.000001"));
+
+    // Contributions still mint units
+    helper.contribute(dec!(100)).expect_commit_success();
+
+    assert!(helper.get_balance(helper.pool_unit_resource_address) > dec!(0));
+}
+
+#[test]
+fn test_pool_min_amounts() {
+    let mut helper = PoolTestHelper::new(no_fee_config());
+
+    helper
+        .call("update_min_amounts", manifest_args!(dec!(-1), dec!(5)))
+        .expect_commit_failure();
+
+    helper
+        .call("update_min_amounts", manifest_args!(dec!(10), dec!(-1)))
+        .expect_commit_failure();
+
+    helper
+        .call("update_min_amounts", manifest_args!(dec!(10), dec!(5)))
+        .expect_commit_success();
+
+    helper.contribute(dec!(5)).expect_commit_failure();
+    helper.contribute(dec!(10)).expect_commit_success();
+
+    helper.redeem(dec!(4)).expect_commit_failure();
+    helper.redeem(dec!(5)).expect_commit_success();
+
+    // Protocol redemptions ignore the minimum
+    let balance = helper.get_balance(XRD);
+
+    helper.protected_redeem(dec!(1)).expect_commit_success();
+
+    assert!(helper.get_balance(XRD) > balance);
+    assert_eq!(
+        helper.get_balance(helper.pool_unit_resource_address),
+        dec!(4)
+    );
+}
+
+#[test]
+fn test_pool_redemption_rounding() {
+    let mut helper = PoolTestHelper::new(no_fee_config());
+
+    // Raise the ratio above 1 unit per asset by supplying then removing external liquidity
+    helper.contribute(dec!(100)).expect_commit_success();
+
+    helper
+        .call("increase_external_liquidity", manifest_args!(dec!(100)))
+        .expect_commit_success();
+
+    helper.contribute(dec!(100)).expect_commit_success();
+
+    helper
+        .call("decrease_external_liquidity", manifest_args!(dec!(100)))
+        .expect_commit_success();
+
+    assert!(helper.get_pool_unit_ratio() > pdec!(1));
+
+    let units = helper.get_balance(helper.pool_unit_resource_address);
+
+    // Dust units are worth nothing after rounding, user redemptions are rejected
+    helper
+        .redeem(dec!("0.000000000000000001"))
+        .expect_commit_failure();
+
+    // Protocol redemptions burn them for an empty bucket
+    helper
+        .protected_redeem(dec!("0.000000000000000001"))
+        .expect_commit_success();
+
+    assert_eq!(
+        helper.get_balance(helper.pool_unit_resource_address),
+        units - dec!("0.000000000000000001")
+    );
+}
@@ -0,0 +1,125 @@
+use crate::helpers::init::*;
+use radix_engine_interface::prelude::*;
+use single_asset_pool::*;
+
+// 100 XRD pool with half of the liquidity allowed in the mock yield strategy
+fn setup_strategy(helper: &mut PoolTestHelper) -> ComponentAddress {
+    helper.contribute(dec!(100)).expect_commit_success();
+
+    let strategy_address = helper.create_mock_yield_strategy();
+
+    helper
+        .call(
+            "update_yield_strategy",
+            manifest_args!(Some(strategy_address), dec!("0.5")),
+        )
+        .expect_commit_success();
+
+    strategy_address
+}
+
+fn manage_yield_strategy(
+    helper: &mut PoolTestHelper,
+    action: YieldStrategyAction,
+) -> TransactionReceipt {
+    helper.call("manage_yield_strategy", manifest_args!(action))
+}
+
+#[test]
+fn test_yield_strategy_allocate_and_recall() {
+    let mut helper = PoolTestHelper::new(no_fee_config());
+
+    setup_strategy(&mut helper);
+
+    // Allocations are capped by the max strategy share
+    manage_yield_strategy(&mut helper, YieldStrategyAction::Allocate(dec!(60)))
+        .expect_commit_failure();
+
+    manage_yield_strategy(&mut helper, YieldStrategyAction::Allocate(dec!(50)))
+        .expect_commit_success();
+
+    // Strategy liquidity is reported apart from the liquidity available right away
+    assert_eq!(helper.get_pooled_amount(), (dec!(50), dec!(0), dec!(50)));
+    assert_eq!(helper.get_pool_unit_ratio(), pdec!(1));
+
+    manage_yield_strategy(&mut helper, YieldStrategyAction::Recall(dec!(60)))
+        .expect_commit_failure();
+
+    manage_yield_strategy(&mut helper, YieldStrategyAction::Recall(dec!(20)))
+        .expect_commit_success();
+
+    assert_eq!(helper.get_pooled_amount(), (dec!(70), dec!(0), dec!(30)));
+
+    // Redemptions recall the missing liquidity from the strategy
+    let balance = helper.get_balance(XRD);
+
+    helper.redeem(dec!(80)).expect_commit_success();
+
+    assert_eq!(helper.get_balance(XRD), balance + dec!(80));
+    assert_eq!(helper.get_pooled_amount(), (dec!(0), dec!(0), dec!(20)));
+
+    // Removing the strategy recalls the remaining liquidity
+    helper
+        .call(
+            "update_yield_strategy",
+            manifest_args!(None::<ComponentAddress>, dec!(0)),
+        )
+        .expect_commit_success();
+
+    assert_eq!(helper.get_pooled_amount(), (dec!(20), dec!(0), dec!(0)));
+}
+
+#[test]
+fn test_yield_strategy_gains_and_losses() {
+    let mut helper = PoolTestHelper::new(no_fee_config());
+
+    let strategy_address = setup_strategy(&mut helper);
+
+    manage_yield_strategy(&mut helper, YieldStrategyAction::Allocate(dec!(50)))
+        .expect_commit_success();
+
+    // The strategy earns 10 XRD, accounted on sync
+    let account_address = helper.account_address;
+
+    let manifest_builder = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .withdraw_from_account(account_address, XRD, dec!(10))
+        .take_all_from_worktop(XRD, "yield")
+        .with_name_lookup(|builder, lookup| {
+            let bucket = lookup.bucket("yield");
+
+            builder.call_method(strategy_address, "add_yield", manifest_args!(bucket))
+        });
+
+    helper.execute(manifest_builder).expect_commit_success();
+
+    assert_eq!(helper.get_pooled_amount(), (dec!(50), dec!(0), dec!(50)));
+
+    manage_yield_strategy(&mut helper, YieldStrategyAction::Sync).expect_commit_success();
+
+    assert_eq!(helper.get_pooled_amount(), (dec!(50), dec!(0), dec!(60)));
+
+    // The yield accrues to the pool unit holders
+    let ratio = helper.get_pool_unit_ratio();
+    assert!(ratio > pdec!("0.90") && ratio < pdec!("0.91"));
+
+    // The strategy loses 30 XRD, accounted on sync
+    let manifest_builder = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(strategy_address, "remove_funds", manifest_args!(dec!(30)))
+        .deposit_batch(account_address);
+
+    helper.execute(manifest_builder).expect_commit_success();
+
+    manage_yield_strategy(&mut helper, YieldStrategyAction::Sync).expect_commit_success();
+
+    assert_eq!(helper.get_pooled_amount(), (dec!(50), dec!(0), dec!(30)));
+
+    // All units are redeemed for the remaining liquidity
+    let balance = helper.get_balance(XRD);
+
+    helper.redeem(dec!(100)).expect_commit_success();
+
+    let redeemed_amount = helper.get_balance(XRD) - balance;
+    assert!(redeemed_amount > dec!("79.99") && redeemed_amount <= dec!(80));
+}
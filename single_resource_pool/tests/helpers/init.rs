@@ -0,0 +1,193 @@
+use radix_engine::vm::NoExtension;
+use radix_engine_interface::prelude::*;
+use scrypto_test::prelude::*;
+use scrypto_unit::*;
+use single_asset_pool::*;
+use std::path::Path;
+
+pub struct PoolTestHelper {
+    pub test_runner: TestRunner<NoExtension, InMemorySubstateDatabase>,
+    pub package_address: PackageAddress,
+    pub public_key: Secp256k1PublicKey,
+    pub account_address: ComponentAddress,
+    pub pool_resource_address: ResourceAddress,
+    pub pool_component_address: ComponentAddress,
+    pub pool_unit_resource_address: ResourceAddress,
+}
+
+impl PoolTestHelper {
+    // XRD pool administrated by the helper account, anyone can contribute and redeem
+    pub fn new(fee_config: PoolFeeConfig) -> PoolTestHelper {
+        PoolTestHelper::new_with_resource(fee_config, false)
+    }
+
+    // Pool of XRD or of a new resource without metadata held by the helper account
+    pub fn new_with_resource(fee_config: PoolFeeConfig, new_resource: bool) -> PoolTestHelper {
+        let mut test_runner = TestRunnerBuilder::new().build();
+
+        let (public_key, _, account_address) = test_runner.new_allocated_account();
+
+        test_runner.load_account_from_faucet(account_address);
+
+        let pool_resource_address = if new_resource {
+            test_runner.create_fungible_resource(dec!(1_000), 18, account_address)
+        } else {
+            XRD
+        };
+
+        let package_address = test_runner.compile_and_publish(this_package!());
+
+        let admin_rule = rule!(require(NonFungibleGlobalId::from_public_key(&public_key)));
+
+        let manifest = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_function(
+                package_address,
+                "SingleResourcePool",
+                "instantiate",
+                manifest_args!(
+                    pool_resource_address,
+                    OwnerRole::None,
+                    admin_rule,
+                    rule!(allow_all),
+                    rule!(allow_all),
+                    fee_config,
+                    None::<ComponentAddress>
+                ),
+            )
+            .build();
+
+        let receipt = test_runner.execute_manifest(
+            manifest,
+            vec![NonFungibleGlobalId::from_public_key(&public_key)],
+        );
+        println!("{:?}\n", receipt);
+        let result = receipt.expect_commit_success();
+
+        let pool_component_address = result.new_component_addresses()[0];
+        let pool_unit_resource_address = result.new_resource_addresses()[0];
+
+        PoolTestHelper {
+            test_runner,
+            package_address,
+            public_key,
+            account_address,
+            pool_resource_address,
+            pool_component_address,
+            pool_unit_resource_address,
+        }
+    }
+
+    pub fn execute(&mut self, manifest_builder: ManifestBuilder) -> TransactionReceipt {
+        let receipt = self.test_runner.execute_manifest(
+            manifest_builder.build(),
+            vec![NonFungibleGlobalId::from_public_key(&self.public_key)],
+        );
+
+        println!("{:?}\n", receipt);
+
+        receipt
+    }
+
+    // Call a pool method without bucket arguments and deposit the returned resources
+    pub fn call(&mut self, method_name: &str, args: ManifestArgs) -> TransactionReceipt {
+        let manifest_builder = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_method(self.pool_component_address, method_name, args)
+            .deposit_batch(self.account_address);
+
+        self.execute(manifest_builder)
+    }
+
+    // Call a pool method with a bucket of the given resource and deposit the returned resources
+    pub fn call_with_bucket(
+        &mut self,
+        method_name: &str,
+        resource_address: ResourceAddress,
+        amount: Decimal,
+    ) -> TransactionReceipt {
+        let pool_component_address = self.pool_component_address;
+
+        let manifest_builder = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .withdraw_from_account(self.account_address, resource_address, amount)
+            .take_all_from_worktop(resource_address, "bucket")
+            .with_name_lookup(|builder, lookup| {
+                let bucket = lookup.bucket("bucket");
+
+                builder.call_method(pool_component_address, method_name, manifest_args!(bucket))
+            })
+            .deposit_batch(self.account_address);
+
+        self.execute(manifest_builder)
+    }
+
+    pub fn contribute(&mut self, amount: Decimal) -> TransactionReceipt {
+        let pool_resource_address = self.pool_resource_address;
+
+        self.call_with_bucket("contribute", pool_resource_address, amount)
+    }
+
+    pub fn redeem(&mut self, units: Decimal) -> TransactionReceipt {
+        let pool_unit_resource_address = self.pool_unit_resource_address;
+
+        self.call_with_bucket("redeem", pool_unit_resource_address, units)
+    }
+
+    pub fn protected_redeem(&mut self, units: Decimal) -> TransactionReceipt {
+        let pool_unit_resource_address = self.pool_unit_resource_address;
+
+        self.call_with_bucket("protected_redeem", pool_unit_resource_address, units)
+    }
+
+    pub fn queued_redeem(&mut self, units: Decimal) -> TransactionReceipt {
+        let pool_unit_resource_address = self.pool_unit_resource_address;
+
+        self.call_with_bucket("queued_redeem", pool_unit_resource_address, units)
+    }
+
+    pub fn get_pool_unit_ratio(&mut self) -> PreciseDecimal {
+        self.call("get_pool_unit_ratio", manifest_args!())
+            .expect_commit_success()
+            .output::<PreciseDecimal>(1)
+    }
+
+    pub fn get_pooled_amount(&mut self) -> (Decimal, Decimal, Decimal) {
+        self.call("get_pooled_amount", manifest_args!())
+            .expect_commit_success()
+            .output::<(Decimal, Decimal, Decimal)>(1)
+    }
+
+    // Mock yield strategy holding the pool resource, only the pool can deposit and withdraw
+    pub fn create_mock_yield_strategy(&mut self) -> ComponentAddress {
+        let mock_package_address = self
+            .test_runner
+            .compile_and_publish(Path::new("../mock_yield_strategy"));
+
+        let pool_rule = rule!(require(global_caller(self.pool_component_address)));
+
+        let manifest_builder = ManifestBuilder::new().lock_fee_from_faucet().call_function(
+            mock_package_address,
+            "MockYieldStrategy",
+            "instantiate",
+            manifest_args!(self.pool_resource_address, pool_rule),
+        );
+
+        self.execute(manifest_builder)
+            .expect_commit_success()
+            .new_component_addresses()[0]
+    }
+
+    pub fn get_balance(&mut self, resource_address: ResourceAddress) -> Decimal {
+        self.test_runner
+            .get_component_balance(self.account_address, resource_address)
+    }
+}
+
+pub fn no_fee_config() -> PoolFeeConfig {
+    PoolFeeConfig {
+        contribution_fee_rate: dec!(0),
+        redemption_fee_rate: dec!(0),
+        fee_destination: PoolFeeDestination::Pool,
+    }
+}